@@ -0,0 +1,103 @@
+//! Peak memory of reading and rewriting a large JSON file, with the document read
+//! as a string and with it streamed (`LocatedJsonStore::new_streaming`).
+//!
+//! `cargo bench --bench json_memory --features fs`
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use serde_json::{json, Value};
+
+use anystore::{
+    store::StoreEx,
+    stores::{
+        fs::FileSystemStore,
+        located::json::{JsonFormat, LocatedJsonStore},
+    },
+};
+
+/// Counts the allocated bytes, and the most of them at once.
+struct PeakAlloc;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for PeakAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::SeqCst) + layout.size();
+            PEAK.fetch_max(current, Ordering::SeqCst);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::SeqCst);
+    }
+}
+
+#[global_allocator]
+static ALLOC: PeakAlloc = PeakAlloc;
+
+/// The most bytes allocated at once while `f` runs, over what was allocated before.
+async fn peak<F: std::future::Future>(f: F) -> usize {
+    let before = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(before, Ordering::SeqCst);
+
+    f.await;
+
+    PEAK.load(Ordering::SeqCst) - before
+}
+
+fn main() -> Result<(), anyhow::Error> {
+    let rt = tokio::runtime::Builder::new_current_thread().build()?;
+
+    rt.block_on(async {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        tokio::fs::create_dir(&dir).await?;
+        let fs = FileSystemStore::new(dir.clone());
+
+        let items = (0..200_000)
+            .map(|i| json!({"id": i, "name": format!("item {i}"), "tags": ["a", "b"]}))
+            .collect::<Vec<_>>();
+        tokio::fs::write(dir.join("doc.json"), serde_json::to_vec(&items)?).await?;
+        drop(items);
+
+        let size = tokio::fs::metadata(dir.join("doc.json")).await?.len();
+        println!("file: {} KiB", size / 1024);
+
+        let string = LocatedJsonStore::new(fs.path("doc.json")?);
+        let streamed = LocatedJsonStore::new_streaming(fs.path("doc.json")?, JsonFormat::default());
+
+        for (name, store) in [("string", &string), ("streamed", &streamed)] {
+            let read = peak(async {
+                let _: Option<Value> = store.path("[0]").unwrap().getv().await.unwrap();
+            })
+            .await;
+
+            let write = peak(async {
+                store
+                    .path("[0].name")
+                    .unwrap()
+                    .setv(&Some(json!("x")))
+                    .await
+                    .unwrap();
+            })
+            .await;
+
+            println!(
+                "{name:>8}: read {} KiB, read-modify-write {} KiB",
+                read / 1024,
+                write / 1024
+            );
+        }
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    })
+}
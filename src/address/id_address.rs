@@ -0,0 +1,64 @@
+/// Implements [`Address`](crate::address::Address) for a struct that is addressed by one of
+/// its fields, e.g. an id: `own_name` is that field's `to_string()`, and `as_parts` is just it,
+/// or, with `{ field in parent }`, appended to the `as_parts` of the `parent` field.
+///
+/// With `sub of Parent`, also implements [`SubAddress<Self>`](crate::address::SubAddress) for `Parent`,
+/// for the addresses that already know where they are: `parent.sub(child)` is just `child`.
+///
+/// The struct still needs to derive the `Address` supertraits itself (`Eq`, `Clone`, `Debug`).
+///
+/// ```
+/// use anystore::address::{Address, SubAddress};
+/// use anystore::id_address;
+///
+/// #[derive(Clone, PartialEq, Eq, Debug)]
+/// struct Team {
+///     name: String,
+/// }
+///
+/// #[derive(Clone, PartialEq, Eq, Debug)]
+/// struct Member<Id> {
+///     id: Id,
+///     team: Team,
+/// }
+///
+/// id_address! { impl[] Team { name } }
+/// id_address! { impl[Id: ToString + Eq + Clone + std::fmt::Debug + 'static] Member<Id> { id in team } sub of Team }
+///
+/// let team = Team { name: "core".to_owned() };
+/// let member = team.clone().sub(Member { id: 42, team });
+///
+/// assert_eq!(member.own_name(), "42");
+/// assert_eq!(member.as_parts(), vec!["core", "42"]);
+/// ```
+#[macro_export]
+macro_rules! id_address {
+    (
+        impl[$($g:tt)*] $a:ty { $id:tt $(in $parent:tt)? } $(sub of $p:ty)?
+    ) => {
+        impl<$($g)*> $crate::address::Address for $a {
+            fn own_name(&self) -> String {
+                ::std::string::ToString::to_string(&self.$id)
+            }
+
+            fn as_parts(&self) -> Vec<String> {
+                let parts = vec![$crate::address::Address::own_name(self)];
+                $(let parts = [$crate::address::Address::as_parts(&self.$parent), parts].concat();)?
+                parts
+            }
+        }
+
+        $crate::id_address!(@sub [$($g)*] [$a] [$($p)?]);
+    };
+
+    (@sub $g:tt $a:tt []) => {};
+    (@sub [$($g:tt)*] [$a:ty] [$p:ty]) => {
+        impl<$($g)*> $crate::address::SubAddress<$a> for $p {
+            type Output = $a;
+
+            fn sub(self, sub: $a) -> $a {
+                sub
+            }
+        }
+    };
+}
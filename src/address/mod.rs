@@ -2,9 +2,13 @@ use std::fmt::Debug;
 
 use crate::store::Store;
 
+mod id_address;
 pub mod primitive;
+mod string_key;
 pub mod traits;
 
+pub use string_key::StringKey;
+
 /// Must be a syntactically valid address: successfully parsed, but not yet validated.
 ///
 /// You will want to implement [`SubAddress<NextPart>`](SubAddress) for this if your store supports several layers of indirection,
@@ -19,9 +23,17 @@ pub mod traits;
 // }
 
 pub trait Address: Eq + Clone + Debug + 'static {
-    /// This should be an addressable, unique id in the container (not "own name")
+    /// This should be an addressable, unique id in the container (not "own name"):
+    /// it's used as a key, so it's escaped as needed to keep it unique.
     fn own_name(&self) -> String;
 
+    /// A label to show to people, e.g. in a UI. Not necessarily unique, and not for addressing.
+    ///
+    /// Defaults to [`own_name`](Address::own_name).
+    fn display_name(&self) -> String {
+        self.own_name()
+    }
+
     /// The whole path
     fn as_parts(&self) -> Vec<String>;
 }
@@ -37,6 +49,15 @@ pub trait PathAddress: Address {
     fn path(self, str: &str) -> Result<Self::Output, Self::Error>;
 }
 
+/// Addresses that can be navigated relatively: see [`Location::resolve`](crate::location::Location::resolve).
+pub trait ResolveAddress: Address {
+    type Error;
+
+    /// `relative` is a `/`-separated list of parts, like `../sibling/file`: `.` stays,
+    /// `..` goes to the parent, and anything else goes down. It's an error to go above the root.
+    fn resolve(self, relative: &str) -> Result<Self, Self::Error>;
+}
+
 pub trait SubAddress<Sub>: Address {
     type Output: Address;
 
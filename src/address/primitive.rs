@@ -43,6 +43,22 @@ impl<A: Address> SubAddress<A> for UniqueRootAddress {
 #[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
 pub struct Existence;
 
+/// Presence as a first-class value: `Some(Present(true))` or `Some(Present(false))`.
+///
+/// Unlike [`Existence`], reading this is never `None`, so generic code that only
+/// deals with values can treat it like any other value.
+/// It's not a stored boolean: stores implement it as a view over `Existence`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default)]
+pub struct Present(pub bool);
+
+/// The entire content of a store, as one value: `store.root().get::<WholeStore<Value>>()`.
+///
+/// Only implemented by stores where the whole content is small and well-defined
+/// (memory cells, JSON documents, vectors), so asking it from e.g. a file system
+/// is a compile error.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WholeStore<V>(pub V);
+
 // #[derive(PartialEq, Eq, Clone, Default, Debug)]
 // pub struct ListOfAddresses<BaseAddr: Address + SubAddress<SubAddr>, SubAddr: Clone> {
 //     pub base: BaseAddr,
@@ -0,0 +1,114 @@
+use std::fmt::Display;
+
+use super::{primitive::UniqueRootAddress, Address, PathAddress, SubAddress};
+
+/// A string as an address, for simple stores keyed by strings (maps, key-value databases, etc.).
+///
+/// The parts of the key are separated by `SEP` (`/` by default): `path` splits on it,
+/// and `sub` joins with it. Empty parts are skipped, so `"a//b"` and `"a/b"` have the same parts.
+/// The root is the empty string.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Default)]
+pub struct StringKey<const SEP: char = '/'>(pub String);
+
+impl<const SEP: char> StringKey<SEP> {
+    fn join(mut self, rest: &str) -> Self {
+        for part in rest.split(SEP).filter(|p| !p.is_empty()) {
+            if !self.0.is_empty() {
+                self.0.push(SEP);
+            }
+            self.0.push_str(part);
+        }
+
+        self
+    }
+}
+
+impl<const SEP: char> Display for StringKey<SEP> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl<const SEP: char> From<&str> for StringKey<SEP> {
+    fn from(value: &str) -> Self {
+        StringKey(value.to_owned())
+    }
+}
+
+impl<const SEP: char> From<String> for StringKey<SEP> {
+    fn from(value: String) -> Self {
+        StringKey(value)
+    }
+}
+
+impl<const SEP: char> From<UniqueRootAddress> for StringKey<SEP> {
+    fn from(_value: UniqueRootAddress) -> Self {
+        StringKey(String::new())
+    }
+}
+
+impl<const SEP: char> Address for StringKey<SEP> {
+    fn own_name(&self) -> String {
+        self.as_parts().pop().unwrap_or_default()
+    }
+
+    fn as_parts(&self) -> Vec<String> {
+        self.0
+            .split(SEP)
+            .filter(|p| !p.is_empty())
+            .map(str::to_owned)
+            .collect()
+    }
+}
+
+impl<const SEP: char> PathAddress for StringKey<SEP> {
+    type Error = !;
+
+    type Output = StringKey<SEP>;
+
+    fn path(self, str: &str) -> Result<Self::Output, Self::Error> {
+        Ok(self.join(str))
+    }
+}
+
+impl<const SEP: char> SubAddress<StringKey<SEP>> for StringKey<SEP> {
+    type Output = StringKey<SEP>;
+
+    fn sub(self, sub: StringKey<SEP>) -> Self::Output {
+        self.join(&sub.0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::address::{primitive::UniqueRootAddress, Address, PathAddress, SubAddress};
+
+    use super::StringKey;
+
+    #[test]
+    fn test_path() {
+        let root: StringKey = UniqueRootAddress.into();
+        assert_eq!(root.as_parts(), Vec::<String>::new());
+        assert_eq!(root.own_name(), "");
+
+        let key = root.path("users//alice/").unwrap();
+        assert_eq!(key, StringKey("users/alice".to_owned()));
+        assert_eq!(key.as_parts(), vec!["users", "alice"]);
+        assert_eq!(key.own_name(), "alice");
+
+        let key = key.path("settings/theme").unwrap();
+        assert_eq!(key.to_string(), "users/alice/settings/theme");
+    }
+
+    #[test]
+    fn test_sub() {
+        let users = StringKey::<'/'>::from("users");
+        assert_eq!(users.clone().sub("bob".into()).to_string(), "users/bob");
+        assert_eq!(users.sub(StringKey::default()).to_string(), "users");
+
+        let key = StringKey::<':'>::from("cache").sub("user:1".into());
+        assert_eq!(key.to_string(), "cache:user:1");
+        assert_eq!(key.as_parts(), vec!["cache", "user", "1"]);
+        assert_eq!(key.path("a/b").unwrap().own_name(), "a/b");
+    }
+}
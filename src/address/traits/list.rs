@@ -1,3 +1,7 @@
+use std::pin::pin;
+
+use futures::TryStreamExt;
+
 use super::*;
 
 pub trait AddressableList<
@@ -22,6 +26,32 @@ pub trait AddressableList<
     fn list(&self, addr: &ListAddr) -> Self::ListOfAddressesStream;
 }
 
+/// Looking up a child of a list by its name. The default goes through the listing
+/// until an item's [`own_name`](Address::own_name) matches;
+/// stores that can address the child directly override it.
+pub trait AddressableChild<
+    'a,
+    ListAddr: Address + SubAddress<Self::AddedAddress, Output = Self::ItemAddress>,
+>: AddressableList<'a, ListAddr>
+{
+    /// The address of the item called `name` in the list, if it exists.
+    async fn child(
+        &self,
+        addr: &ListAddr,
+        name: &str,
+    ) -> StoreResult<Option<Self::ItemAddress>, Self> {
+        let mut items = pin!(self.list(addr));
+
+        while let Some((_, item)) = items.try_next().await? {
+            if item.own_name() == name {
+                return Ok(Some(item));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
 pub trait AddressableInsert<
     'a,
     Value,
@@ -45,3 +75,28 @@ pub trait AddressableQuery<
     /// it's better not to implement this trait, but use wrappers.
     fn query(&self, addr: &ListAddr, query: Query) -> Self::ListOfAddressesStream;
 }
+
+/// The default [`AddressableListFrom::ListFromStream`]: the items come with
+/// the cursor that resumes the listing after them.
+pub type ListFromBoxStream<'a, Cursor, Added, Item, S> =
+    Pin<Box<dyn 'a + Stream<Item = StoreResult<(Cursor, Added, Item), S>>>>;
+
+/// Listings that can be checkpointed and resumed later, e.g. across restarts.
+pub trait AddressableListFrom<
+    'a,
+    ListAddr: Address + SubAddress<Self::AddedAddress, Output = Self::ItemAddress>,
+>: AddressableList<'a, ListAddr>
+{
+    /// Opaque position in a listing. Network stores wrap their page token,
+    /// in-memory stores use the index of the next item.
+    type ListCursor: Clone + 'static;
+
+    type ListFromStream: 'a
+        + Stream<Item = StoreResult<(Self::ListCursor, Self::AddedAddress, Self::ItemAddress), Self>> =
+        ListFromBoxStream<'a, Self::ListCursor, Self::AddedAddress, Self::ItemAddress, Self>;
+
+    /// Lists starting at `cursor`, or from the beginning if it's `None`.
+    ///
+    /// Every item comes with the cursor that resumes the listing right after it.
+    fn list_from(&self, addr: &ListAddr, cursor: Option<Self::ListCursor>) -> Self::ListFromStream;
+}
@@ -2,7 +2,7 @@ use std::pin::Pin;
 
 use futures::Stream;
 
-use crate::store::StoreResult;
+use crate::{address::primitive::Existence, store::StoreResult};
 
 pub use super::{Address, Addressable, SubAddress};
 
@@ -19,3 +19,67 @@ pub trait AddressableGet<Value, A: Address>: Addressable<A> {
 pub trait AddressableSet<Value, A: Address>: Addressable<A> {
     async fn set_addr(&self, addr: &A, value: &Option<Value>) -> StoreResult<(), Self>;
 }
+
+/// Optimistic concurrency: "write X only if the value is still Y".
+pub trait AddressableCas<Value, A: Address>: Addressable<A> {
+    /// Write `new` only if the current value equals `expected` (`None` for no value).
+    /// Returns whether it was written.
+    async fn compare_and_swap(
+        &self,
+        addr: &A,
+        expected: &Option<Value>,
+        new: &Option<Value>,
+    ) -> StoreResult<bool, Self>;
+}
+
+/// Overwrite a value and get the previous one back.
+pub trait AddressableSwap<Value, A: Address>: Addressable<A> {
+    /// Write `value` (`None` to remove it), and return the value that was there before.
+    async fn swap(&self, addr: &A, value: &Option<Value>) -> StoreResult<Option<Value>, Self>;
+}
+
+/// Check many addresses at once. The default checks them one by one;
+/// stores that can do better in one round trip override it.
+pub trait AddressableExistsMany<A: Address>: AddressableGet<Existence, A> {
+    /// Whether something exists at each of `addrs`, in the same order.
+    async fn exists_many(&self, addrs: &[A]) -> StoreResult<Vec<bool>, Self> {
+        let mut result = Vec::with_capacity(addrs.len());
+
+        for addr in addrs {
+            result.push(self.addr_get(addr).await?.is_some());
+        }
+
+        Ok(result)
+    }
+}
+
+/// Stores that can remove values.
+pub trait AddressableDelete<A: Address>: Addressable<A> {
+    /// Remove the value at `addr`. Removing a value that doesn't exist isn't an error.
+    async fn delete(&self, addr: &A) -> StoreResult<(), Self>;
+}
+
+/// Stores with containers (directories, objects) that can be created empty.
+pub trait AddressableBranch<A: Address>: Addressable<A> {
+    /// Create an empty container at `addr` if there's nothing there.
+    /// It's an error if a non-container value is there.
+    async fn ensure_branch(&self, addr: &A) -> StoreResult<(), Self>;
+}
+
+/// Stores that can hand out a reader over a value instead of materializing it,
+/// e.g. to stream large files.
+pub trait AddressableReadStream<A: Address>: Addressable<A> {
+    type Reader: tokio::io::AsyncRead + Unpin;
+
+    /// `None` means that the value doesn't exist.
+    async fn read_stream(&self, addr: &A) -> StoreResult<Option<Self::Reader>, Self>;
+}
+
+/// Stores that can hand out a writer to replace a value without materializing it first.
+pub trait AddressableWriteStream<A: Address>: Addressable<A> {
+    type Writer: tokio::io::AsyncWrite + Unpin;
+
+    /// What's written replaces the previous value. Whether the previous value is gone
+    /// as soon as this is called, or only once the writer is shut down, depends on the store.
+    async fn write_stream(&self, addr: &A) -> StoreResult<Self::Writer, Self>;
+}
@@ -1,4 +1,5 @@
 use derive_more::Display;
+use futures::{future, stream::LocalBoxStream, Stream, StreamExt, TryStreamExt};
 
 use crate::store::StoreResult;
 
@@ -17,6 +18,66 @@ impl<B, L> BranchOrLeaf<B, L> {
             BranchOrLeaf::Leaf(_) => BranchOrLeaf::Leaf(()),
         }
     }
+
+    pub fn as_branch(&self) -> Option<&B> {
+        match self {
+            BranchOrLeaf::Branch(b) => Some(b),
+            BranchOrLeaf::Leaf(_) => None,
+        }
+    }
+
+    pub fn as_leaf(&self) -> Option<&L> {
+        match self {
+            BranchOrLeaf::Branch(_) => None,
+            BranchOrLeaf::Leaf(l) => Some(l),
+        }
+    }
+
+    pub fn into_branch(self) -> Option<B> {
+        match self {
+            BranchOrLeaf::Branch(b) => Some(b),
+            BranchOrLeaf::Leaf(_) => None,
+        }
+    }
+
+    pub fn into_leaf(self) -> Option<L> {
+        match self {
+            BranchOrLeaf::Branch(_) => None,
+            BranchOrLeaf::Leaf(l) => Some(l),
+        }
+    }
+}
+
+/// Helpers for the results of
+/// [`walk_tree_recursively`](crate::location::Location::walk_tree_recursively).
+pub trait BranchOrLeafStreamExt<B, L, E>:
+    Stream<Item = Result<BranchOrLeaf<B, L>, E>> + Sized
+{
+    fn leaves_only(self) -> impl Stream<Item = Result<L, E>> {
+        self.try_filter_map(|bl| future::ok(bl.into_leaf()))
+    }
+
+    fn branches_only(self) -> impl Stream<Item = Result<B, E>> {
+        self.try_filter_map(|bl| future::ok(bl.into_branch()))
+    }
+
+    /// Collect into `(branches, leaves)`.
+    async fn partition(self) -> Result<(Vec<B>, Vec<L>), E> {
+        self.try_fold((vec![], vec![]), |(mut branches, mut leaves), bl| {
+            match bl {
+                BranchOrLeaf::Branch(b) => branches.push(b),
+                BranchOrLeaf::Leaf(l) => leaves.push(l),
+            }
+
+            future::ok((branches, leaves))
+        })
+        .await
+    }
+}
+
+impl<B, L, E, S: Stream<Item = Result<BranchOrLeaf<B, L>, E>>> BranchOrLeafStreamExt<B, L, E>
+    for S
+{
 }
 
 pub trait AddressableTree<'a, TreeAddr, ItemAddr>:
@@ -28,6 +89,28 @@ where
         &self,
         addr: TreeAddr,
     ) -> StoreResult<BranchOrLeaf<TreeAddr, ItemAddr>, Self>;
+
+    /// The items of `addr`, each as a branch or a leaf. The walks use this.
+    ///
+    /// The default lists `addr` and calls `branch_or_leaf` for every item. Stores whose listing
+    /// already knows the kinds of the items (e.g. from directory entries) override it to skip that.
+    fn list_classified(
+        &self,
+        addr: &TreeAddr,
+    ) -> LocalBoxStream<'a, StoreResult<BranchOrLeaf<TreeAddr, ItemAddr>, Self>>
+    where
+        Self: 'a,
+        ItemAddr: 'a,
+    {
+        let this = self.clone();
+
+        self.list(addr)
+            .and_then(move |(_, item)| {
+                let this = this.clone();
+                async move { this.branch_or_leaf(item).await }
+            })
+            .boxed_local()
+    }
 }
 
 #[cfg(test)]
@@ -39,8 +122,13 @@ mod test {
     use serde_json::json;
 
     use crate::{
+        address::{
+            traits::{AddressableList, BranchOrLeaf, BranchOrLeafStreamExt},
+            Addressable,
+        },
         store::*,
         stores::json::{paths::JsonPath, *},
+        test_util::FlakyStore,
         wrappers::filter_addresses::FilterAddressesWrapperStore,
     };
 
@@ -77,4 +165,151 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_partition() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": {"b": 1, "c": [2]}, "d": 3}))?;
+        let root = store.root();
+
+        let leaves = root
+            .walk_tree_recursively()
+            .leaves_only()
+            .map_ok(|l: JsonPath| l.to_string())
+            .try_collect::<HashSet<_>>()
+            .await?;
+        assert_eq!(
+            leaves,
+            HashSet::from(["a.b".to_owned(), "a.c[0]".to_owned(), "d".to_owned()])
+        );
+
+        let branches = root
+            .walk_tree_recursively::<JsonPath>()
+            .branches_only()
+            .map_ok(|b| b.to_string())
+            .try_collect::<HashSet<_>>()
+            .await?;
+        assert_eq!(branches, HashSet::from(["a".to_owned(), "a.c".to_owned()]));
+
+        let (branches, leaves) = root.walk_tree_recursively::<JsonPath>().partition().await?;
+        assert_eq!((branches.len(), leaves.len()), (2, 3));
+
+        let bl = BranchOrLeaf::<u8, &str>::Leaf("x");
+        assert_eq!(bl.as_leaf(), Some(&"x"));
+        assert_eq!(bl.as_branch(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_count_recursive() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({
+            "wow": {"hello": "yes"},
+            "another": {"seriously": {"throrougly": 7}, "basic": [1, 2, 3, {"hello": "_why"}, {"_why": "ya"}]},
+            "_ignore": {"haha": {"_yes": 3}}
+        }))?;
+
+        // wow.hello, another.seriously.throrougly, 3 numbers in basic, 2 keys in its objects, _ignore.haha._yes
+        assert_eq!(store.root().count_recursive::<JsonPath>(false).await?, 8);
+        // wow, another, another.seriously, another.basic, basic[3], basic[4], _ignore, _ignore.haha
+        assert_eq!(store.root().count_recursive::<JsonPath>(true).await?, 16);
+        assert_eq!(
+            store
+                .path("another")?
+                .count_recursive::<JsonPath>(false)
+                .await?,
+            6
+        );
+
+        Ok(())
+    }
+
+    /// Lists like the JSON store, but every item twice.
+    #[derive(Clone)]
+    struct DuplicatingStore {
+        inner: JsonValueStore,
+    }
+
+    impl Store for DuplicatingStore {
+        type Error = JsonValueStoreError;
+
+        type RootAddress = JsonPath;
+    }
+
+    impl Addressable<JsonPath> for DuplicatingStore {
+        type DefaultValue = serde_json::Value;
+    }
+
+    impl<'a> AddressableList<'a, JsonPath> for DuplicatingStore {
+        type AddedAddress = JsonPathPart;
+
+        type ItemAddress = JsonPath;
+
+        fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
+            self.inner
+                .list(addr)
+                .chain(self.inner.list(addr))
+                .boxed_local()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_unique() -> Result<(), anyhow::Error> {
+        let store = DuplicatingStore {
+            inner: json_value_store(json!({"a": 1, "b": 2}))?,
+        };
+
+        let names = |listing: Vec<(JsonPathPart, JsonPath)>| {
+            listing
+                .into_iter()
+                .map(|(_, p)| p.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(store.root().list().try_collect().await?),
+            vec!["a", "b", "a", "b"]
+        );
+        assert_eq!(
+            names(store.root().list_unique().try_collect().await?),
+            vec!["a", "b"]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lenient() -> Result<(), anyhow::Error> {
+        let store = FlakyStore::new(json_value_store(
+            json!({"bad": {"x": 1}, "flaky": {"y": 2}, "ok": {"z": 3}}),
+        )?);
+        store.fail_at("bad", usize::MAX);
+        store.fail_at("flaky", 1);
+
+        assert!(store
+            .root()
+            .walk_tree_recursively::<JsonPath>()
+            .try_collect::<Vec<_>>()
+            .await
+            .is_err());
+
+        let (ok, errors): (Vec<_>, Vec<_>) = store
+            .root()
+            .walk_tree_lenient::<JsonPath>(2)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .partition(|r| r.is_ok());
+
+        let paths = ok
+            .into_iter()
+            .map(|r| r.unwrap().to_string())
+            .collect::<HashSet<_>>();
+        assert_eq!(
+            paths,
+            HashSet::from(["bad", "flaky", "flaky.y", "ok", "ok.z"].map(|p| p.to_owned()))
+        );
+        assert_eq!(errors.len(), 1);
+
+        Ok(())
+    }
 }
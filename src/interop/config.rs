@@ -0,0 +1,125 @@
+use config::{ConfigError, Map, Source, Value, ValueKind};
+use futures::TryStreamExt;
+
+use crate::{
+    address::{
+        traits::{AddressableGet, AddressableTree},
+        SubAddress,
+    },
+    location::Location,
+    store::{Store, StoreResult},
+    stores::json::paths::JsonPath,
+};
+
+/// A JSON subtree as a [`config::Source`], so a store can be layered with the other
+/// sources of a [`config::Config`]. The keys are the dotted paths of the scalars under
+/// the location, see [`Location::flatten`].
+///
+/// `config` reads its sources synchronously, so the values are read once, when the source
+/// is created. Create it again to pick up the changes.
+///
+/// ```
+/// use serde_json::json;
+///
+/// use anystore::interop::config::LocationSource;
+/// use anystore::store::StoreEx;
+/// use anystore::stores::json::json_value_store;
+///
+/// # tokio_test::block_on(async {
+/// let store = json_value_store(json!({"app": {"db": {"port": 5432}}}))?;
+///
+/// let config = config::Config::builder()
+///     .set_default("db.host", "localhost")?
+///     .add_source(LocationSource::new(&store.path("app")?).await?)
+///     .build()?;
+///
+/// assert_eq!(config.get_int("db.port")?, 5432);
+/// assert_eq!(config.get_string("db.host")?, "localhost");
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+#[derive(Debug, Clone)]
+pub struct LocationSource {
+    values: Map<String, Value>,
+}
+
+impl LocationSource {
+    pub async fn new<'a, S>(location: &Location<JsonPath, S>) -> StoreResult<Self, S>
+    where
+        S: 'a
+            + Store
+            + AddressableTree<'a, JsonPath, JsonPath>
+            + AddressableGet<serde_json::Value, JsonPath>,
+        S::AddedAddress: std::fmt::Debug,
+        JsonPath: SubAddress<S::AddedAddress, Output = JsonPath>,
+    {
+        let origin = format!("anystore location `{}`", location.address);
+
+        let values = location
+            .flatten()
+            .map_ok(|(path, value)| (path.to_string(), config_value(&origin, value)))
+            .try_collect()
+            .await?;
+
+        Ok(LocationSource { values })
+    }
+}
+
+/// Only for the scalars: `flatten` doesn't yield the objects and arrays.
+fn config_value(origin: &String, value: serde_json::Value) -> Value {
+    let kind = match value {
+        serde_json::Value::Null => ValueKind::Nil,
+        serde_json::Value::Bool(b) => ValueKind::Boolean(b),
+        serde_json::Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => ValueKind::I64(i),
+            (None, Some(u)) => ValueKind::U64(u),
+            _ => ValueKind::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        serde_json::Value::String(s) => ValueKind::String(s),
+        v => ValueKind::String(v.to_string()),
+    };
+
+    Value::new(Some(origin), kind)
+}
+
+impl Source for LocationSource {
+    fn clone_into_box(&self) -> Box<dyn Source + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn collect(&self) -> Result<Map<String, Value>, ConfigError> {
+        Ok(self.values.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::{store::StoreEx, stores::json::json_value_store};
+
+    use super::LocationSource;
+
+    #[tokio::test]
+    async fn test_config() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({
+            "server": {"port": 8080, "debug": true, "hosts": ["a", "b"]},
+            "ratio": 0.5,
+        }))?;
+
+        let config = config::Config::builder()
+            .set_default("server.port", 80)?
+            .set_default("name", "default")?
+            .add_source(LocationSource::new(&store.root()).await?)
+            .build()?;
+
+        assert_eq!(config.get_int("server.port")?, 8080);
+        assert!(config.get_bool("server.debug")?);
+        assert_eq!(config.get_string("server.hosts[1]")?, "b");
+        assert_eq!(config.get_float("ratio")?, 0.5);
+        assert_eq!(config.get_string("name")?, "default");
+
+        Ok(())
+    }
+}
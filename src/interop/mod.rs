@@ -0,0 +1,4 @@
+//! Using stores with other crates.
+
+#[cfg(feature = "config")]
+pub mod config;
@@ -7,6 +7,7 @@
 #![feature(error_generic_member_access)]
 // #![feature(provide_any)]
 #![feature(doc_cfg)]
+#![feature(return_type_notation)]
 #![cfg_attr(all(doc, CHANNEL_NIGHTLY), feature(doc_auto_cfg))]
 
 //! # anystore
@@ -138,6 +139,7 @@
 //!
 //! The traits in this crate are designed to be easily composable without too much boilerplate. That allows
 //! the creation of abstract wrappers that add functionality to the existing stores or compose stores together.
+//! To write one, [`delegate_store!`] passes the traits it doesn't change to the wrapped store.
 //!
 //! # Table of contents
 //!
@@ -145,10 +147,20 @@
 //!
 //! Basic stores:
 //! - [`stores::fs::FileSystemStore`](stores::fs::FileSystemStore) file system as a store
+//! - [`stores::any::AnyStore`](stores::any::AnyStore) one of the stores, chosen at runtime by a config
 //!
 //! Wrappers:
 //! - [`stores::located::json::LocatedJsonStore`] -- use this over any `Location` to store JSON in it
 //! - [`wrappers::filter_addresses::FilterAddressesWrapperStore`] -- wrap this over a store to dynamically filter out addresses
+//! - [`wrappers::remap::MapKeyWrapperStore`] -- wrap this over a store to rename its keys transparently
+//! - [`wrappers::debounce::DebounceWrapperStore`] -- wrap this over a store to coalesce rapid writes
+//! - [`wrappers::map_error::MapErrorStore`] -- wrap this over a store to convert its errors to another type
+//! - [`wrappers::audit::AuditWrapperStore`] -- wrap this over a store to record every write into another store
+//! - [`wrappers::dryrun::DryRunWrapperStore`] -- wrap this over a store to record the writes instead of applying them
+//! - [`wrappers::memoize_list::MemoizeListWrapperStore`] -- wrap this over a slow store to remember its listings
+//! - [`wrappers::graft::GraftStore`] -- compose two stores, switching to the inner one under a boundary address
+//! - [`wrappers::metrics::MetricsWrapperStore`] -- wrap this over a store to count its operations and measure their latency
+//! - [`wrappers::skip_unchanged::SkipUnchangedWrapperStore`] -- wrap this over a store to skip writing values that didn't change
 //!
 //! Cloud services:
 //! - [`stores::cloud::airtable::AirtableStore`](stores::cloud::airtable::AirtableStore) -- Airtable
@@ -157,11 +169,21 @@
 //! - [`stores::cell::MemoryCellStore`] arbitrary memory cell as a readable/writable location (use it with wrappers)
 //! - [`stores::json::json_value_store`] `serde_json::Value` as a store (simply a [`MemoryCellStore`](stores::cell::MemoryCellStore) wrapped in [`LocatedJsonStore`](stores::located::json::LocatedJsonStore))
 //! - [`stores::indexed_vec::IndexedVecStore`] Vector indexed by Id derived from the value
+//! - [`stores::hashmap::HashMapStore`] flat in-memory key-value store
+//!
+//! Interop:
+//! - [`interop::config::LocationSource`] -- a JSON subtree as a source for the `config` crate
 //!
 pub mod store;
 
 pub mod address;
+pub mod interop;
 pub mod location;
 pub mod stores;
 pub mod util;
 pub mod wrappers;
+
+// which helpers are used depends on the features
+#[cfg(test)]
+#[allow(dead_code)]
+mod test_util;
@@ -5,24 +5,53 @@
 //     store::{list::ListOfAddresses, ReadStore, Store, StoreResult},
 // };
 
-use std::pin::Pin;
+use std::{collections::HashSet, pin::Pin};
 
 use crate::{
     address::{
-        primitive::Existence,
+        primitive::{Existence, Present},
         traits::{
-            AddressableGet, AddressableInsert, AddressableList, AddressableQuery, AddressableSet,
-            AddressableTree, BranchOrLeaf,
+            AddressableBranch, AddressableCas, AddressableChild, AddressableDelete,
+            AddressableExistsMany, AddressableGet, AddressableInsert, AddressableList,
+            AddressableListFrom, AddressableQuery, AddressableReadStream, AddressableSet,
+            AddressableSwap, AddressableTree, BranchOrLeaf,
         },
-        Address, Addressable, PathAddress, SubAddress,
+        Address, Addressable, PathAddress, ResolveAddress, SubAddress,
     },
-    store::{Store, StoreEx, StoreResult},
+    store::{PathError, Store, StoreEx, StoreResult},
 };
-use futures::StreamExt;
-use futures::{stream, Stream};
+#[cfg(feature = "json")]
+use futures::future::LocalBoxFuture;
+use futures::{future, stream, Stream};
+use futures::{StreamExt, TryFutureExt, TryStreamExt};
+#[cfg(feature = "json")]
+use serde_json::Value;
+
+#[cfg(feature = "json")]
+use crate::stores::{
+    hashmap::HashMapStore,
+    json::paths::{JsonPath, JsonPathParseError},
+    located::json::MissingValue,
+};
+#[cfg(feature = "retry")]
+use crate::util::retry::{RetryPolicy, RetryingLocation};
+
+/// How many values [`Location::values`] and [`Location::stream_leaves_with_values`] read at once.
+const VALUES_CONCURRENCY: usize = 8;
+
+/// A page of a listing, see [`Location::list_page`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ListPage<T> {
+    pub items: Vec<T>,
+    /// Whether there are more items after this page.
+    pub has_more: bool,
+}
 
 /// A pair of a store and an address. You can pass this object around,
 /// use it to traverse the store, and get/change values.
+///
+/// The comparisons (`==`, `<`) are of the stores and the addresses, not of the values there;
+/// see [`value_eq`](Location::value_eq) for that.
 #[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
 pub struct Location<Addr: Address, S: Store + Addressable<Addr>> {
     pub store: S,
@@ -45,6 +74,19 @@ impl<V, Addr: Address, S: Store + Addressable<Addr, DefaultValue = V>> Location<
     {
         self.set(v).await
     }
+
+    /// Whether there's a value here, found by reading it.
+    ///
+    /// Meant for the stores that can't be read as [`Present`], like
+    /// [`HashMapStore`](crate::stores::hashmap::HashMapStore): their reads are generic over
+    /// the value type, so a `Present` impl would overlap with them. Elsewhere `get::<Present>()`
+    /// doesn't read the value.
+    pub async fn present(&self) -> StoreResult<Present, S>
+    where
+        S: AddressableGet<V, Addr>,
+    {
+        Ok(Present(self.getv().await?.is_some()))
+    }
 }
 
 impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
@@ -61,6 +103,93 @@ impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
         self.store.list(&self.address)
     }
 
+    /// Same as `list`, but resumable: starts at `cursor` (or from the beginning if it's `None`),
+    /// and streams `(cursor, sub, full_address)`, where `cursor` resumes right after this item.
+    pub fn list_from(&self, cursor: Option<S::ListCursor>) -> S::ListFromStream
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableListFrom<'a, Addr>,
+    {
+        self.store.list_from(&self.address, cursor)
+    }
+
+    /// Same as `list`, but ordered by the item addresses, for a deterministic listing
+    /// regardless of the order of the underlying store.
+    ///
+    /// Unlike `list`, this buffers the whole listing before streaming it.
+    pub fn sorted_list(
+        &self,
+    ) -> impl 'a + Stream<Item = StoreResult<(S::AddedAddress, S::ItemAddress), S>>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableList<'a, Addr>,
+        S::ItemAddress: Ord,
+    {
+        self.list()
+            .try_collect::<Vec<_>>()
+            .map_ok(|mut items| {
+                items.sort_by(|(_, a), (_, b)| a.cmp(b));
+                stream::iter(items.into_iter().map(Ok))
+            })
+            .try_flatten_stream()
+    }
+
+    /// Same as `list`, but skips the items whose address was already listed,
+    /// e.g. for stores that can list an item twice, so sync jobs don't process it twice.
+    ///
+    /// The parts of every listed address are kept until the stream is dropped,
+    /// so the memory used grows with the size of the listing.
+    pub fn list_unique(
+        &self,
+    ) -> impl 'a + Stream<Item = StoreResult<(S::AddedAddress, S::ItemAddress), S>>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableList<'a, Addr>,
+    {
+        let mut seen = HashSet::new();
+
+        self.list()
+            .try_filter(move |(_, addr)| future::ready(seen.insert(addr.as_parts())))
+    }
+
+    /// A page of up to `limit` item addresses, skipping the first `offset` ones.
+    ///
+    /// This goes through the listing from the start, so for big network listings
+    /// prefer [`list_from`](Location::list_from) where the store supports it.
+    pub async fn list_page(
+        &self,
+        offset: usize,
+        limit: usize,
+    ) -> StoreResult<ListPage<S::ItemAddress>, S>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableList<'a, Addr>,
+    {
+        let mut listing = std::pin::pin!(self.list());
+        let mut items = vec![];
+        let mut seen = 0;
+
+        while let Some((_, addr)) = listing.try_next().await? {
+            if seen >= offset {
+                if items.len() == limit {
+                    return Ok(ListPage {
+                        items,
+                        has_more: true,
+                    });
+                }
+
+                items.push(addr);
+            }
+
+            seen += 1;
+        }
+
+        Ok(ListPage {
+            items,
+            has_more: false,
+        })
+    }
+
     /// Type-safe navigation. Every store defines its own address types.
     ///
     #[cfg_attr(not(feature = "json"), doc = "```ignore")]
@@ -81,6 +210,37 @@ impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
         Location::new(self.address.sub(address), self.store)
     }
 
+    /// Relative navigation, e.g. for paths typed by a user: `.` stays, `..` goes to the parent,
+    /// and the other `/`-separated parts go down. Going above the root is an error.
+    ///
+    #[cfg_attr(not(feature = "json"), doc = "```ignore")]
+    #[cfg_attr(feature = "json", doc = "```")]
+    /// use serde_json::json;
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let store = json_value_store(json!({"a": {"b": 1, "c": [2, 3]}}))?;
+    ///
+    /// let b = store.path("a.b")?;
+    /// assert_eq!(b.resolve("../c[1]")?.getv().await?, Some(json!(3)));
+    /// assert!(b.resolve("../../..").is_err());
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub fn resolve(&self, relative: &str) -> StoreResult<Location<Addr, S>, S>
+    where
+        Addr: ResolveAddress,
+        <S as Store>::Error: From<<Addr as ResolveAddress>::Error>,
+    {
+        Ok(Location::new(
+            self.address.clone().resolve(relative)?,
+            self.store.clone(),
+        ))
+    }
+
     /// String-based navigation. Some stores allow this.
     ///
     #[cfg_attr(not(feature = "json"), doc = "```ignore")]
@@ -101,6 +261,21 @@ impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
         Ok(Location::new(self.address.path(p)?, self.store))
     }
 
+    /// Like [`path`](Location::path), but returns the parsing error as it is,
+    /// for stores whose error can't be made from it. See [`StoreEx::try_path`].
+    pub fn try_path<A: Address>(
+        self,
+        p: &str,
+    ) -> Result<Location<A, S>, PathError<S::Error, <Addr as PathAddress>::Error>>
+    where
+        S: Addressable<A>,
+        Addr: PathAddress<Output = A>,
+    {
+        let address = self.address.path(p).map_err(PathError::ParseError)?;
+
+        Ok(Location::new(address, self.store))
+    }
+
     /// Get a Value of a parituclar type from the store, if the store supports that.
     ///
     /// Often it's easier to use `location.getv()`, as it will return the default type
@@ -114,6 +289,40 @@ impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
         self.store.addr_get(&self.address).await
     }
 
+    /// Like `get`, but `default` if the value doesn't exist. Nothing is written.
+    ///
+    #[cfg_attr(not(feature = "json"), doc = "```ignore")]
+    #[cfg_attr(feature = "json", doc = "```")]
+    /// use serde_json::json;
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let store = json_value_store(json!({"port": 8080}))?;
+    ///
+    /// assert_eq!(store.path("port")?.get_or(json!(80)).await?, json!(8080));
+    /// assert_eq!(store.path("host")?.get_or(json!("localhost")).await?, json!("localhost"));
+    /// assert_eq!(store.path("host")?.getv().await?, None);
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub async fn get_or<Value>(&self, default: Value) -> StoreResult<Value, S>
+    where
+        S: AddressableGet<Value, Addr>,
+    {
+        Ok(self.get().await?.unwrap_or(default))
+    }
+
+    /// Like `get`, but `Value::default()` if the value doesn't exist. Nothing is written.
+    pub async fn get_or_default<Value: Default>(&self) -> StoreResult<Value, S>
+    where
+        S: AddressableGet<Value, Addr>,
+    {
+        Ok(self.get().await?.unwrap_or_default())
+    }
+
     /// Write a Value of a particular type to the store, if the store supports that.
     ///
     /// Often it's easier to use `location.setv(value)`, as it will use the default type
@@ -127,6 +336,112 @@ impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
         self.store.set_addr(&self.address, value).await
     }
 
+    /// Whether the two locations hold equal values, read as `Value` from both.
+    /// Two missing values are equal.
+    ///
+    /// Unlike `==`, which compares the stores and the addresses, this compares what's stored,
+    /// so the locations can be in different stores, and have different types of addresses.
+    ///
+    #[cfg_attr(not(feature = "json"), doc = "```ignore")]
+    #[cfg_attr(feature = "json", doc = "```")]
+    /// use serde_json::{json, Value};
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let a = json_value_store(json!({"x": {"y": 1}}))?;
+    /// let b = json_value_store(json!({"y": 1}))?;
+    ///
+    /// assert!(a.path("x")?.value_eq::<Value, _, _>(&b.root()).await?);
+    /// assert!(!a.path("x.y")?.value_eq::<Value, _, _>(&b.root()).await?);
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub async fn value_eq<Value: PartialEq, A2: Address, S2>(
+        &self,
+        other: &Location<A2, S2>,
+    ) -> StoreResult<bool, S>
+    where
+        S: AddressableGet<Value, Addr>,
+        S2: AddressableGet<Value, A2>,
+        S::Error: From<S2::Error>,
+    {
+        let (mine, theirs) = future::try_join(
+            self.get::<Value>(),
+            other.get::<Value>().map_err(S::Error::from),
+        )
+        .await?;
+
+        Ok(mine == theirs)
+    }
+
+    /// Same as `get::<String>()`.
+    ///
+    #[cfg_attr(not(feature = "fs"), doc = "```ignore")]
+    #[cfg_attr(feature = "fs", doc = "```")]
+    /// # use anystore::{store::StoreEx, stores::fs::{FileStoreError, FileSystemStore}};
+    /// # tokio_test::block_on(async {
+    /// # let dir = std::env::temp_dir().join("anystore-get-string");
+    /// # tokio::fs::create_dir_all(&dir).await?;
+    /// let file = FileSystemStore::new(dir.clone()).path("hello.txt")?;
+    ///
+    /// file.set_string("hello").await?;
+    /// assert_eq!(file.get_string().await?, Some("hello".to_owned()));
+    /// # tokio::fs::remove_dir_all(&dir).await?;
+    /// # Ok::<(), FileStoreError>(())
+    /// # }).unwrap()
+    /// ```
+    pub async fn get_string(&self) -> StoreResult<Option<String>, S>
+    where
+        S: AddressableGet<String, Addr>,
+    {
+        self.get::<String>().await
+    }
+
+    /// Same as `set(&Some(value.to_owned()))` for a `String`.
+    pub async fn set_string(&self, value: &str) -> StoreResult<(), S>
+    where
+        S: AddressableSet<String, Addr>,
+    {
+        self.set(&Some(value.to_owned())).await
+    }
+
+    /// Same as `get::<Vec<u8>>()`.
+    ///
+    #[cfg_attr(not(feature = "fs"), doc = "```ignore")]
+    #[cfg_attr(feature = "fs", doc = "```")]
+    /// # use anystore::{store::StoreEx, stores::fs::{FileStoreError, FileSystemStore}};
+    /// # tokio_test::block_on(async {
+    /// # let dir = std::env::temp_dir().join("anystore-get-bytes");
+    /// # tokio::fs::create_dir_all(&dir).await?;
+    /// let file = FileSystemStore::new(dir.clone()).path("data.bin")?;
+    ///
+    /// file.set_bytes(&[0, 159, 146, 150]).await?;
+    /// assert_eq!(file.get_bytes().await?, Some(vec![0, 159, 146, 150]));
+    ///
+    /// // not valid UTF-8
+    /// assert!(file.get_string().await.is_err());
+    /// # tokio::fs::remove_dir_all(&dir).await?;
+    /// # Ok::<(), FileStoreError>(())
+    /// # }).unwrap()
+    /// ```
+    pub async fn get_bytes(&self) -> StoreResult<Option<Vec<u8>>, S>
+    where
+        S: AddressableGet<Vec<u8>, Addr>,
+    {
+        self.get::<Vec<u8>>().await
+    }
+
+    /// Same as `set(&Some(value.to_vec()))` for a `Vec<u8>`.
+    pub async fn set_bytes(&self, value: &[u8]) -> StoreResult<(), S>
+    where
+        S: AddressableSet<Vec<u8>, Addr>,
+    {
+        self.set(&Some(value.to_vec())).await
+    }
+
     /// Inserts a list, returning the addresses of the items.
     ///
     /// Typically you want to use `.try_collect::<Vec<_>>().await?` on the returned
@@ -139,6 +454,122 @@ impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
         self.store.insert(&self.address, values)
     }
 
+    /// Stream the items of this location together with their values,
+    /// reading at most `concurrency` of them at once.
+    ///
+    /// The order of the listing is preserved. Items that disappear between listing
+    /// and reading are skipped.
+    ///
+    #[cfg_attr(not(feature = "json"), doc = "```ignore")]
+    #[cfg_attr(feature = "json", doc = "```")]
+    /// use futures::TryStreamExt;
+    /// use serde_json::{json, Value};
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let store = json_value_store(json!({"a": 1, "b": [2]}))?;
+    ///
+    /// let values = store
+    ///     .root()
+    ///     .list_values::<Value>(4)
+    ///     .map_ok(|(addr, v)| (addr.to_string(), v))
+    ///     .try_collect::<Vec<_>>()
+    ///     .await?;
+    ///
+    /// assert_eq!(values, vec![("a".to_owned(), json!(1)), ("b".to_owned(), json!([2]))]);
+    ///
+    /// Ok::<(), JsonValueStoreError>(())
+    /// # }).unwrap()
+    /// ```
+    pub fn list_values<Value: 'a>(
+        &self,
+        concurrency: usize,
+    ) -> impl 'a + Stream<Item = StoreResult<(S::ItemAddress, Value), S>>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableList<'a, Addr> + AddressableGet<Value, S::ItemAddress>,
+    {
+        let store = self.store.clone();
+
+        self.list()
+            .map_ok(move |(_, item)| {
+                let store = store.clone();
+
+                async move {
+                    let value = store.addr_get(&item).await?;
+                    Ok(value.map(|v| (item, v)))
+                }
+            })
+            .try_buffered(concurrency.max(1))
+            .try_filter_map(|v| async move { Ok(v) })
+    }
+
+    /// Stream the values of the items of this location, without their addresses.
+    ///
+    /// Like [`list_values`](Location::list_values), with a default concurrency.
+    pub fn values<Value: 'a>(&self) -> impl 'a + Stream<Item = StoreResult<Value, S>>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableList<'a, Addr> + AddressableGet<Value, S::ItemAddress>,
+    {
+        self.list_values(VALUES_CONCURRENCY).map_ok(|(_, v)| v)
+    }
+
+    /// The first item of this location that matches `predicate`.
+    /// The listing stops as soon as it's found.
+    pub async fn find_child<P: Fn(&S::ItemAddress) -> bool>(
+        &self,
+        predicate: P,
+    ) -> StoreResult<Option<S::ItemAddress>, S>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableList<'a, Addr>,
+    {
+        let mut items = std::pin::pin!(self.list());
+
+        while let Some((_, item)) = items.try_next().await? {
+            if predicate(&item) {
+                return Ok(Some(item));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The address of the child whose [`own_name`](Address::own_name) is `name`, if there is one.
+    /// Like [`find_child`](Self::find_child) by name, but the store can skip the listing
+    /// and address the child directly, see [`AddressableChild`].
+    pub async fn child(&self, name: &str) -> StoreResult<Option<S::ItemAddress>, S>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableChild<'a, Addr>,
+    {
+        self.store.child(&self.address, name).await
+    }
+
+    /// The first item of this location whose value matches `predicate`, with the value.
+    /// The items are read one by one, and the listing stops as soon as one is found.
+    pub async fn find_child_value<Value: 'a, P: Fn(&Value) -> bool>(
+        &self,
+        predicate: P,
+    ) -> StoreResult<Option<(S::ItemAddress, Value)>, S>
+    where
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+        S: AddressableList<'a, Addr> + AddressableGet<Value, S::ItemAddress>,
+    {
+        let mut values = std::pin::pin!(self.list_values::<Value>(1));
+
+        while let Some((item, value)) = values.try_next().await? {
+            if predicate(&value) {
+                return Ok(Some((item, value)));
+            }
+        }
+
+        Ok(None)
+    }
+
     pub fn query<Query>(&self, query: Query) -> S::ListOfAddressesStream
     where
         Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
@@ -151,6 +582,62 @@ impl<'a, Addr: Address, S: 'a + Store + Addressable<Addr>> Location<Addr, S> {
     pub fn new(address: Addr, store: S) -> Self {
         Location { store, address }
     }
+
+    /// The same address in another store, e.g. to mirror values between stores.
+    ///
+    #[cfg_attr(not(feature = "json"), doc = "```ignore")]
+    #[cfg_attr(feature = "json", doc = "```")]
+    /// use serde_json::json;
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let from = json_value_store(json!({"a": {"b": 1}}))?;
+    /// let to = json_value_store(json!({}))?;
+    ///
+    /// let here = from.path("a.b")?;
+    /// here.with_store(to.clone()).setv(&here.getv().await?).await?;
+    ///
+    /// assert_eq!(to.root().getv().await?, Some(json!({"a": {"b": 1}})));
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub fn with_store<S2: Store + Addressable<Addr>>(&self, store: S2) -> Location<Addr, S2> {
+        Location::new(self.address.clone(), store)
+    }
+
+    /// Retry the operations on this location, without wrapping the whole store.
+    ///
+    #[cfg_attr(not(feature = "json"), doc = "```ignore")]
+    #[cfg_attr(feature = "json", doc = "```")]
+    /// use std::time::Duration;
+    ///
+    /// use serde_json::json;
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    /// use anystore::util::retry::RetryPolicy;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let store = json_value_store(json!({"a": 1}))?;
+    ///
+    /// let location = store.path("a")?;
+    /// let value = location
+    ///     .with_retry(RetryPolicy::new(3, Duration::from_millis(100)))
+    ///     .getv()
+    ///     .await?;
+    ///
+    /// assert_eq!(value, Some(json!(1)));
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    #[cfg(feature = "retry")]
+    pub fn with_retry(&self, policy: RetryPolicy) -> RetryingLocation<'_, Addr, S> {
+        RetryingLocation::new(self, policy)
+    }
 }
 
 impl<Addr: Address, S: Store + AddressableGet<Existence, Addr>> Location<Addr, S> {
@@ -160,9 +647,91 @@ impl<Addr: Address, S: Store + AddressableGet<Existence, Addr>> Location<Addr, S
     }
 }
 
+impl<Addr: Address, S: Store + Addressable<Addr>> Location<Addr, S> {
+    /// The addresses of `subs` under this location that exist, in the same order.
+    /// Stores may check them all in one go, see [`AddressableExistsMany`].
+    pub async fn which_exist<Sub, Output: Address>(
+        &self,
+        subs: impl IntoIterator<Item = Sub>,
+    ) -> StoreResult<Vec<Output>, S>
+    where
+        Addr: SubAddress<Sub, Output = Output>,
+        S: AddressableExistsMany<Output>,
+    {
+        let addrs: Vec<Output> = subs
+            .into_iter()
+            .map(|sub| self.address.clone().sub(sub))
+            .collect();
+        let exist = self.store.exists_many(&addrs).await?;
+
+        Ok(addrs
+            .into_iter()
+            .zip(exist)
+            .filter_map(|(addr, exists)| exists.then_some(addr))
+            .collect())
+    }
+}
+
+impl<Addr: Address, S: Store + Addressable<Addr>> Location<Addr, S> {
+    /// Write `new` only if the value is still `expected`. Returns whether it was written.
+    pub async fn compare_and_swap<Value>(
+        &self,
+        expected: &Option<Value>,
+        new: &Option<Value>,
+    ) -> StoreResult<bool, S>
+    where
+        S: AddressableCas<Value, Addr>,
+    {
+        self.store
+            .compare_and_swap(&self.address, expected, new)
+            .await
+    }
+
+    /// Write `value` and return the value that was here before.
+    pub async fn swap<Value>(&self, value: &Option<Value>) -> StoreResult<Option<Value>, S>
+    where
+        S: AddressableSwap<Value, Addr>,
+    {
+        self.store.swap(&self.address, value).await
+    }
+}
+
+impl<Addr: Address, S: Store + AddressableDelete<Addr>> Location<Addr, S> {
+    /// Remove the value here.
+    pub async fn delete(&self) -> StoreResult<(), S> {
+        self.store.delete(&self.address).await
+    }
+}
+
+impl<Addr: Address, S: Store + AddressableBranch<Addr>> Location<Addr, S> {
+    /// Make sure there's a container here (an empty JSON object, a directory, etc.),
+    /// e.g. before inserting children. Fails if a non-container value is here.
+    pub async fn ensure_branch(&self) -> StoreResult<(), S> {
+        self.store.ensure_branch(&self.address).await
+    }
+}
+
+impl<Addr: Address, S: Store + AddressableReadStream<Addr>> Location<Addr, S> {
+    /// Copy the value into `writer` without loading it into memory.
+    ///
+    /// Returns the number of bytes copied, or `None` if the value doesn't exist.
+    pub async fn copy_to_writer<W>(&self, writer: &mut W) -> StoreResult<Option<u64>, S>
+    where
+        W: tokio::io::AsyncWrite + Unpin + ?Sized,
+        S::Error: From<std::io::Error>,
+    {
+        match self.store.read_stream(&self.address).await? {
+            Some(mut reader) => Ok(Some(tokio::io::copy(&mut reader, writer).await?)),
+            None => Ok(None),
+        }
+    }
+}
+
 impl<'a, ListAddr: Address, S: 'a + Store + Addressable<ListAddr>> Location<ListAddr, S> {
     /// Recursively traverse the tree and stream all the addresses.
     ///
+    /// The items are classified with [`AddressableTree::list_classified`].
+    ///
     #[cfg_attr(not(feature = "json"), doc = "```ignore")]
     #[cfg_attr(feature = "json", doc = "```")]
     /// use std::collections::HashSet;
@@ -221,37 +790,630 @@ impl<'a, ListAddr: Address, S: 'a + Store + Addressable<ListAddr>> Location<List
         ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
     {
         let store = self.store.clone();
-        let to_visit: Vec<Pin<Box<S::ListOfAddressesStream>>> = vec![Box::pin(self.list())];
+        let to_visit = vec![self.store.list_classified(&self.address)];
 
         stream::try_unfold(to_visit, move |mut to_visit| {
             let store = store.clone();
 
             async move {
                 while let Some(last) = to_visit.last_mut() {
-                    let Some(val) = last.next().await else {
+                    let Some(bl) = last.next().await else {
                         to_visit.pop();
                         continue;
                     };
 
-                    let (_, val) = val?;
-                    let bl = store.branch_or_leaf(val).await?;
+                    let bl = bl?;
+
+                    if let BranchOrLeaf::Branch(br) = &bl {
+                        to_visit.push(store.list_classified(br));
+                    }
+
+                    return Ok(Some((bl, to_visit)));
+                }
+
+                Ok(None)
+            }
+        })
+    }
+
+    /// Like [`walk_tree_recursively`](Location::walk_tree_recursively), but in the same order
+    /// every time, e.g. for snapshot tests: the children of every branch are sorted by their
+    /// added addresses (and the repeated ones skipped) before they're walked.
+    ///
+    /// Each branch's listing is buffered, and the rest of it is kept while its subtree is walked,
+    /// so the memory used grows with the sizes of the branches along the current path.
+    /// The items are classified one by one with `branch_or_leaf`, not with `list_classified`.
+    pub fn walk_tree_sorted<ItemAddr>(
+        &self,
+    ) -> impl 'a + Stream<Item = StoreResult<BranchOrLeaf<ListAddr, ItemAddr>, S>>
+    where
+        ItemAddr: Address,
+        S: AddressableTree<'a, ListAddr, ItemAddr>,
+        S::AddedAddress: Ord,
+        ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
+    {
+        let store = self.store.clone();
+        let to_visit: Vec<std::vec::IntoIter<ListAddr>> = vec![];
+
+        stream::try_unfold(
+            (to_visit, Some(self.address.clone())),
+            move |(mut to_visit, mut to_list)| {
+                let store = store.clone();
+
+                async move {
+                    if let Some(branch) = to_list.take() {
+                        let mut children = store.list(&branch).try_collect::<Vec<_>>().await?;
+                        children.sort_by(|(a, _), (b, _)| a.cmp(b));
+                        children.dedup_by(|(a, _), (b, _)| a == b);
+
+                        to_visit.push(
+                            children
+                                .into_iter()
+                                .map(|(_, item)| item)
+                                .collect::<Vec<_>>()
+                                .into_iter(),
+                        );
+                    }
+
+                    while let Some(last) = to_visit.last_mut() {
+                        let Some(item) = last.next() else {
+                            to_visit.pop();
+                            continue;
+                        };
+
+                        let bl = store.branch_or_leaf(item).await?;
+
+                        if let BranchOrLeaf::Branch(br) = &bl {
+                            to_list = Some(br.clone());
+                        }
+
+                        return Ok(Some((bl, (to_visit, to_list))));
+                    }
+
+                    Ok(None)
+                }
+            },
+        )
+    }
 
+    /// Like [`walk_tree_recursively`](Location::walk_tree_recursively), but each leaf comes with
+    /// its value, e.g. to export a whole store in one call. At most `concurrency` leaves are read at once.
+    ///
+    /// The order of the walk is preserved. Leaves that disappear between the walk
+    /// and the read are skipped.
+    pub fn walk_with_values<ItemAddr, Value: 'a>(
+        &self,
+        concurrency: usize,
+    ) -> impl 'a + Stream<Item = StoreResult<BranchOrLeaf<ListAddr, (ItemAddr, Value)>, S>>
+    where
+        ItemAddr: Address,
+        S: AddressableTree<'a, ListAddr, ItemAddr> + AddressableGet<Value, ItemAddr>,
+        S::AddedAddress: std::fmt::Debug,
+        ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
+    {
+        let store = self.store.clone();
+
+        self.walk_tree_recursively()
+            .map_ok(move |bl| {
+                let store = store.clone();
+
+                async move {
                     match bl {
-                        BranchOrLeaf::Leaf(_) => {
-                            return Ok(Some((bl, to_visit)));
+                        BranchOrLeaf::Branch(br) => Ok(Some(BranchOrLeaf::Branch(br))),
+                        BranchOrLeaf::Leaf(leaf) => {
+                            let value = store.addr_get(&leaf).await?;
+                            Ok(value.map(|v| BranchOrLeaf::Leaf((leaf, v))))
                         }
-                        BranchOrLeaf::Branch(br) => {
-                            to_visit.push(Box::pin(store.sub(br.clone()).list()));
+                    }
+                }
+            })
+            .try_buffered(concurrency.max(1))
+            .try_filter_map(|v| async move { Ok(v) })
+    }
 
-                            return Ok(Some((BranchOrLeaf::Branch(br), to_visit)));
+    /// Every leaf under this location with its value, recursively, e.g. to dump a whole store.
+    /// Like [`walk_with_values`](Location::walk_with_values) without the branches, reading
+    /// as many values at once as [`values`](Location::values).
+    pub fn stream_leaves_with_values<ItemAddr, Value: 'a>(
+        &self,
+    ) -> impl 'a + Stream<Item = StoreResult<(ItemAddr, Value), S>>
+    where
+        ItemAddr: Address,
+        S: AddressableTree<'a, ListAddr, ItemAddr> + AddressableGet<Value, ItemAddr>,
+        S::AddedAddress: std::fmt::Debug,
+        ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
+    {
+        self.walk_with_values(VALUES_CONCURRENCY)
+            .try_filter_map(|bl| async move {
+                Ok(match bl {
+                    BranchOrLeaf::Branch(_) => None,
+                    BranchOrLeaf::Leaf(leaf) => Some(leaf),
+                })
+            })
+    }
+
+    /// The number of leaves under this location, e.g. to show "N items" in a UI.
+    /// With `include_branches`, the branches under it are counted as well.
+    ///
+    /// This walks the whole subtree with [`walk_tree_recursively`](Location::walk_tree_recursively).
+    pub async fn count_recursive<ItemAddr>(&self, include_branches: bool) -> StoreResult<usize, S>
+    where
+        ItemAddr: Address,
+        S: AddressableTree<'a, ListAddr, ItemAddr>,
+        S::AddedAddress: std::fmt::Debug,
+        ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
+    {
+        self.walk_tree_recursively::<ItemAddr>()
+            .try_fold(0, |count, bl| async move {
+                match bl {
+                    BranchOrLeaf::Branch(_) if !include_branches => Ok(count),
+                    _ => Ok(count + 1),
+                }
+            })
+            .await
+    }
+
+    /// Like [`walk_tree_recursively`](Location::walk_tree_recursively), but an error doesn't end the walk,
+    /// e.g. to crawl a flaky network store.
+    ///
+    /// A failing listing or `branch_or_leaf` is retried up to `retries` times. Then the error
+    /// is yielded and the walk goes on without that branch (or with the rest of its listing).
+    /// Retried listings skip the items they've already yielded, so the order of the items
+    /// should be stable.
+    pub fn walk_tree_lenient<ItemAddr>(
+        &self,
+        retries: usize,
+    ) -> impl 'a + Stream<Item = StoreResult<BranchOrLeaf<ListAddr, ItemAddr>, S>>
+    where
+        ItemAddr: Address,
+        S: AddressableTree<'a, ListAddr, ItemAddr>,
+        ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
+    {
+        struct Listing<A, St> {
+            address: A,
+            items: Pin<Box<St>>,
+            consumed: usize,
+            failures: usize,
+        }
+
+        let store = self.store.clone();
+        let list = move |address: ListAddr, consumed: usize, failures: usize| Listing {
+            items: Box::pin(store.sub(address.clone()).list().skip(consumed)),
+            address,
+            consumed,
+            failures,
+        };
+
+        let to_visit = vec![list(self.address.clone(), 0, 0)];
+        let store = self.store.clone();
+
+        stream::unfold(to_visit, move |mut to_visit| {
+            let store = store.clone();
+            let list = list.clone();
+
+            async move {
+                while let Some(last) = to_visit.last_mut() {
+                    let val = match last.items.next().await {
+                        None => {
+                            to_visit.pop();
+                            continue;
+                        }
+                        Some(Ok((_, val))) => val,
+                        Some(Err(_)) if last.failures < retries => {
+                            *last = list(last.address.clone(), last.consumed, last.failures + 1);
+                            continue;
+                        }
+                        Some(Err(e)) => {
+                            to_visit.pop();
+                            return Some((Err(e), to_visit));
+                        }
+                    };
+
+                    last.consumed += 1;
+
+                    let mut bl = store.branch_or_leaf(val.clone()).await;
+                    for _ in 0..retries {
+                        if bl.is_ok() {
+                            break;
                         }
+                        bl = store.branch_or_leaf(val.clone()).await;
                     }
 
-                    //
+                    if let Ok(BranchOrLeaf::Branch(br)) = &bl {
+                        to_visit.push(list(br.clone(), 0, 0));
+                    }
+
+                    return Some((bl, to_visit));
                 }
 
-                Ok(None)
+                None
+            }
+        })
+    }
+
+    /// Whether there's a branch here (an object, a directory, etc.).
+    /// A missing address is handled by [`AddressableTree::branch_or_leaf`]: the JSON and file system stores fail.
+    pub async fn is_branch<ItemAddr>(&self) -> StoreResult<bool, S>
+    where
+        S: AddressableTree<'a, ListAddr, ItemAddr>,
+        ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
+    {
+        let bl = self.store.branch_or_leaf(self.address.clone()).await?;
+
+        Ok(bl.as_branch().is_some())
+    }
+
+    /// Whether there's a leaf here (a scalar value, a file, etc.).
+    /// A missing address is handled by [`AddressableTree::branch_or_leaf`]: the JSON and file system stores fail.
+    pub async fn is_leaf<ItemAddr>(&self) -> StoreResult<bool, S>
+    where
+        S: AddressableTree<'a, ListAddr, ItemAddr>,
+        ListAddr: SubAddress<S::AddedAddress, Output = ListAddr>,
+    {
+        let bl = self.store.branch_or_leaf(self.address.clone()).await?;
+
+        Ok(bl.as_leaf().is_some())
+    }
+}
+
+#[cfg(feature = "json")]
+impl<S: Store + AddressableGet<Value, JsonPath>> Location<JsonPath, S> {
+    /// Navigate with [`path`](Location::path) and read the value there as a `V` right away,
+    /// e.g. to report a broken config when it's loaded rather than when it's used.
+    ///
+    /// Fails with [`MissingValue`] if there's no value, and with a `serde_json::Error`
+    /// if it can't be deserialized into `V`.
+    ///
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::stores::located::json::{LocatedJsonStoreError, MissingValue};
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let config = json_value_store(json!({"server": {"port": 8080, "host": "localhost"}}))?;
+    ///
+    /// let (location, port) = config.root().path_typed::<u16>("server.port").await?;
+    /// assert_eq!(port, 8080);
+    /// assert_eq!(location.address.to_string(), "server.port");
+    ///
+    /// assert!(matches!(
+    ///     config.root().path_typed::<u16>("server.host").await,
+    ///     Err(LocatedJsonStoreError::SerdeError(_))
+    /// ));
+    /// assert!(matches!(
+    ///     config.root().path_typed::<u16>("server.timeout").await,
+    ///     Err(LocatedJsonStoreError::MissingValue(MissingValue(path))) if path.to_string() == "server.timeout"
+    /// ));
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub async fn path_typed<V: serde::de::DeserializeOwned>(
+        self,
+        p: &str,
+    ) -> StoreResult<(Location<JsonPath, S>, V), S>
+    where
+        S::Error: From<JsonPathParseError> + From<serde_json::Error> + From<MissingValue>,
+    {
+        let location = self.path(p)?;
+
+        let Some(value) =
+            AddressableGet::<Value, _>::addr_get(&location.store, &location.address).await?
+        else {
+            return Err(MissingValue(location.address).into());
+        };
+
+        let value = serde_json::from_value(value)?;
+
+        Ok((location, value))
+    }
+
+    /// Read the value here as a `V`, e.g. the whole config at the root of a JSON store.
+    ///
+    /// `None` if there's no value. Use [`get_as_or_default`](Location::get_as_or_default)
+    /// to treat a missing or empty document as `V::default()`.
+    ///
+    #[cfg_attr(not(feature = "fs"), doc = "```ignore")]
+    #[cfg_attr(feature = "fs", doc = "```")]
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// use anystore::stores::fs::FileSystemStore;
+    /// use anystore::stores::located::json::LocatedJsonStore;
+    /// use anystore::store::StoreEx;
+    ///
+    /// #[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+    /// struct AppConfig {
+    ///     name: String,
+    ///     retries: u32,
+    /// }
+    ///
+    /// # tokio_test::block_on(async {
+    /// let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+    /// tokio::fs::create_dir(&dir).await?;
+    ///
+    /// let config = LocatedJsonStore::new(FileSystemStore::new(dir.clone()).path("config.json")?);
+    ///
+    /// // the file doesn't exist yet
+    /// assert_eq!(config.root().get_as_or_default::<AppConfig>().await?, AppConfig::default());
+    ///
+    /// let wanted = AppConfig { name: "app".to_owned(), retries: 3 };
+    /// config.root().set_as(&wanted).await?;
+    /// assert_eq!(config.root().get_as::<AppConfig>().await?, Some(wanted));
+    ///
+    /// tokio::fs::remove_dir_all(&dir).await?;
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub async fn get_as<V: serde::de::DeserializeOwned>(&self) -> StoreResult<Option<V>, S>
+    where
+        S::Error: From<serde_json::Error>,
+    {
+        match AddressableGet::<Value, _>::addr_get(&self.store, &self.address).await? {
+            Some(value) => Ok(Some(serde_json::from_value(value)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Like [`get_as`](Location::get_as), but no value or `null` (e.g. a missing or empty document)
+    /// is `V::default()`.
+    pub async fn get_as_or_default<V: serde::de::DeserializeOwned + Default>(
+        &self,
+    ) -> StoreResult<V, S>
+    where
+        S::Error: From<serde_json::Error>,
+    {
+        match AddressableGet::<Value, _>::addr_get(&self.store, &self.address).await? {
+            None | Some(Value::Null) => Ok(V::default()),
+            Some(value) => Ok(serde_json::from_value(value)?),
+        }
+    }
+
+    /// Write `value` here, serialized as JSON. At the root, this replaces the whole document.
+    pub async fn set_as<V: serde::Serialize>(&self, value: &V) -> StoreResult<(), S>
+    where
+        S: AddressableSet<Value, JsonPath>,
+        S::Error: From<serde_json::Error>,
+    {
+        let value = serde_json::to_value(value)?;
+
+        AddressableSet::<Value, _>::set_addr(&self.store, &self.address, &Some(value)).await
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'a, S> Location<JsonPath, S>
+where
+    S: 'a + Store + AddressableTree<'a, JsonPath, JsonPath> + AddressableGet<Value, JsonPath>,
+    S::AddedAddress: std::fmt::Debug,
+    JsonPath: SubAddress<S::AddedAddress, Output = JsonPath>,
+{
+    /// Stream all the scalar values under this location, with their paths
+    /// relative to it, e.g. to export a config as dotted keys.
+    ///
+    /// Empty objects and arrays have no scalars, so they are skipped.
+    ///
+    /// ```
+    /// use futures::TryStreamExt;
+    /// use serde_json::json;
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let store = json_value_store(json!({"db": {"host": "localhost", "ports": [1, 2]}}))?;
+    ///
+    /// let flat = store
+    ///     .root()
+    ///     .flatten()
+    ///     .map_ok(|(path, value)| (path.to_string(), value))
+    ///     .try_collect::<Vec<_>>()
+    ///     .await?;
+    ///
+    /// assert_eq!(
+    ///     flat,
+    ///     vec![
+    ///         ("db.host".to_owned(), json!("localhost")),
+    ///         ("db.ports[0]".to_owned(), json!(1)),
+    ///         ("db.ports[1]".to_owned(), json!(2)),
+    ///     ]
+    /// );
+    ///
+    /// let copy = json_value_store(json!(null))?;
+    /// copy.path("backup")?
+    ///     .import_flat(store.root().flatten().try_collect::<Vec<_>>().await?)
+    ///     .await?;
+    ///
+    /// assert_eq!(copy.path("backup.db.ports[1]")?.getv().await?, Some(json!(2)));
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub fn flatten(&self) -> impl 'a + Stream<Item = StoreResult<(JsonPath, Value), S>> {
+        let store = self.store.clone();
+        let prefix = self.address.0.len();
+
+        self.walk_tree_recursively().try_filter_map(move |bl| {
+            let store = store.clone();
+
+            async move {
+                let BranchOrLeaf::Leaf(path) = bl else {
+                    return Ok(None);
+                };
+
+                let value = AddressableGet::<Value, _>::addr_get(&store, &path).await?;
+
+                Ok(value.map(|v| (JsonPath(path.0[prefix..].to_vec()), v)))
             }
         })
     }
+
+    /// Write the values at the paths relative to this location, creating
+    /// the objects and arrays on the way. The opposite of [`flatten`](Location::flatten).
+    pub async fn import_flat<I>(&self, items: I) -> StoreResult<(), S>
+    where
+        I: IntoIterator<Item = (JsonPath, Value)>,
+        S: AddressableSet<Value, JsonPath>,
+    {
+        for (path, value) in items {
+            let mut addr = self.address.clone();
+            addr.0.extend(path.0);
+
+            AddressableSet::<Value, _>::set_addr(&self.store, &addr, &Some(value)).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Read the scalars under this location into a flat store keyed by their dotted paths,
+    /// for code that expects a key-value store. See [`flatten`](Location::flatten).
+    ///
+    /// The map is a copy: changing it doesn't change the tree, push it back with
+    /// [`apply_map`](Location::apply_map).
+    ///
+    /// ```
+    /// use serde_json::json;
+    ///
+    /// use anystore::stores::json::*;
+    /// use anystore::store::StoreEx;
+    ///
+    /// # tokio_test::block_on(async {
+    /// let store = json_value_store(json!({"db": {"host": "localhost", "port": 5432}}))?;
+    ///
+    /// let map = store.path("db")?.flatten_to_map().await?;
+    /// map.path("port")?.setv(&Some(json!(6543))).await?;
+    /// assert_eq!(store.path("db.port")?.getv().await?, Some(json!(5432)));
+    ///
+    /// store.path("db")?.apply_map(&map).await?;
+    /// assert_eq!(store.path("db.port")?.getv().await?, Some(json!(6543)));
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    pub async fn flatten_to_map(&self) -> StoreResult<HashMapStore<Value>, S> {
+        let map = self
+            .flatten()
+            .map_ok(|(path, value)| (path.to_string(), value))
+            .try_collect()
+            .await?;
+
+        Ok(HashMapStore::new(map))
+    }
+
+    /// Write the values of a flat map, keyed by paths relative to this location,
+    /// like [`import_flat`](Location::import_flat). The opposite of [`flatten_to_map`](Location::flatten_to_map).
+    ///
+    /// Only writes: the paths that are not in the map are left as they are.
+    pub async fn apply_map(&self, map: &HashMapStore<Value>) -> StoreResult<(), S>
+    where
+        S: AddressableSet<Value, JsonPath>,
+        S::Error: From<JsonPathParseError>,
+    {
+        let mut items = map.snapshot().await.into_iter().collect::<Vec<_>>();
+        items.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let items = items
+            .into_iter()
+            .map(|(key, value)| Ok((JsonPath(vec![]).path(&key)?, value)))
+            .collect::<Result<Vec<_>, JsonPathParseError>>()?;
+
+        self.import_flat(items).await
+    }
+}
+
+/// Errors of [`LocalDynLocation`]: whatever error the underlying store returned.
+#[cfg(feature = "json")]
+pub type LocalDynLocationError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Read-only view of a [`Location`] with the store and address types erased,
+/// so that locations in different stores can be kept together,
+/// e.g. in a `Vec<Box<dyn LocalDynLocation>>`.
+///
+/// Values are read as JSON, and children are listed as their displayed added addresses.
+/// Any `Location` whose default value is serializable, and that can be listed
+/// and checked for existence, is a `LocalDynLocation`.
+///
+/// It's for a single task, e.g. a UI's event loop: the futures are not `Send`,
+/// as the stores' futures aren't either (e.g. the JSON stores' listings), so the locations
+/// can't be moved to other tasks.
+///
+/// ```
+/// use serde_json::json;
+///
+/// use anystore::location::LocalDynLocation;
+/// use anystore::stores::json::*;
+/// use anystore::store::StoreEx;
+/// use anystore::wrappers::filter_addresses::FilterAddressesWrapperStore;
+///
+/// # tokio_test::block_on(async {
+/// let store = json_value_store(json!({"a": {"b": 1}, "_c": 2}))?;
+/// let filtered = FilterAddressesWrapperStore::new(store.clone(), |s: String| !s.starts_with('_'));
+///
+/// let history: Vec<Box<dyn LocalDynLocation>> = vec![
+///     Box::new(store.root()),
+///     Box::new(filtered.root()),
+///     Box::new(store.path("a.b")?),
+/// ];
+///
+/// assert_eq!(history[0].list_children().await?, vec!["._c", ".a"]);
+/// assert_eq!(history[1].list_children().await?, vec![".a"]);
+/// assert_eq!(history[2].address(), "a.b");
+/// assert_eq!(history[2].get_json().await?, Some(json!(1)));
+///
+/// # Ok::<(), Box<dyn std::error::Error + Send + Sync>>(())
+/// # }).unwrap()
+/// ```
+#[cfg(feature = "json")]
+pub trait LocalDynLocation {
+    /// The address, as it's displayed.
+    fn address(&self) -> String;
+
+    /// Read the value of the default type, as JSON.
+    fn get_json(&self) -> LocalBoxFuture<'_, Result<Option<Value>, LocalDynLocationError>>;
+
+    /// List the children by the added parts of their addresses, e.g. `.key` or `[0]` in JSON.
+    fn list_children(&self) -> LocalBoxFuture<'_, Result<Vec<String>, LocalDynLocationError>>;
+
+    fn exists(&self) -> LocalBoxFuture<'_, Result<bool, LocalDynLocationError>>;
+}
+
+#[cfg(feature = "json")]
+impl<Addr, S, V> LocalDynLocation for Location<Addr, S>
+where
+    Addr: Address + std::fmt::Display + SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+    S: 'static
+        + Addressable<Addr, DefaultValue = V>
+        + AddressableGet<V, Addr>
+        + AddressableGet<Existence, Addr>
+        + AddressableList<'static, Addr>,
+    S::AddedAddress: std::fmt::Display,
+    V: serde::Serialize,
+{
+    fn address(&self) -> String {
+        self.address.to_string()
+    }
+
+    fn get_json(&self) -> LocalBoxFuture<'_, Result<Option<Value>, LocalDynLocationError>> {
+        Box::pin(async move {
+            match self.getv().await? {
+                Some(v) => Ok(Some(serde_json::to_value(v)?)),
+                None => Ok(None),
+            }
+        })
+    }
+
+    fn list_children(&self) -> LocalBoxFuture<'_, Result<Vec<String>, LocalDynLocationError>> {
+        Box::pin(async move {
+            Ok(self
+                .list()
+                .map_ok(|(added, _)| added.to_string())
+                .try_collect()
+                .await?)
+        })
+    }
+
+    fn exists(&self) -> LocalBoxFuture<'_, Result<bool, LocalDynLocationError>> {
+        Box::pin(async move { Ok(Location::exists(self).await?) })
+    }
 }
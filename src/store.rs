@@ -11,8 +11,58 @@ use crate::{
 /// This and the related traits are what you need to implement
 /// if you want to add a store.
 pub trait Store: Clone {
-    type Error: std::fmt::Debug + std::fmt::Display + Send + Sync + 'static;
+    /// Every store error is a proper `std::error::Error`, so it works with `?`,
+    /// `anyhow`, and can be boxed by the wrappers.
+    type Error: std::error::Error + Send + Sync + 'static;
     type RootAddress: Address + From<UniqueRootAddress> = UniqueRootAddress;
+
+    /// What this store supports, for tools that adapt to the store at runtime.
+    ///
+    /// Stores override this to reflect their implementations; the default is read-only.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// The shape of the content at the root, for tools that pick how to show a store
+    /// before listing it.
+    ///
+    /// By default, it's a tree if the store can list, and a value otherwise.
+    fn root_kind(&self) -> RootKind {
+        if self.capabilities().can_list {
+            RootKind::Tree
+        } else {
+            RootKind::Value
+        }
+    }
+
+    /// Write everything the store still holds in memory, e.g. before the app exits.
+    ///
+    /// The default does nothing, for the stores that write right away.
+    /// Wrappers pass it on to the stores they wrap.
+    async fn flush(&self) -> StoreResult<(), Self> {
+        Ok(())
+    }
+}
+
+/// The shape of a store's content, see [`Store::root_kind`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RootKind {
+    /// A single value, e.g. a memory cell.
+    Value,
+    /// A flat collection of values, e.g. a vector or a key-value map.
+    List,
+    /// Branches of values, e.g. a file system or a JSON document.
+    Tree,
+}
+
+/// Runtime description of what a store supports. See [`Store::capabilities`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Capabilities {
+    pub can_write: bool,
+    pub can_list: bool,
+    pub can_delete: bool,
+    pub can_query: bool,
+    pub can_watch: bool,
 }
 
 pub trait StoreEx<Root: Address + From<UniqueRootAddress>>: Store {
@@ -38,10 +88,72 @@ pub trait StoreEx<Root: Address + From<UniqueRootAddress>>: Store {
     {
         self.root().path(p)
     }
+
+    /// Like [`path`](StoreEx::path), but without requiring the store error to absorb
+    /// the address parsing error: it's returned as [`PathError::ParseError`].
+    ///
+    #[cfg_attr(not(feature = "json"), doc = "```ignore")]
+    #[cfg_attr(feature = "json", doc = "```")]
+    /// use serde_json::json;
+    ///
+    /// use anystore::store::{PathError, StoreEx};
+    /// use anystore::stores::json::json_value_store;
+    /// use anystore::wrappers::map_error::MapErrorStore;
+    ///
+    /// /// No `From<JsonPathParseError>`, so `store.path(...)` doesn't compile.
+    /// #[derive(Debug, thiserror::Error)]
+    /// #[error("app error: {0}")]
+    /// struct AppError(String);
+    ///
+    /// # tokio_test::block_on(async {
+    /// let store = MapErrorStore::new(json_value_store(json!({"a": [1]}))?, |e| {
+    ///     AppError(e.to_string())
+    /// });
+    ///
+    /// assert_eq!(store.try_path("a[0]")?.getv().await?, Some(json!(1)));
+    /// assert!(matches!(store.try_path("a]"), Err(PathError::ParseError(_))));
+    ///
+    /// // the store errors that follow convert into `PathError::StoreError`
+    /// let write = async {
+    ///     store.try_path("a.b")?.setv(&Some(json!(2))).await?;
+    ///     Ok::<_, PathError<_, _>>(())
+    /// };
+    /// assert!(matches!(write.await, Err(PathError::StoreError(AppError(_)))));
+    ///
+    /// # Ok::<(), anyhow::Error>(())
+    /// # }).unwrap()
+    /// ```
+    fn try_path<Addr: Address>(
+        &self,
+        p: &str,
+    ) -> Result<Location<Addr, Self>, PathError<Self::Error, <Root as PathAddress>::Error>>
+    where
+        Self: Addressable<Root> + Addressable<Addr>,
+        Root: PathAddress<Output = Addr>,
+    {
+        self.root().try_path(p)
+    }
 }
 
 impl<S: Store> StoreEx<S::RootAddress> for S {}
 
+/// An error of [`StoreEx::try_path`]: the address didn't parse, or, when the
+/// same type is used for what's done with the location next, the store failed.
+#[derive(Debug, thiserror::Error)]
+pub enum PathError<E, P> {
+    #[error("StoreError({0})")]
+    StoreError(E),
+
+    #[error("ParseError({0})")]
+    ParseError(P),
+}
+
+impl<E, P> From<E> for PathError<E, P> {
+    fn from(value: E) -> Self {
+        Self::StoreError(value)
+    }
+}
+
 pub type StoreResult<V, S> = Result<V, <S as Store>::Error>;
 
 // pub struct SharedStore<S: Store> {
@@ -70,3 +182,89 @@ pub type StoreResult<V, S> = Result<V, <S as Store>::Error>;
 // pub trait WriteStore<A: AddressFor<V, Self>, V>: Store {
 //     async fn _write(&self, value: Option<V>, addr: A) -> StoreResult<(), Self>;
 // }
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        address::primitive::WholeStore,
+        store::{Capabilities, Store, StoreEx},
+        stores::cell::MemoryCellStore,
+        stores::indexed_vec::IndexedVecStore,
+    };
+
+    #[tokio::test]
+    async fn test_root_memory() -> Result<(), anyhow::Error> {
+        let cell = MemoryCellStore::new(Some(1));
+        assert_eq!(cell.root().getv().await?, Some(1));
+        cell.root().setv(&Some(2)).await?;
+        assert_eq!(cell.root().getv().await?, Some(2));
+
+        let vec = IndexedVecStore::new(vec![1, 2], |v| *v);
+        assert_eq!(vec.root().getv().await?, Some(vec![1, 2]));
+        vec.root().setv(&Some(vec![3])).await?;
+        assert_eq!(vec.root().getv().await?, Some(vec![3]));
+        assert_eq!(vec.root().get().await?, Some(WholeStore(vec![3])));
+        assert_eq!(cell.root().get().await?, Some(WholeStore(2)));
+
+        assert_eq!(
+            cell.capabilities(),
+            Capabilities {
+                can_write: true,
+                can_delete: true,
+                ..Default::default()
+            }
+        );
+        assert_ne!(cell.capabilities(), Capabilities::default());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn test_root_json() -> Result<(), anyhow::Error> {
+        use serde_json::json;
+
+        let store = crate::stores::json::json_value_store(json!({"a": 1}))?;
+        assert_eq!(store.root().getv().await?, Some(json!({"a": 1})));
+        assert_eq!(store.root().get().await?, Some(WholeStore(json!({"a": 1}))));
+        assert!(store
+            .path("a")?
+            .get::<WholeStore<serde_json::Value>>()
+            .await
+            .is_err());
+        store.root().setv(&Some(json!([2]))).await?;
+        assert_eq!(store.root().getv().await?, Some(json!([2])));
+        store.root().setv(&None).await?;
+        assert_eq!(store.root().getv().await?, None);
+
+        let filtered = crate::wrappers::filter_addresses::FilterAddressesWrapperStore::new(
+            store.clone(),
+            |_: String| true,
+        );
+        assert_eq!(filtered.capabilities(), store.capabilities());
+        assert!(store.capabilities().can_list);
+        assert!(!store.capabilities().can_query);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn test_root_fs() -> Result<(), anyhow::Error> {
+        use crate::{
+            stores::fs::{FileOrDir, FileSystemStore},
+            test_util::temp_dir,
+        };
+
+        let dir = temp_dir();
+        let store = FileSystemStore::new(dir.clone());
+
+        assert_eq!(store.root().getv().await?, None);
+        store.root().setv(&Some(FileOrDir::Dir)).await?;
+        assert_eq!(store.root().getv().await?, Some(FileOrDir::Dir));
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+}
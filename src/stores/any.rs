@@ -0,0 +1,382 @@
+#[cfg(feature = "fs")]
+use std::path::PathBuf;
+
+use derive_more::{Display, From};
+use futures::{stream, StreamExt, TryStreamExt};
+use serde::Deserialize;
+use serde_json::Value;
+use thiserror::Error;
+
+#[cfg(feature = "airtable")]
+use crate::stores::cloud::airtable::{AirtableStore, AirtableStoreError};
+#[cfg(feature = "fs")]
+use crate::stores::{
+    fs::{FileStoreError, FileSystemStore, RelativePath},
+    located::json::{JsonFormat, LocatedJsonStore},
+};
+use crate::{
+    address::{
+        primitive::{Existence, Present},
+        traits::{AddressableGet, AddressableList, AddressableSet},
+        Addressable,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+    stores::{
+        json::{json_value_store, JsonPath, JsonPathParseError, JsonPathPart, JsonValueStore},
+        located::json::LocatedJsonStoreError,
+    },
+};
+
+#[derive(From, Display, Debug, Error)]
+pub enum AnyStoreError {
+    Json(LocatedJsonStoreError),
+
+    #[cfg(feature = "fs")]
+    FileSystem(FileStoreError),
+
+    #[cfg(feature = "airtable")]
+    Airtable(AirtableStoreError),
+
+    #[from(ignore)]
+    Unsupported(String),
+}
+
+impl From<JsonPathParseError> for AnyStoreError {
+    fn from(value: JsonPathParseError) -> Self {
+        AnyStoreError::Json(value.into())
+    }
+}
+
+/// Which store to build, e.g. deserialized from a config file:
+///
+/// ```json
+/// {"type": "json_file", "path": "config.json", "pretty": true}
+/// ```
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StoreConfig {
+    /// An in-memory JSON value
+    Json {
+        #[serde(default)]
+        value: Value,
+    },
+
+    /// A directory
+    #[cfg(feature = "fs")]
+    FileSystem { path: PathBuf },
+
+    /// A JSON file
+    #[cfg(feature = "fs")]
+    JsonFile {
+        path: PathBuf,
+        #[serde(default)]
+        pretty: bool,
+    },
+
+    #[cfg(feature = "airtable")]
+    Airtable { token: String },
+}
+
+/// One of the stores, chosen at runtime, e.g. with [`AnyStore::from_config`].
+///
+/// The JSON-like stores are addressable by [`JsonPath`], the file system by
+/// `RelativePath`. Using an address the active store doesn't support fails with
+/// [`AnyStoreError::Unsupported`]. Airtable has no shared addresses:
+/// match on the variant to use it.
+///
+/// ```
+/// use serde_json::json;
+///
+/// use anystore::stores::any::*;
+/// use anystore::stores::json::JsonPath;
+/// use anystore::address::primitive::UniqueRootAddress;
+/// use anystore::store::StoreEx;
+///
+/// # tokio_test::block_on(async {
+/// let config: StoreConfig = serde_json::from_value(json!({"type": "json", "value": {"a": 1}}))?;
+/// let store = AnyStore::from_config(config)?;
+///
+/// let a = store.sub(JsonPath::from(UniqueRootAddress)).path("a")?;
+/// assert_eq!(a.getv().await?, Some(json!(1)));
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+#[derive(Clone)]
+pub enum AnyStore {
+    Json(JsonValueStore),
+
+    #[cfg(feature = "fs")]
+    FileSystem(FileSystemStore),
+
+    #[cfg(feature = "fs")]
+    JsonFile(LocatedJsonStore<RelativePath, FileSystemStore>),
+
+    #[cfg(feature = "airtable")]
+    Airtable(AirtableStore),
+}
+
+impl AnyStore {
+    pub fn from_config(config: StoreConfig) -> Result<Self, AnyStoreError> {
+        Ok(match config {
+            StoreConfig::Json { value } => AnyStore::Json(json_value_store(value)?),
+
+            #[cfg(feature = "fs")]
+            StoreConfig::FileSystem { path } => AnyStore::FileSystem(FileSystemStore::new(path)),
+
+            #[cfg(feature = "fs")]
+            StoreConfig::JsonFile { path, pretty } => {
+                let file_name = path
+                    .file_name()
+                    .ok_or_else(|| AnyStoreError::Unsupported(format!("Not a file: {path:?}")))?
+                    .to_owned();
+                let dir = path.parent().map(|p| p.to_owned()).unwrap_or_default();
+
+                let location =
+                    crate::location::Location::new(file_name.into(), FileSystemStore::new(dir));
+
+                let format = JsonFormat {
+                    pretty,
+                    ..Default::default()
+                };
+
+                AnyStore::JsonFile(LocatedJsonStore::new_streaming(location, format))
+            }
+
+            #[cfg(feature = "airtable")]
+            StoreConfig::Airtable { token } => AnyStore::Airtable(AirtableStore::new(&token)?),
+        })
+    }
+
+    fn unsupported<T>(&self, what: &str) -> StoreResult<T, Self> {
+        Err(AnyStoreError::Unsupported(format!(
+            "{what} is not supported by this store"
+        )))
+    }
+}
+
+impl Store for AnyStore {
+    type Error = AnyStoreError;
+
+    fn capabilities(&self) -> Capabilities {
+        match self {
+            AnyStore::Json(s) => s.capabilities(),
+            #[cfg(feature = "fs")]
+            AnyStore::FileSystem(s) => s.capabilities(),
+            #[cfg(feature = "fs")]
+            AnyStore::JsonFile(s) => s.capabilities(),
+            #[cfg(feature = "airtable")]
+            AnyStore::Airtable(s) => s.capabilities(),
+        }
+    }
+
+    fn root_kind(&self) -> RootKind {
+        match self {
+            AnyStore::Json(s) => s.root_kind(),
+            #[cfg(feature = "fs")]
+            AnyStore::FileSystem(s) => s.root_kind(),
+            #[cfg(feature = "fs")]
+            AnyStore::JsonFile(s) => s.root_kind(),
+            #[cfg(feature = "airtable")]
+            AnyStore::Airtable(s) => s.root_kind(),
+        }
+    }
+
+    async fn flush(&self) -> StoreResult<(), Self> {
+        match self {
+            AnyStore::Json(s) => Ok(s.flush().await?),
+            #[cfg(feature = "fs")]
+            AnyStore::FileSystem(s) => Ok(s.flush().await?),
+            #[cfg(feature = "fs")]
+            AnyStore::JsonFile(s) => Ok(s.flush().await?),
+            #[cfg(feature = "airtable")]
+            AnyStore::Airtable(s) => Ok(s.flush().await?),
+        }
+    }
+}
+
+impl Addressable<JsonPath> for AnyStore {
+    type DefaultValue = Value;
+}
+
+impl AddressableGet<Value, JsonPath> for AnyStore {
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Value>, Self> {
+        match self {
+            AnyStore::Json(s) => Ok(AddressableGet::<Value, _>::addr_get(s, addr).await?),
+            #[cfg(feature = "fs")]
+            AnyStore::JsonFile(s) => Ok(AddressableGet::<Value, _>::addr_get(s, addr).await?),
+            #[allow(unreachable_patterns)]
+            _ => self.unsupported("JsonPath"),
+        }
+    }
+}
+
+impl AddressableSet<Value, JsonPath> for AnyStore {
+    async fn set_addr(&self, addr: &JsonPath, value: &Option<Value>) -> StoreResult<(), Self> {
+        match self {
+            AnyStore::Json(s) => Ok(s.set_addr(addr, value).await?),
+            #[cfg(feature = "fs")]
+            AnyStore::JsonFile(s) => Ok(s.set_addr(addr, value).await?),
+            #[allow(unreachable_patterns)]
+            _ => self.unsupported("JsonPath"),
+        }
+    }
+}
+
+impl AddressableGet<Existence, JsonPath> for AnyStore {
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Existence>, Self> {
+        match self {
+            AnyStore::Json(s) => Ok(AddressableGet::<Existence, _>::addr_get(s, addr).await?),
+            #[cfg(feature = "fs")]
+            AnyStore::JsonFile(s) => Ok(AddressableGet::<Existence, _>::addr_get(s, addr).await?),
+            #[allow(unreachable_patterns)]
+            _ => self.unsupported("JsonPath"),
+        }
+    }
+}
+
+impl AddressableGet<Present, JsonPath> for AnyStore {
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Present>, Self> {
+        let e = AddressableGet::<Existence, _>::addr_get(self, addr).await?;
+
+        Ok(Some(Present(e.is_some())))
+    }
+}
+
+impl<'a> AddressableList<'a, JsonPath> for AnyStore {
+    type AddedAddress = JsonPathPart;
+
+    type ItemAddress = JsonPath;
+
+    fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
+        match self {
+            AnyStore::Json(s) => s.list(addr).map_err(AnyStoreError::from).boxed_local(),
+            #[cfg(feature = "fs")]
+            AnyStore::JsonFile(s) => s.list(addr).map_err(AnyStoreError::from).boxed_local(),
+            #[allow(unreachable_patterns)]
+            _ => stream::iter([self.unsupported("JsonPath")]).boxed_local(),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl Addressable<RelativePath> for AnyStore {
+    type DefaultValue = String;
+}
+
+#[cfg(feature = "fs")]
+impl AddressableGet<String, RelativePath> for AnyStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<String>, Self> {
+        match self {
+            AnyStore::FileSystem(s) => Ok(AddressableGet::<String, _>::addr_get(s, addr).await?),
+            _ => self.unsupported("RelativePath"),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl AddressableSet<String, RelativePath> for AnyStore {
+    async fn set_addr(&self, addr: &RelativePath, value: &Option<String>) -> StoreResult<(), Self> {
+        match self {
+            AnyStore::FileSystem(s) => Ok(s.set_addr(addr, value).await?),
+            _ => self.unsupported("RelativePath"),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl AddressableGet<Existence, RelativePath> for AnyStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<Existence>, Self> {
+        match self {
+            AnyStore::FileSystem(s) => Ok(AddressableGet::<Existence, _>::addr_get(s, addr).await?),
+            _ => self.unsupported("RelativePath"),
+        }
+    }
+}
+
+#[cfg(feature = "fs")]
+impl AddressableGet<Present, RelativePath> for AnyStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<Present>, Self> {
+        let e = AddressableGet::<Existence, _>::addr_get(self, addr).await?;
+
+        Ok(Some(Present(e.is_some())))
+    }
+}
+
+#[cfg(feature = "fs")]
+impl<'a> AddressableList<'a, RelativePath> for AnyStore {
+    type AddedAddress = RelativePath;
+
+    type ItemAddress = RelativePath;
+
+    fn list(&self, addr: &RelativePath) -> Self::ListOfAddressesStream {
+        match self {
+            AnyStore::FileSystem(s) => s.list(addr).map_err(AnyStoreError::from).boxed_local(),
+            _ => stream::iter([self.unsupported("RelativePath")]).boxed_local(),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "fs")]
+mod test {
+    use serde_json::json;
+
+    use crate::{
+        address::primitive::{Present, UniqueRootAddress},
+        store::StoreEx,
+        stores::{fs::RelativePath, json::JsonPath},
+        test_util::temp_dir,
+    };
+
+    use super::{AnyStore, AnyStoreError, StoreConfig};
+
+    #[tokio::test]
+    async fn test_from_config() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let config: StoreConfig = serde_json::from_value(json!({
+            "type": "json_file",
+            "path": dir.join("test.json"),
+        }))?;
+        let json_file = AnyStore::from_config(config)?;
+
+        json_file
+            .sub(JsonPath::from(UniqueRootAddress))
+            .path("a.b")?
+            .setv(&Some(json!(1)))
+            .await?;
+
+        let config: StoreConfig = serde_json::from_value(json!({
+            "type": "file_system",
+            "path": dir,
+        }))?;
+        let fs = AnyStore::from_config(config)?;
+
+        assert_eq!(
+            fs.sub(RelativePath::from("test.json")).getv().await?,
+            Some(r#"{"a":{"b":1}}"#.to_owned())
+        );
+        assert!(matches!(
+            fs.sub(JsonPath::from(UniqueRootAddress)).getv().await,
+            Err(AnyStoreError::Unsupported(_))
+        ));
+        assert_eq!(
+            fs.sub(RelativePath::from("test.json")).get().await?,
+            Some(Present(true))
+        );
+        assert_eq!(
+            json_file
+                .sub(JsonPath::from(UniqueRootAddress))
+                .path("a.c")?
+                .get()
+                .await?,
+            Some(Present(false))
+        );
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+}
@@ -1,20 +1,43 @@
 use std::sync::Arc;
 
+#[cfg(feature = "json")]
+use futures::{stream, StreamExt, TryStreamExt};
+#[cfg(feature = "json")]
+use serde_json::Value;
 use thiserror::Error;
 use tokio::sync::RwLock;
 
 use crate::{
     address::{
-        primitive::UniqueRootAddress,
-        traits::{AddressableGet, AddressableSet},
+        primitive::{UniqueRootAddress, WholeStore},
+        traits::{AddressableCas, AddressableGet, AddressableSet, AddressableSwap},
         Addressable,
     },
-    store::Store,
+    store::{Capabilities, RootKind, Store},
+};
+#[cfg(feature = "json")]
+use crate::{
+    address::{
+        traits::{AddressableList, AddressableTree, BranchOrLeaf},
+        SubAddress,
+    },
+    store::StoreResult,
+    stores::json::{
+        paths::*,
+        traverse::{get_pathvalue, set_pathvalue},
+    },
 };
 
 #[derive(Debug, Error, Eq, PartialEq)]
-pub enum MemoryCellStoreError {}
+pub enum MemoryCellStoreError {
+    #[cfg(feature = "json")]
+    #[error("JsonError({0})")]
+    JsonError(String),
+}
 
+/// Arbitrary memory cell as a readable/writable location.
+///
+/// The root (`store.root()`) is the cell itself.
 #[derive(Debug, Clone)]
 pub struct MemoryCellStore<V: Clone> {
     value: Arc<RwLock<Option<V>>>,
@@ -26,10 +49,79 @@ impl<V: Clone> MemoryCellStore<V> {
             value: Arc::new(RwLock::new(value)),
         }
     }
+
+    /// Clone out the current value.
+    pub async fn snapshot(&self) -> Option<V> {
+        self.value.read().await.clone()
+    }
+}
+
+#[cfg(feature = "json")]
+impl MemoryCellStore<String> {
+    /// Store a value serialized as JSON, e.g. to use it with
+    /// [`LocatedJsonStore`](crate::stores::located::json::LocatedJsonStore).
+    pub fn from_json<T: serde::Serialize>(value: &T) -> Result<Self, serde_json::Error> {
+        Ok(Self::new(Some(serde_json::to_string(value)?)))
+    }
+}
+
+#[cfg(feature = "json")]
+impl MemoryCellStore<Value> {
+    /// The number of the top-level object keys or array items.
+    /// `None` if there's no value, or it's a scalar, which has no items to count.
+    pub async fn len(&self) -> Option<usize> {
+        match &*self.value.read().await {
+            Some(Value::Array(arr)) => Some(arr.len()),
+            Some(Value::Object(obj)) => Some(obj.len()),
+            _ => None,
+        }
+    }
+
+    /// Whether there's no value, or it's an empty array or object. A scalar isn't empty.
+    pub async fn is_empty(&self) -> bool {
+        match &*self.value.read().await {
+            Some(Value::Array(arr)) => arr.is_empty(),
+            Some(Value::Object(obj)) => obj.is_empty(),
+            Some(_) => false,
+            None => true,
+        }
+    }
+}
+
+/// The value, as `Option<V>`. It's read with `try_read`, so serializing fails
+/// if it's being written at the same time.
+#[cfg(feature = "serde")]
+impl<V: Clone + serde::Serialize> serde::Serialize for MemoryCellStore<V> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let value = self.value.try_read().map_err(serde::ser::Error::custom)?;
+
+        value.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V: Clone + serde::Deserialize<'de>> serde::Deserialize<'de> for MemoryCellStore<V> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::new(Option::<V>::deserialize(deserializer)?))
+    }
 }
 
 impl<V: Clone> Store for MemoryCellStore<V> {
     type Error = MemoryCellStoreError;
+
+    /// Listing is only available for `MemoryCellStore<Value>`, so it's not reported here.
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            can_write: true,
+            can_delete: true,
+            ..Default::default()
+        }
+    }
+
+    /// Even when the value is a JSON object: the cell is one value.
+    fn root_kind(&self) -> RootKind {
+        RootKind::Value
+    }
 }
 
 impl<V: Clone> Addressable<UniqueRootAddress> for MemoryCellStore<V> {
@@ -43,6 +135,15 @@ impl<V: Clone> AddressableGet<V, UniqueRootAddress> for MemoryCellStore<V> {
     }
 }
 
+impl<V: Clone> AddressableGet<WholeStore<V>, UniqueRootAddress> for MemoryCellStore<V> {
+    async fn addr_get(
+        &self,
+        _address: &UniqueRootAddress,
+    ) -> Result<Option<WholeStore<V>>, Self::Error> {
+        Ok(self.value.read().await.clone().map(WholeStore))
+    }
+}
+
 impl<V: Clone> AddressableSet<V, UniqueRootAddress> for MemoryCellStore<V> {
     async fn set_addr(
         &self,
@@ -53,3 +154,270 @@ impl<V: Clone> AddressableSet<V, UniqueRootAddress> for MemoryCellStore<V> {
         Ok(())
     }
 }
+
+impl<V: Clone + PartialEq> AddressableCas<V, UniqueRootAddress> for MemoryCellStore<V> {
+    async fn compare_and_swap(
+        &self,
+        _address: &UniqueRootAddress,
+        expected: &Option<V>,
+        new: &Option<V>,
+    ) -> Result<bool, Self::Error> {
+        let mut value = self.value.write().await;
+
+        if *value != *expected {
+            return Ok(false);
+        }
+
+        *value = new.clone();
+        Ok(true)
+    }
+}
+
+impl<V: Clone> AddressableSwap<V, UniqueRootAddress> for MemoryCellStore<V> {
+    async fn swap(
+        &self,
+        _address: &UniqueRootAddress,
+        value: &Option<V>,
+    ) -> Result<Option<V>, Self::Error> {
+        let mut current = self.value.write().await;
+
+        Ok(std::mem::replace(&mut *current, value.clone()))
+    }
+}
+
+/// A cell with a JSON value can be browsed without wrapping it
+/// into a [`LocatedJsonStore`](crate::stores::located::json::LocatedJsonStore):
+/// listing the root yields the top-level object keys or array indices.
+#[cfg(feature = "json")]
+impl Addressable<JsonPath> for MemoryCellStore<Value> {
+    type DefaultValue = Value;
+}
+
+#[cfg(feature = "json")]
+impl AddressableGet<Value, JsonPath> for MemoryCellStore<Value> {
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Value>, Self> {
+        let value = self.value.read().await;
+
+        let Some(value) = &*value else {
+            return Ok(None);
+        };
+
+        Ok(get_pathvalue(value, &addr.0[..])
+            .map_err(|e| MemoryCellStoreError::JsonError(e.to_string()))?
+            .cloned())
+    }
+}
+
+#[cfg(feature = "json")]
+impl AddressableSet<Value, JsonPath> for MemoryCellStore<Value> {
+    async fn set_addr(&self, addr: &JsonPath, value: &Option<Value>) -> StoreResult<(), Self> {
+        let mut cur = self.value.write().await;
+
+        // deleting the root empties the cell
+        if addr.0.is_empty() && value.is_none() {
+            *cur = None;
+            return Ok(());
+        }
+
+        let cur = cur.get_or_insert(Value::Null);
+
+        set_pathvalue(cur, &addr.0[..], value)
+            .map_err(|e| MemoryCellStoreError::JsonError(e.to_string()))
+    }
+}
+
+#[cfg(feature = "json")]
+impl MemoryCellStore<Value> {
+    async fn child_parts(&self, addr: &JsonPath) -> StoreResult<Vec<JsonPathPart>, Self> {
+        let value = self.value.read().await;
+
+        let value = match &*value {
+            None => return Ok(vec![]),
+            Some(value) => get_pathvalue(value, &addr.0[..])
+                .map_err(|e| MemoryCellStoreError::JsonError(e.to_string()))?,
+        };
+
+        match value {
+            None => Err(MemoryCellStoreError::JsonError(
+                "Path doesn't exist".to_owned(),
+            )),
+            Some(Value::Array(arr)) => Ok((0..arr.len()).map(JsonPathPart::Index).collect()),
+            Some(Value::Object(obj)) => Ok(obj
+                .keys()
+                .map(|k| JsonPathPart::Key(k.to_owned()))
+                .collect()),
+            Some(value) => Err(MemoryCellStoreError::JsonError(format!(
+                "Can't list: {value}"
+            ))),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'a> AddressableList<'a, UniqueRootAddress> for MemoryCellStore<Value> {
+    type AddedAddress = JsonPath;
+
+    type ItemAddress = JsonPath;
+
+    fn list(&self, _addr: &UniqueRootAddress) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+
+        stream::once(async move {
+            let parts = this.child_parts(&JsonPath(vec![])).await?;
+
+            Ok(stream::iter(parts.into_iter().map(|p| {
+                let path = JsonPath(vec![p]);
+                Ok((path.clone(), path))
+            })))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'a> AddressableList<'a, JsonPath> for MemoryCellStore<Value> {
+    type AddedAddress = JsonPathPart;
+
+    type ItemAddress = JsonPath;
+
+    fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        stream::once(async move {
+            let parts = this.child_parts(&addr).await?;
+
+            Ok(stream::iter(
+                parts
+                    .into_iter()
+                    .map(move |p| Ok((p.clone(), addr.clone().sub(p)))),
+            ))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'a> AddressableTree<'a, JsonPath, JsonPath> for MemoryCellStore<Value> {
+    async fn branch_or_leaf(
+        &self,
+        addr: JsonPath,
+    ) -> StoreResult<BranchOrLeaf<JsonPath, JsonPath>, Self> {
+        let value: Option<Value> = self.addr_get(&addr).await?;
+
+        match value {
+            None => Err(MemoryCellStoreError::JsonError(
+                "Path doesn't exist".to_owned(),
+            )),
+            Some(Value::Array(_)) | Some(Value::Object(_)) => Ok(BranchOrLeaf::Branch(addr)),
+            Some(_) => Ok(BranchOrLeaf::Leaf(addr)),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    use crate::{
+        store::{RootKind, Store, StoreEx},
+        stores::json::JsonPath,
+    };
+
+    use super::MemoryCellStore;
+
+    #[tokio::test]
+    async fn test_list() -> Result<(), anyhow::Error> {
+        let store = MemoryCellStore::new(Some(json!({"a": 1, "b": [1, 2]})));
+
+        let listed = store.root().list().try_collect::<Vec<_>>().await?;
+
+        assert_eq!(
+            listed
+                .iter()
+                .map(|(_, p)| p.to_string())
+                .collect::<Vec<_>>(),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            store.sub(listed[1].1.clone()).getv().await?,
+            Some(json!([1, 2]))
+        );
+
+        assert_eq!(store.len().await, Some(2));
+        assert!(!store.is_empty().await);
+
+        let all_paths = store
+            .sub(JsonPath(vec![]))
+            .walk_tree_recursively()
+            .map_ok(|v| v.to_string())
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(all_paths, vec!["a", "b", "b[0]", "b[1]"]);
+
+        let scalar = MemoryCellStore::new(Some(json!(7)));
+        assert!(scalar.root().list().try_collect::<Vec<_>>().await.is_err());
+        assert_eq!(scalar.len().await, None);
+        assert!(!scalar.is_empty().await);
+        assert_eq!(scalar.snapshot().await, Some(json!(7)));
+        scalar.sub(JsonPath(vec![])).setv(&None).await?;
+        assert_eq!(scalar.snapshot().await, None);
+        assert_eq!(scalar.len().await, None);
+        assert!(scalar.is_empty().await);
+        assert_eq!(MemoryCellStore::new(Some(json!([]))).len().await, Some(0));
+        assert_eq!(store.root_kind(), RootKind::Value);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_serde() -> Result<(), anyhow::Error> {
+        let store = MemoryCellStore::new(Some(json!({"a": [1, 2]})));
+
+        let serialized = serde_json::to_string(&store)?;
+        assert_eq!(serialized, r#"{"a":[1,2]}"#);
+
+        let restored: MemoryCellStore<serde_json::Value> = serde_json::from_str(&serialized)?;
+        assert_eq!(restored.snapshot().await, store.snapshot().await);
+
+        let empty: MemoryCellStore<String> = serde_json::from_str("null")?;
+        assert_eq!(empty.snapshot().await, None);
+
+        let _writing = store.value.write().await;
+        assert!(serde_json::to_string(&store).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compare_and_swap() -> Result<(), anyhow::Error> {
+        let cell = MemoryCellStore::new(Some(1));
+        let root = cell.root();
+
+        assert!(!root.compare_and_swap(&Some(2), &Some(3)).await?);
+        assert_eq!(cell.snapshot().await, Some(1));
+
+        assert!(root.compare_and_swap(&Some(1), &None).await?);
+        assert!(root.compare_and_swap(&None, &Some(4)).await?);
+        assert_eq!(cell.snapshot().await, Some(4));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_swap() -> Result<(), anyhow::Error> {
+        let cell = MemoryCellStore::new(Some(1));
+        let root = cell.root();
+
+        assert_eq!(root.swap(&Some(2)).await?, Some(1));
+        assert_eq!(root.swap(&None).await?, Some(2));
+        assert_eq!(root.swap(&Some(3)).await?, None);
+        assert_eq!(cell.snapshot().await, Some(3));
+
+        Ok(())
+    }
+}
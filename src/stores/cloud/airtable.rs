@@ -1,4 +1,10 @@
-use std::{collections::HashMap, fmt::Formatter, marker::PhantomData, sync::Arc, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Formatter,
+    marker::PhantomData,
+    sync::Arc,
+    time::Duration,
+};
 
 use derive_more::{Display, From};
 
@@ -7,19 +13,21 @@ use futures::{
     Stream, StreamExt, TryStreamExt,
 };
 use reqwest::Method;
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::fmt::Debug;
 use thiserror::Error;
 
 use crate::{
     address::{
+        primitive::{Existence, UniqueRootAddress},
         traits::{
-            AddressableGet, AddressableInsert, AddressableList, AddressableQuery, AddressableSet,
+            AddressableExistsMany, AddressableGet, AddressableInsert, AddressableList,
+            AddressableListFrom, AddressableQuery, AddressableSet,
         },
         Address, Addressable, SubAddress,
     },
-    store::Store,
+    store::{Capabilities, Store, StoreResult},
     util::ratelimiter::Ratelimiter,
 };
 
@@ -30,8 +38,32 @@ pub enum AirtableStoreError {
     #[display(fmt = "HttpError {_0}: {_1}")]
     HttpError(reqwest::StatusCode, Value),
 
+    /// A non-2xx response in Airtable's `{"error": {"type", "message"}}` shape
+    #[from(ignore)]
+    #[display(fmt = "Api {status}: {error_type}: {message}")]
+    Api {
+        status: reqwest::StatusCode,
+        error_type: String,
+        message: String,
+    },
+
     ReqwestError(reqwest::Error),
     JsonError(serde_json::Error),
+
+    #[from(ignore)]
+    #[display(fmt = "Timeout after {_0:?}")]
+    Timeout(Duration),
+}
+
+impl AirtableStoreError {
+    /// The status of the unsuccessful response, if that's what the error is.
+    pub fn status(&self) -> Option<reqwest::StatusCode> {
+        match self {
+            AirtableStoreError::HttpError(status, _) => Some(*status),
+            AirtableStoreError::Api { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
 }
 
 impl<'a> From<&'a str> for AirtableStoreError {
@@ -40,14 +72,27 @@ impl<'a> From<&'a str> for AirtableStoreError {
     }
 }
 
+/// Airtable as a store.
+///
+/// The root (`store.root()`) has no value of its own, navigate from it
+/// with `.sub(AirtableBasesRootAddr)` or `.sub(AirtableBase::by_id(...))`.
 #[derive(Clone)]
 pub struct AirtableStore {
     http_client: reqwest::Client,
     ratelimiter: Arc<Ratelimiter>,
+    timeout: Duration,
 }
 
 impl AirtableStore {
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// Uses [`AirtableStore::DEFAULT_TIMEOUT`] for every request.
     pub fn new(token: &str) -> Result<Self, AirtableStoreError> {
+        Self::new_with_timeout(token, Self::DEFAULT_TIMEOUT)
+    }
+
+    /// Every request that takes longer than `timeout` fails with [`AirtableStoreError::Timeout`].
+    pub fn new_with_timeout(token: &str, timeout: Duration) -> Result<Self, AirtableStoreError> {
         let headers = (&HashMap::from([
             ("Authorization".to_owned(), format!("Bearer {token}")),
             ("Content-Type".to_owned(), "application/json".to_owned()),
@@ -58,11 +103,21 @@ impl AirtableStore {
         Ok(AirtableStore {
             http_client: reqwest::Client::builder()
                 .default_headers(headers)
+                .timeout(timeout)
                 .build()?,
             ratelimiter: Arc::new(Ratelimiter::new(Duration::from_secs(1), 5)),
+            timeout,
         })
     }
 
+    fn reqwest_error(&self, e: reqwest::Error) -> AirtableStoreError {
+        if e.is_timeout() {
+            AirtableStoreError::Timeout(self.timeout)
+        } else {
+            e.into()
+        }
+    }
+
     async fn request(
         &self,
         method: Method,
@@ -78,16 +133,40 @@ impl AirtableStore {
             req = req.body(serde_json::to_string(&b)?)
         }
 
-        let resp = req.send().await?;
+        let resp = req.send().await.map_err(|e| self.reqwest_error(e))?;
 
         let status = resp.status();
-        let val = resp.text().await?;
-        let val = serde_json::from_str(&val)?;
+        let val = resp.text().await.map_err(|e| self.reqwest_error(e))?;
 
         if status.is_success() {
-            Ok(val)
+            Ok(serde_json::from_str(&val)?)
         } else {
-            Err(AirtableStoreError::HttpError(status, val))
+            Err(Self::error_response(status, val))
+        }
+    }
+
+    fn error_response(status: reqwest::StatusCode, body: String) -> AirtableStoreError {
+        let Ok(val) = serde_json::from_str::<Value>(&body) else {
+            return AirtableStoreError::HttpError(status, Value::String(body));
+        };
+
+        // it's either `{"error": {"type": ..., "message": ...}}` or `{"error": "TYPE"}`
+        let (error_type, message) = match val.get("error") {
+            Some(Value::String(t)) => (Some(t.as_str()), Some("")),
+            Some(Value::Object(e)) => (
+                e.get("type").and_then(Value::as_str),
+                e.get("message").and_then(Value::as_str),
+            ),
+            _ => (None, None),
+        };
+
+        match (error_type, message) {
+            (Some(error_type), Some(message)) => AirtableStoreError::Api {
+                status,
+                error_type: error_type.to_owned(),
+                message: message.to_owned(),
+            },
+            _ => AirtableStoreError::HttpError(status, val),
         }
     }
 
@@ -97,24 +176,35 @@ impl AirtableStore {
         object_key: &str,
         query: HashMap<String, String>,
     ) -> impl Stream<Item = Result<(String, Value), AirtableStoreError>> {
+        self.get_paginated_from(url, object_key, query, None)
+            .map_ok(|(_, id, value)| (id, value))
+    }
+
+    fn get_paginated_from(
+        &self,
+        url: &str,
+        object_key: &str,
+        query: HashMap<String, String>,
+        cursor: Option<AirtableListCursor>,
+    ) -> impl Stream<Item = Result<(AirtableListCursor, String, Value), AirtableStoreError>> {
         let this = self.clone();
         let object_key = object_key.to_owned();
         let url = url.to_owned();
         // let query = query.clone();
 
-        stream::try_unfold(Some("".to_owned()), move |next_offset| {
+        stream::try_unfold(Some(cursor.unwrap_or_default()), move |next_cursor| {
             let this = this.clone();
             let object_key = object_key.clone();
             let url = url.clone();
             let query = query.clone();
 
             async move {
-                let Some(next_offset) = next_offset else {
+                let Some(AirtableListCursor { offset, skip }) = next_cursor else {
                     return Ok(None);
                 };
 
                 let mut paged_q = query.clone();
-                paged_q.insert("offset".to_owned(), next_offset);
+                paged_q.insert("offset".to_owned(), offset.clone());
 
                 let resp = this.request(Method::GET, &url, paged_q, None).await?;
 
@@ -126,12 +216,27 @@ impl AirtableStore {
                     .iter()
                     .map(|v| Some((v.get("id")?.as_str()?.to_owned(), v.clone())))
                     .collect::<Option<Vec<_>>>()
-                    .ok_or("Api conversion problem")?;
+                    .ok_or("Api conversion problem")?
+                    .into_iter()
+                    .enumerate()
+                    .skip(skip)
+                    .map(|(ix, (id, v))| {
+                        let cursor = AirtableListCursor {
+                            offset: offset.clone(),
+                            skip: ix + 1,
+                        };
+                        (cursor, id, v)
+                    })
+                    .collect::<Vec<_>>();
 
                 Ok::<_, AirtableStoreError>(Some((
                     bases,
                     resp.get("offset")
-                        .and_then(|v| v.as_str().map(|s| s.to_owned())),
+                        .and_then(|v| v.as_str())
+                        .map(|offset| AirtableListCursor {
+                            offset: offset.to_owned(),
+                            skip: 0,
+                        }),
                 )))
             }
         })
@@ -140,12 +245,34 @@ impl AirtableStore {
     }
 }
 
+/// A position in an Airtable listing: the page `offset` token and the number of items
+/// already consumed from that page. Serialize it to resume a listing later.
+///
+/// Note that Airtable expires offsets after a while.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AirtableListCursor {
+    offset: String,
+    skip: usize,
+}
+
 impl Store for AirtableStore {
     type Error = AirtableStoreError;
 
-    type RootAddress = crate::address::primitive::UniqueRootAddress;
+    type RootAddress = UniqueRootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            can_write: true,
+            can_list: true,
+            can_delete: true,
+            can_query: true,
+            can_watch: false,
+        }
+    }
 }
 
+impl Addressable<UniqueRootAddress> for AirtableStore {}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct AirtableBasesRootAddr;
 
@@ -175,6 +302,8 @@ impl<'a> AddressableList<'a, AirtableBasesRootAddr> for AirtableStore {
 
     type ItemAddress = AirtableBase;
 
+    type ListOfAddressesStream = BoxStream<'a, Result<(AirtableBase, AirtableBase), Self::Error>>;
+
     fn list(&self, _addr: &AirtableBasesRootAddr) -> Self::ListOfAddressesStream {
         self.get_paginated(
             "https://api.airtable.com/v0/meta/bases",
@@ -193,6 +322,32 @@ impl<'a> AddressableList<'a, AirtableBasesRootAddr> for AirtableStore {
     }
 }
 
+impl<'a> AddressableListFrom<'a, AirtableBasesRootAddr> for AirtableStore {
+    type ListCursor = AirtableListCursor;
+
+    fn list_from(
+        &self,
+        _addr: &AirtableBasesRootAddr,
+        cursor: Option<AirtableListCursor>,
+    ) -> Self::ListFromStream {
+        self.get_paginated_from(
+            "https://api.airtable.com/v0/meta/bases",
+            "bases",
+            Default::default(),
+            cursor,
+        )
+        .map(|v| {
+            let (cursor, id, value) = v?;
+            let b = AirtableBase {
+                id,
+                meta: serde_json::from_value(value)?,
+            };
+            Ok((cursor, b.clone(), b))
+        })
+        .boxed()
+    }
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct AirtableBase {
     pub id: String,
@@ -213,6 +368,15 @@ impl Address for AirtableBase {
         self.id.to_string()
     }
 
+    /// The name of the base, if it came from a listing.
+    fn display_name(&self) -> String {
+        self.meta
+            .as_ref()
+            .and_then(|m| m["name"].as_str())
+            .map(|n| n.to_owned())
+            .unwrap_or_else(|| self.own_name())
+    }
+
     fn as_parts(&self) -> Vec<String> {
         vec![self.own_name()]
     }
@@ -241,6 +405,9 @@ impl<'a> AddressableList<'a, AirtableBase> for AirtableStore {
 
     type ItemAddress = AirtableTable<Value>;
 
+    type ListOfAddressesStream =
+        BoxStream<'a, Result<(AirtableTable<Value>, AirtableTable<Value>), Self::Error>>;
+
     fn list(&self, addr: &AirtableBase) -> Self::ListOfAddressesStream {
         let addr = addr.clone();
 
@@ -255,10 +422,83 @@ impl<'a> AddressableList<'a, AirtableBase> for AirtableStore {
                 id,
                 base: Some(addr.clone()),
                 meta: serde_json::from_value(value)?,
+                use_field_ids: false,
                 phantom: PhantomData,
             };
             Ok((b.clone(), b))
         })
+        .boxed()
+    }
+}
+
+/// The whole base as JSON: `{"Table name": [{record fields}, ...], ...}`.
+///
+/// Tables and records are fetched page by page, through the ratelimiter,
+/// so only the result is kept in memory.
+impl AddressableGet<Value, AirtableBase> for AirtableStore {
+    async fn addr_get(&self, addr: &AirtableBase) -> StoreResult<Option<Value>, Self> {
+        let mut tables = self.list(addr);
+        let mut result = serde_json::Map::new();
+
+        while let Some(table) = tables.next().await {
+            let table = match table {
+                Ok((table, _)) => table,
+                Err(e) => {
+                    return match e.status() {
+                        Some(http) if http.as_u16() == 404 || http.as_u16() == 403 => Ok(None),
+                        _ => Err(e),
+                    }
+                }
+            };
+
+            let name = table
+                .meta
+                .as_ref()
+                .and_then(|m| m.get("name"))
+                .and_then(Value::as_str)
+                .unwrap_or(&table.id)
+                .to_owned();
+
+            let records = self
+                .records_from(&table, FilterByFormula("".to_owned()), None)
+                .map_ok(|(_, r)| r.value.unwrap_or(Value::Null))
+                .try_collect::<Vec<_>>()
+                .await?;
+
+            result.insert(name, Value::Array(records));
+        }
+
+        Ok(Some(Value::Object(result)))
+    }
+}
+
+impl<'a> AddressableListFrom<'a, AirtableBase> for AirtableStore {
+    type ListCursor = AirtableListCursor;
+
+    fn list_from(
+        &self,
+        addr: &AirtableBase,
+        cursor: Option<AirtableListCursor>,
+    ) -> Self::ListFromStream {
+        let addr = addr.clone();
+
+        self.get_paginated_from(
+            &format!("https://api.airtable.com/v0/meta/bases/{}/tables", addr.id),
+            "tables",
+            Default::default(),
+            cursor,
+        )
+        .map(move |v| {
+            let (cursor, id, value) = v?;
+            let b = AirtableTable {
+                id,
+                base: Some(addr.clone()),
+                meta: serde_json::from_value(value)?,
+                use_field_ids: false,
+                phantom: PhantomData,
+            };
+            Ok((cursor, b.clone(), b))
+        })
         .boxed_local()
     }
 }
@@ -267,6 +507,12 @@ pub struct AirtableTable<V> {
     pub id: String,
     pub base: Option<AirtableBase>,
     pub meta: Option<Value>,
+    /// Key the record fields by field ids instead of field names, both when reading
+    /// (`returnFieldsByFieldId`) and writing. Ids survive renaming the fields in Airtable,
+    /// but they are opaque (`fldXXXXXXXXXXXXXX`), so `V` has to use them as keys.
+    ///
+    /// Off by default.
+    pub use_field_ids: bool,
     phantom: PhantomData<V>,
 }
 
@@ -276,9 +522,36 @@ impl<V> AirtableTable<V> {
             id: id_or_name.to_owned(),
             base: None,
             meta: None,
+            use_field_ids: false,
             phantom: PhantomData,
         }
     }
+
+    /// See [`use_field_ids`](AirtableTable::use_field_ids).
+    pub fn with_field_ids(mut self) -> Self {
+        self.use_field_ids = true;
+        self
+    }
+
+    /// Query parameters for the requests that return records.
+    fn records_query(&self) -> HashMap<String, String> {
+        let mut query = HashMap::new();
+
+        if self.use_field_ids {
+            query.insert("returnFieldsByFieldId".to_owned(), "true".to_owned());
+        }
+
+        query
+    }
+
+    /// Adds the options to the body of the requests that write records.
+    fn records_body(&self, mut body: Value) -> Value {
+        if self.use_field_ids {
+            body["returnFieldsByFieldId"] = Value::Bool(true);
+        }
+
+        body
+    }
 }
 
 impl<V> Clone for AirtableTable<V> {
@@ -287,6 +560,7 @@ impl<V> Clone for AirtableTable<V> {
             id: self.id.to_owned(),
             base: self.base.clone(),
             meta: self.meta.clone(),
+            use_field_ids: self.use_field_ids,
             phantom: PhantomData,
         }
     }
@@ -304,6 +578,7 @@ impl<V> Debug for AirtableTable<V> {
             .field("id", &self.id)
             .field("base", &self.base)
             .field("meta", &self.meta)
+            .field("use_field_ids", &self.use_field_ids)
             .finish()
     }
 }
@@ -331,6 +606,25 @@ pub struct AirtableRecord<V: Serialize + DeserializeOwned> {
     pub id: String,
     pub table: AirtableTable<V>,
     pub value: Option<V>,
+    /// `createdTime` of the record, if it came from a listing or an insert.
+    pub created_time: Option<String>,
+}
+
+/// Metadata of a record: `store.sub(record).get::<RecordMeta>()`.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct RecordMeta {
+    pub id: String,
+    /// As Airtable returns it, e.g. `2023-01-01T00:00:00.000Z`.
+    pub created_time: String,
+}
+
+impl RecordMeta {
+    fn from_response(record: &Value) -> Option<Self> {
+        Some(RecordMeta {
+            id: record.get("id")?.as_str()?.to_owned(),
+            created_time: record.get("createdTime")?.as_str()?.to_owned(),
+        })
+    }
 }
 
 impl<V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq> SubAddress<AirtableRecord<V>>
@@ -345,16 +639,8 @@ impl<V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq> SubAddress<
     }
 }
 
-impl<V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq> Address for AirtableRecord<V> {
-    fn own_name(&self) -> String {
-        self.id.to_owned()
-    }
-
-    fn as_parts(&self) -> Vec<String> {
-        let mut v = self.table.as_parts();
-        v.push(self.id.to_owned());
-        v
-    }
+crate::id_address! {
+    impl[V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq] AirtableRecord<V> { id in table }
 }
 impl<V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq> Addressable<AirtableRecord<V>>
     for AirtableStore
@@ -388,6 +674,40 @@ impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>
         addr: &AirtableTable<V>,
         query: FilterByFormula,
     ) -> Self::ListOfAddressesStream {
+        self.records_from(addr, query, None)
+            .map_ok(|(_, r)| (r.clone(), r))
+            .boxed()
+    }
+}
+
+impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>
+    AddressableListFrom<'a, AirtableTable<V>> for AirtableStore
+{
+    type ListCursor = AirtableListCursor;
+
+    type ListFromStream = BoxStream<
+        'a,
+        Result<(AirtableListCursor, AirtableRecord<V>, AirtableRecord<V>), Self::Error>,
+    >;
+
+    fn list_from(
+        &self,
+        addr: &AirtableTable<V>,
+        cursor: Option<AirtableListCursor>,
+    ) -> Self::ListFromStream {
+        self.records_from(addr, FilterByFormula("".to_owned()), cursor)
+            .map_ok(|(cursor, r)| (cursor, r.clone(), r))
+            .boxed()
+    }
+}
+
+impl AirtableStore {
+    fn records_from<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>(
+        &self,
+        addr: &AirtableTable<V>,
+        query: FilterByFormula,
+        cursor: Option<AirtableListCursor>,
+    ) -> BoxStream<'a, Result<(AirtableListCursor, AirtableRecord<V>), AirtableStoreError>> {
         let addr = addr.clone();
         let this = self.clone();
 
@@ -395,8 +715,11 @@ impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>
             let addr = addr.clone();
             let addr2 = addr.clone();
 
+            let mut records_query = addr.records_query();
+            records_query.insert("filterByFormula".to_owned(), query.0);
+
             let s = this
-                .get_paginated(
+                .get_paginated_from(
                     &format!(
                         "https://api.airtable.com/v0/{}/{}",
                         addr.base
@@ -407,16 +730,18 @@ impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>
                         addr.id
                     ),
                     "records",
-                    HashMap::from_iter([("filterByFormula".to_owned(), query.0)]),
+                    records_query,
+                    cursor,
                 )
                 .map(move |v| {
-                    let (id, value) = v?;
+                    let (cursor, id, value) = v?;
                     let b = AirtableRecord {
                         id,
                         table: addr2.clone(),
                         value: serde_json::from_value(value["fields"].clone())?,
+                        created_time: value["createdTime"].as_str().map(str::to_owned),
                     };
-                    Ok((b.clone(), b))
+                    Ok((cursor, b))
                 });
 
             Ok::<_, AirtableStoreError>(s)
@@ -450,7 +775,7 @@ impl<
                     addr.table.id,
                     addr.id
                 ),
-                Default::default(),
+                addr.table.records_query(),
                 None,
             )
             .await;
@@ -460,13 +785,181 @@ impl<
 
                 Ok(Some(rec))
             }
-            Err(AirtableStoreError::HttpError(http, _))
-                if http.as_u16() == 404 || http.as_u16() == 403 =>
-            {
-                Ok(None)
+            Err(e) => match e.status() {
+                Some(http) if http.as_u16() == 404 || http.as_u16() == 403 => Ok(None),
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+impl<Any: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq>
+    AddressableGet<RecordMeta, AirtableRecord<Any>> for AirtableStore
+{
+    async fn addr_get(&self, addr: &AirtableRecord<Any>) -> StoreResult<Option<RecordMeta>, Self> {
+        let resp = self
+            .request(
+                Method::GET,
+                &format!(
+                    "https://api.airtable.com/v0/{}/{}/{}",
+                    addr.table
+                        .base
+                        .as_ref()
+                        .ok_or(AirtableStoreError::Custom(
+                            "Table address contains no base address".to_owned()
+                        ))?
+                        .id,
+                    addr.table.id,
+                    addr.id
+                ),
+                Default::default(),
+                None,
+            )
+            .await;
+
+        match resp {
+            Ok(val) => Ok(Some(RecordMeta::from_response(&val).ok_or_else(|| {
+                AirtableStoreError::Custom(format!(
+                    "Airtable record has no id or createdTime: {val}"
+                ))
+            })?)),
+            Err(e) => match e.status() {
+                Some(http) if http.as_u16() == 404 || http.as_u16() == 403 => Ok(None),
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+impl<Any: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq>
+    AddressableGet<Existence, AirtableRecord<Any>> for AirtableStore
+{
+    async fn addr_get(&self, addr: &AirtableRecord<Any>) -> StoreResult<Option<Existence>, Self> {
+        let meta = AddressableGet::<RecordMeta, _>::addr_get(self, addr).await?;
+
+        Ok(meta.map(|_| Existence))
+    }
+}
+
+/// How many records are looked up by one `filterByFormula` query in `exists_many`.
+const EXISTS_MANY_CHUNK: usize = 50;
+
+impl AirtableStore {
+    /// How many records of the table match `filter` (an empty formula matches all of them),
+    /// counting at most `max_records` (`maxRecords`).
+    ///
+    /// Airtable has no count endpoint, so this still lists the records, a page of 100 per request
+    /// (and per the rate limit). Only `field` of each record is fetched: pass a small one,
+    /// e.g. a short primary field, so the records don't come whole.
+    pub async fn count_query<V>(
+        &self,
+        table: &AirtableTable<V>,
+        filter: FilterByFormula,
+        field: &str,
+        max_records: Option<usize>,
+    ) -> StoreResult<usize, Self> {
+        let (url, query) = count_query_request(table, filter, field, max_records)?;
+
+        self.get_paginated(&url, "records", query)
+            .try_fold(0, |count, _| async move { Ok(count + 1) })
+            .await
+    }
+}
+
+/// The url and the query of the listing behind [`AirtableStore::count_query`].
+fn count_query_request<V>(
+    table: &AirtableTable<V>,
+    filter: FilterByFormula,
+    field: &str,
+    max_records: Option<usize>,
+) -> Result<(String, HashMap<String, String>), AirtableStoreError> {
+    let url = format!(
+        "https://api.airtable.com/v0/{}/{}",
+        table
+            .base
+            .as_ref()
+            .ok_or(AirtableStoreError::Custom(
+                "Table address contains no base address".to_owned()
+            ))?
+            .id,
+        table.id
+    );
+
+    let mut query = HashMap::from([
+        ("filterByFormula".to_owned(), filter.0),
+        ("fields[]".to_owned(), field.to_owned()),
+        ("pageSize".to_owned(), "100".to_owned()),
+    ]);
+    if let Some(max_records) = max_records {
+        query.insert("maxRecords".to_owned(), max_records.to_string());
+    }
+
+    Ok((url, query))
+}
+
+/// `OR(RECORD_ID()='rec1',...)`, if all the ids are safe to put into a formula.
+fn record_ids_formula<'i>(ids: impl IntoIterator<Item = &'i str>) -> Option<String> {
+    let conditions = ids
+        .into_iter()
+        .map(|id| {
+            id.chars()
+                .all(|c| c.is_ascii_alphanumeric())
+                .then(|| format!("RECORD_ID()='{id}'"))
+        })
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(format!("OR({})", conditions.join(",")))
+}
+
+/// The records of each table are checked with a `filterByFormula` query over their
+/// `RECORD_ID()`s, instead of a request per record.
+impl<Any: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq>
+    AddressableExistsMany<AirtableRecord<Any>> for AirtableStore
+{
+    async fn exists_many(&self, addrs: &[AirtableRecord<Any>]) -> StoreResult<Vec<bool>, Self> {
+        let mut result = vec![false; addrs.len()];
+        let mut by_table: HashMap<(String, String), Vec<usize>> = HashMap::new();
+
+        for (ix, addr) in addrs.iter().enumerate() {
+            match &addr.table.base {
+                Some(base) if record_ids_formula([addr.id.as_str()]).is_some() => by_table
+                    .entry((base.id.to_owned(), addr.table.id.to_owned()))
+                    .or_default()
+                    .push(ix),
+                _ => {
+                    result[ix] = AddressableGet::<Existence, _>::addr_get(self, addr)
+                        .await?
+                        .is_some()
+                }
             }
-            Err(e) => Err(e),
         }
+
+        for indices in by_table.values() {
+            for chunk in indices.chunks(EXISTS_MANY_CHUNK) {
+                let first = &addrs[chunk[0]].table;
+                let table = AirtableTable::<Value> {
+                    id: first.id.to_owned(),
+                    base: first.base.clone(),
+                    meta: None,
+                    use_field_ids: false,
+                    phantom: PhantomData,
+                };
+                let formula = record_ids_formula(chunk.iter().map(|ix| addrs[*ix].id.as_str()))
+                    .expect("the ids were checked");
+
+                let found = self
+                    .records_from(&table, FilterByFormula(formula), None)
+                    .map_ok(|(_, r)| r.id)
+                    .try_collect::<HashSet<_>>()
+                    .await?;
+
+                for ix in chunk {
+                    result[*ix] = found.contains(&addrs[*ix].id);
+                }
+            }
+        }
+
+        Ok(result)
     }
 }
 
@@ -495,7 +988,7 @@ impl<
 
         match value {
             Some(value) => {
-                let body = json!({ "fields": value });
+                let body = addr.table.records_body(json!({ "fields": value }));
 
                 let _resp = self
                     .request(Method::PATCH, record_url, Default::default(), Some(body))
@@ -533,7 +1026,7 @@ impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>
                         })
                         .collect::<Result<Vec<_>, AirtableStoreError>>()?;
 
-                    let data = json!({ "records": records });
+                    let data = addr.records_body(json!({ "records": records }));
 
                     let url = format!(
                         "https://api.airtable.com/v0/{}/{}",
@@ -569,6 +1062,7 @@ impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>
                                     .to_owned(),
                                 table: addr.clone(),
                                 value: Some(serde_json::from_value::<V>(v["fields"].clone())?),
+                                created_time: v["createdTime"].as_str().map(str::to_owned),
                             })
                         })
                         .collect::<Vec<_>>();
@@ -582,18 +1076,411 @@ impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send>
     }
 }
 
+/// A single field of a record: `store.sub(record).sub("Name".to_owned())`, or listed from
+/// the record. The fields are read and written as `Value`s, in the table's keys
+/// (names, or ids with [`use_field_ids`](AirtableTable::use_field_ids)).
+///
+/// Writing a field is a `PATCH` of just that field, the other fields are kept.
+/// Writing `None` clears it.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct AirtableFieldAddr<V: Serialize + DeserializeOwned> {
+    pub record: AirtableRecord<V>,
+    pub field_name: String,
+}
+
+impl<V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq> Address
+    for AirtableFieldAddr<V>
+{
+    fn own_name(&self) -> String {
+        self.field_name.to_owned()
+    }
+
+    fn as_parts(&self) -> Vec<String> {
+        let mut v = self.record.as_parts();
+        v.push(self.field_name.to_owned());
+        v
+    }
+}
+
+impl<V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq> SubAddress<String>
+    for AirtableRecord<V>
+{
+    type Output = AirtableFieldAddr<V>;
+
+    fn sub(self, rhs: String) -> Self::Output {
+        AirtableFieldAddr {
+            record: self,
+            field_name: rhs,
+        }
+    }
+}
+
+impl<V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq>
+    Addressable<AirtableFieldAddr<V>> for AirtableStore
+{
+    type DefaultValue = Value;
+}
+
+impl AirtableStore {
+    /// The fields of the record as Airtable returns them, `None` if there's no such record.
+    async fn record_fields<Any: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq>(
+        &self,
+        addr: &AirtableRecord<Any>,
+    ) -> StoreResult<Option<serde_json::Map<String, Value>>, Self> {
+        let fields = AddressableGet::<Value, _>::addr_get(self, addr).await?;
+
+        match fields {
+            None => Ok(None),
+            Some(Value::Object(fields)) => Ok(Some(fields)),
+            Some(v) => Err(AirtableStoreError::Custom(format!(
+                "Airtable record fields are not an object: {v}"
+            ))),
+        }
+    }
+}
+
+/// The names of the fields of the record. Airtable doesn't return the empty fields,
+/// so they aren't listed.
+impl<'a, V: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq + Send + Sync>
+    AddressableList<'a, AirtableRecord<V>> for AirtableStore
+{
+    type AddedAddress = String;
+
+    type ItemAddress = AirtableFieldAddr<V>;
+
+    type ListOfAddressesStream = BoxStream<'a, Result<(String, AirtableFieldAddr<V>), Self::Error>>;
+
+    fn list(&self, addr: &AirtableRecord<V>) -> Self::ListOfAddressesStream {
+        let addr = addr.clone();
+        let this = self.clone();
+
+        stream::once(async move {
+            let names = this
+                .record_fields(&addr)
+                .await?
+                .map(|fields| fields.keys().cloned().collect::<Vec<_>>())
+                .unwrap_or_default();
+
+            Ok::<_, AirtableStoreError>(stream::iter(
+                names
+                    .into_iter()
+                    .map(move |name| Ok((name.clone(), addr.clone().sub(name)))),
+            ))
+        })
+        .try_flatten()
+        .boxed()
+    }
+}
+
+impl<Any: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq>
+    AddressableGet<Value, AirtableFieldAddr<Any>> for AirtableStore
+{
+    async fn addr_get(&self, addr: &AirtableFieldAddr<Any>) -> StoreResult<Option<Value>, Self> {
+        let fields = self.record_fields(&addr.record).await?;
+
+        Ok(fields.and_then(|mut fields| fields.remove(&addr.field_name)))
+    }
+}
+
+impl<Any: 'static + Serialize + DeserializeOwned + Clone + Debug + Eq>
+    AddressableSet<Value, AirtableFieldAddr<Any>> for AirtableStore
+{
+    async fn set_addr(
+        &self,
+        addr: &AirtableFieldAddr<Any>,
+        value: &Option<Value>,
+    ) -> StoreResult<(), Self> {
+        let patch = json!({ addr.field_name.as_str(): value });
+
+        AddressableSet::<Value, _>::set_addr(self, &addr.record, &Some(patch)).await
+    }
+}
+
 #[cfg(test)]
 mod test_airtable {
-    use std::collections::HashMap;
+    use std::{collections::HashMap, time::Duration};
 
     use crate::{
         store::StoreEx,
         stores::cloud::airtable::{
-            AirtableBase, AirtableBasesRootAddr, AirtableStore, AirtableTable, FilterByFormula,
+            count_query_request, record_ids_formula, AirtableBase, AirtableBasesRootAddr,
+            AirtableFieldAddr, AirtableListCursor, AirtableRecord, AirtableStore,
+            AirtableStoreError, AirtableTable, FilterByFormula, RecordMeta,
         },
     };
     use futures::{StreamExt, TryStreamExt};
-    use serde_json::Value;
+    use reqwest::Method;
+    use serde_json::{json, Value};
+
+    static_assertions::assert_impl_all!(
+        <AirtableStore as crate::address::traits::AddressableList<'static, AirtableBasesRootAddr>>::ListOfAddressesStream: Send
+    );
+    static_assertions::assert_impl_all!(
+        <AirtableStore as crate::address::traits::AddressableList<'static, AirtableBase>>::ListOfAddressesStream: Send
+    );
+    static_assertions::assert_impl_all!(
+        <AirtableStore as crate::address::traits::AddressableList<'static, AirtableTable<Value>>>::ListOfAddressesStream: Send
+    );
+    static_assertions::assert_impl_all!(
+        <AirtableStore as crate::address::traits::AddressableList<'static, AirtableRecord<Value>>>::ListOfAddressesStream: Send
+    );
+
+    #[test]
+    fn test_base_display_name() {
+        use crate::address::Address;
+
+        let mut base = AirtableBase::by_id("app123");
+        assert_eq!(base.display_name(), "app123");
+
+        base.meta = Some(json!({"id": "app123", "name": "Inventory"}));
+        assert_eq!(base.own_name(), "app123");
+        assert_eq!(base.display_name(), "Inventory");
+    }
+
+    #[tokio::test]
+    pub async fn test_api_error() -> Result<(), Box<dyn std::error::Error>> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/", listener.local_addr()?);
+        std::thread::spawn(move || {
+            use std::io::{Read, Write};
+
+            let body = r#"{"error": {"type": "INVALID_REQUEST", "message": "Bad field"}}"#;
+
+            for mut conn in listener.incoming().flatten() {
+                let _ = conn.read(&mut [0; 4096]);
+                let _ = write!(
+                    conn,
+                    "HTTP/1.1 422 Unprocessable Entity\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        let store = AirtableStore::new("token")?;
+
+        let res = store
+            .request(Method::GET, &url, Default::default(), None)
+            .await;
+
+        match res {
+            Err(AirtableStoreError::Api {
+                status,
+                error_type,
+                message,
+            }) => {
+                assert_eq!(status.as_u16(), 422);
+                assert_eq!(error_type, "INVALID_REQUEST");
+                assert_eq!(message, "Bad field");
+            }
+            res => panic!("unexpected: {res:?}"),
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    pub async fn test_list_cursor() -> Result<(), Box<dyn std::error::Error>> {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/", listener.local_addr()?);
+        std::thread::spawn(move || {
+            use std::io::{Read, Write};
+
+            for mut conn in listener.incoming().flatten() {
+                let mut buf = [0; 4096];
+                let n = conn.read(&mut buf).unwrap_or(0);
+                let req = String::from_utf8_lossy(&buf[..n]);
+
+                let body = if req.contains("offset=p2") {
+                    r#"{"records": [{"id": "c"}]}"#
+                } else {
+                    r#"{"records": [{"id": "a"}, {"id": "b"}], "offset": "p2"}"#
+                };
+                let _ = write!(
+                    conn,
+                    "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                );
+            }
+        });
+
+        let store = AirtableStore::new("token")?;
+
+        let all = store
+            .get_paginated_from(&url, "records", Default::default(), None)
+            .try_collect::<Vec<_>>()
+            .await?;
+        let ids = all.iter().map(|(_, id, _)| id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["a", "b", "c"]);
+
+        for (skip, (cursor, _, _)) in all.iter().enumerate() {
+            let cursor: AirtableListCursor = serde_json::from_str(&serde_json::to_string(cursor)?)?;
+
+            let rest = store
+                .get_paginated_from(&url, "records", Default::default(), Some(cursor))
+                .map_ok(|(_, id, _)| id)
+                .try_collect::<Vec<_>>()
+                .await?;
+            assert_eq!(rest, ids[skip + 1..]);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    pub fn test_record_ids_formula() {
+        assert_eq!(
+            record_ids_formula(["rec1", "rec2"]),
+            Some("OR(RECORD_ID()='rec1',RECORD_ID()='rec2')".to_owned())
+        );
+        assert_eq!(record_ids_formula(["rec1", "x') & ('"]), None);
+    }
+
+    #[test]
+    pub fn test_record_meta() {
+        let record = json!({"id": "rec1", "createdTime": "2023-01-01T00:00:00.000Z", "fields": {}});
+        assert_eq!(
+            RecordMeta::from_response(&record),
+            Some(RecordMeta {
+                id: "rec1".to_owned(),
+                created_time: "2023-01-01T00:00:00.000Z".to_owned(),
+            })
+        );
+
+        assert_eq!(RecordMeta::from_response(&json!({"id": "rec1"})), None);
+    }
+
+    #[test]
+    pub fn test_field_ids() {
+        let by_name = AirtableTable::<Value>::by_id_or_name("Entries");
+        assert!(by_name.records_query().is_empty());
+        assert_eq!(
+            by_name.records_body(json!({"fields": {}})),
+            json!({"fields": {}})
+        );
+
+        let by_id = by_name.with_field_ids();
+        assert_eq!(
+            by_id.records_query().get("returnFieldsByFieldId"),
+            Some(&"true".to_owned())
+        );
+        assert_eq!(
+            by_id.records_body(json!({"records": []})),
+            json!({"records": [], "returnFieldsByFieldId": true})
+        );
+    }
+
+    #[test]
+    pub fn test_field_addr() {
+        use crate::address::{Address, SubAddress};
+
+        let mut table = AirtableTable::<Value>::by_id_or_name("Entries");
+        table.base = Some(AirtableBase::by_id("app123"));
+        let record = AirtableRecord {
+            id: "rec1".to_owned(),
+            table,
+            value: None,
+            created_time: None,
+        };
+
+        let field: AirtableFieldAddr<Value> = record.clone().sub("Name".to_owned());
+        assert_eq!(field.record, record);
+        assert_eq!(field.own_name(), "Name");
+        assert_eq!(field.as_parts(), vec!["app123", "Entries", "rec1", "Name"]);
+    }
+
+    #[tokio::test]
+    pub async fn test_timeout() -> Result<(), Box<dyn std::error::Error>> {
+        // accepts connections, but never responds
+        let listener = std::net::TcpListener::bind("127.0.0.1:0")?;
+        let url = format!("http://{}/", listener.local_addr()?);
+        std::thread::spawn(move || {
+            let _connections = listener.incoming().collect::<Vec<_>>();
+        });
+
+        let store = AirtableStore::new_with_timeout("token", Duration::from_millis(100))?;
+
+        let res = store
+            .request(Method::GET, &url, Default::default(), None)
+            .await;
+
+        assert!(matches!(res, Err(AirtableStoreError::Timeout(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_count_query_request() {
+        let mut table = AirtableTable::<Value>::by_id_or_name("tblEntries");
+
+        assert!(
+            count_query_request(&table, FilterByFormula("".to_owned()), "title", None).is_err()
+        );
+
+        table.base = Some(AirtableBase::by_id("app123"));
+
+        let (url, query) = count_query_request(
+            &table,
+            FilterByFormula("{done}".to_owned()),
+            "title",
+            Some(5),
+        )
+        .unwrap();
+        assert_eq!(url, "https://api.airtable.com/v0/app123/tblEntries");
+        assert_eq!(
+            query,
+            HashMap::from(
+                [
+                    ("filterByFormula", "{done}"),
+                    ("fields[]", "title"),
+                    ("pageSize", "100"),
+                    ("maxRecords", "5")
+                ]
+                .map(|(k, v)| (k.to_owned(), v.to_owned()))
+            )
+        );
+
+        let (_, query) =
+            count_query_request(&table, FilterByFormula("".to_owned()), "title", None).unwrap();
+        assert!(!query.contains_key("maxRecords"));
+    }
+
+    /// Needs `AIRTABLE_API_KEY` with access to the base.
+    #[tokio::test]
+    #[ignore]
+    pub async fn test_count_query() -> Result<(), Box<dyn std::error::Error>> {
+        let store =
+            AirtableStore::new(&std::env::var("AIRTABLE_API_KEY").expect("AIRTABLE_API_KEY"))?;
+
+        let mut table = AirtableTable::<Value>::by_id_or_name("Entries");
+        table.base = Some(AirtableBase::by_id("app46Mmalo62fN5Vq"));
+
+        let all = store
+            .count_query(&table, FilterByFormula("".to_owned()), "title", None)
+            .await?;
+        let listed = store
+            .sub(table.clone())
+            .list()
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(all, listed.len());
+
+        let matching = store
+            .count_query(
+                &table,
+                FilterByFormula("Find(\"RPC\", {title})".to_owned()),
+                "title",
+                None,
+            )
+            .await?;
+        assert!(matching <= all);
+
+        let capped = store
+            .count_query(&table, FilterByFormula("".to_owned()), "title", Some(1))
+            .await?;
+        assert_eq!(capped, all.min(1));
+
+        Ok(())
+    }
 
     #[tokio::test]
     #[ignore]
@@ -643,6 +1530,17 @@ mod test_airtable {
             }
         }
 
+        println!();
+        println!();
+        println!("Will snapshot a base...");
+
+        let snapshot = store
+            .sub(AirtableBase::by_id("app46Mmalo62fN5Vq"))
+            .get::<Value>()
+            .await?
+            .unwrap();
+        assert!(snapshot["Entries"].is_array());
+
         println!();
         println!();
         println!("Will query...");
@@ -702,6 +1600,21 @@ mod test_airtable {
 
         println!("v3: {obj3:?}");
         assert_eq!(obj3.unwrap()["c"], "test777");
+
+        // listed records keep `createdTime`, and the metadata can be read on its own
+        assert!(obj.created_time.is_some());
+        let meta = loc.clone().sub(obj.clone()).get::<RecordMeta>().await?;
+        assert_eq!(meta.as_ref().map(|m| &m.id), Some(&obj.id));
+        assert_eq!(meta.map(|m| m.created_time), obj.created_time);
+
+        let missing = AirtableRecord {
+            id: "recXXXXXXXXXXXXXX".to_owned(),
+            ..obj.clone()
+        };
+        assert_eq!(
+            loc.which_exist([missing, obj.clone()]).await?,
+            vec![obj.clone()]
+        );
         println!("1");
 
         loc.clone().sub(obj.clone()).setv(&None).await?;
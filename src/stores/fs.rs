@@ -4,20 +4,49 @@
 //     path: RelativePath,
 // }
 
-use std::{ffi::OsString, path::PathBuf, string::FromUtf8Error, sync::Arc};
+use std::{
+    ffi::OsString,
+    io::SeekFrom,
+    path::{Path, PathBuf},
+    pin::Pin,
+    str::FromStr,
+    string::FromUtf8Error,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{ready, Context, Poll},
+    time::SystemTime,
+};
 
 use derive_more::{Display, From};
-use futures::{stream, FutureExt, StreamExt, TryStreamExt};
+use futures::{
+    future::BoxFuture, stream, stream::BoxStream, stream::LocalBoxStream, FutureExt, StreamExt,
+    TryStreamExt,
+};
 use thiserror::Error;
-use tokio::fs::DirEntry;
+use tokio::{
+    fs::DirEntry,
+    io::{AsyncReadExt, AsyncSeekExt, AsyncWrite},
+};
 
+#[cfg(feature = "debounce")]
+use std::time::Duration;
+
+#[cfg(feature = "debounce")]
+use crate::wrappers::debounce::DebounceWrapperStore;
 use crate::{
     address::{
-        primitive::Existence,
-        traits::{AddressableGet, AddressableList, AddressableSet, AddressableTree, BranchOrLeaf},
-        Address, Addressable, PathAddress, SubAddress,
+        primitive::{Existence, Present},
+        traits::{
+            AddressableBranch, AddressableCas, AddressableChild, AddressableExistsMany,
+            AddressableGet, AddressableList, AddressableQuery, AddressableReadStream,
+            AddressableSet, AddressableSwap, AddressableTree, AddressableWriteStream, BranchOrLeaf,
+        },
+        Address, Addressable, PathAddress, ResolveAddress, SubAddress,
     },
-    store::{Store, StoreResult},
+    store::{Capabilities, Store, StoreResult},
+    wrappers::{filter_addresses::AddedParts, remap::MappableKeys},
 };
 
 #[derive(Error, Display, Debug, From)]
@@ -28,9 +57,16 @@ pub enum FileStoreError {
 
     #[from(ignore)]
     UnsupportedFeature(String),
+
+    #[from(ignore)]
+    InvalidPath(String),
+
+    /// A file read as a number or a boolean doesn't contain one
+    #[from(ignore)]
+    ParseError(String),
 }
 
-#[derive(PartialEq, Eq, Debug, Clone, From)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone, From)]
 pub struct RelativePath(PathBuf);
 
 #[derive(PartialEq, Eq, Debug, Clone, From, Display)]
@@ -42,11 +78,48 @@ impl std::fmt::Display for RelativePath {
     }
 }
 
+/// Unchecked: absolute paths and `..` are accepted as is, and can escape the base directory.
+/// Use `str.parse::<RelativePath>()` for untrusted input.
 impl From<&str> for RelativePath {
     fn from(value: &str) -> Self {
         RelativePath(value.into())
     }
 }
+
+/// Validated construction: rejects absolute paths and `..` components.
+///
+/// (This is not `TryFrom<&str>`, as that's already implied by `From<&str>`.)
+impl std::str::FromStr for RelativePath {
+    type Err = FileStoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let path = RelativePath(PathBuf::from(s));
+        path.check_contained()?;
+
+        Ok(path)
+    }
+}
+
+impl RelativePath {
+    /// Fails on absolute paths and `..` components.
+    fn check_contained(&self) -> Result<(), FileStoreError> {
+        for c in self.0.components() {
+            match c {
+                std::path::Component::Normal(_) | std::path::Component::CurDir => {}
+                std::path::Component::ParentDir => {
+                    return Err(FileStoreError::InvalidPath(format!("`..` in path: {self}")))
+                }
+                std::path::Component::RootDir | std::path::Component::Prefix(_) => {
+                    return Err(FileStoreError::InvalidPath(format!(
+                        "Absolute path: {self}"
+                    )))
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
 impl From<String> for RelativePath {
     fn from(value: String) -> Self {
         RelativePath(value.into())
@@ -87,6 +160,33 @@ impl PathAddress for RelativePath {
     }
 }
 
+impl ResolveAddress for RelativePath {
+    type Error = FileStoreError;
+
+    fn resolve(mut self, relative: &str) -> Result<Self, Self::Error> {
+        for c in Path::new(relative).components() {
+            match c {
+                std::path::Component::CurDir => {}
+                std::path::Component::ParentDir => {
+                    if !self.0.pop() {
+                        return Err(FileStoreError::InvalidPath(format!(
+                            "`..` goes above the root: {relative}"
+                        )));
+                    }
+                }
+                std::path::Component::Normal(name) => self.0.push(name),
+                std::path::Component::RootDir | std::path::Component::Prefix(_) => {
+                    return Err(FileStoreError::InvalidPath(format!(
+                        "Absolute path: {relative}"
+                    )))
+                }
+            }
+        }
+
+        Ok(self)
+    }
+}
+
 impl Address for RelativePath {
     fn own_name(&self) -> String {
         self.0
@@ -102,7 +202,37 @@ impl Address for RelativePath {
     }
 
     fn as_parts(&self) -> Vec<String> {
-        todo!()
+        self.0
+            .components()
+            .filter(|c| matches!(c, std::path::Component::Normal(_)))
+            .map(|c| {
+                c.as_os_str()
+                    .to_str()
+                    .expect("Non-unicode is not supported")
+                    .to_owned()
+            })
+            .collect()
+    }
+}
+
+impl MappableKeys for RelativePath {
+    fn map_last_key(&self, f: &dyn Fn(&str) -> Option<String>) -> Option<Self> {
+        match self.0.file_name() {
+            Some(name) => Some(Self(
+                self.0
+                    .with_file_name(f(name.to_str().expect("Non-unicode is not supported"))?),
+            )),
+            None => Some(self.clone()),
+        }
+    }
+}
+
+impl AddedParts<RelativePath> for RelativePath {
+    fn added_parts(&self) -> Vec<RelativePath> {
+        self.as_parts()
+            .into_iter()
+            .map(RelativePath::from)
+            .collect()
     }
 }
 
@@ -114,18 +244,95 @@ impl SubAddress<RelativePath> for RelativePath {
     }
 }
 
+/// File system as a store. Addresses are paths relative to the base directory.
+///
+/// The root (`store.root()`) is the base directory itself: it reads as [`FileOrDir::Dir`],
+/// and writing `FileOrDir::Dir` there creates it.
 #[derive(Debug, Clone)]
 pub struct FileSystemStore {
     base_directory: Arc<PathBuf>,
+    #[cfg(feature = "debounce")]
+    buffer: Option<WriteBuffer>,
+}
+
+/// The pending writes of [`FileSystemStore::new_buffered`], over the same store without a buffer.
+///
+/// Shared by the [`rebase`](FileSystemStore::rebase)d stores, which add their `prefix`.
+#[cfg(feature = "debounce")]
+#[derive(Clone)]
+struct WriteBuffer {
+    store: DebounceWrapperStore<FileSystemStore, RelativePath, String>,
+    prefix: RelativePath,
+}
+
+#[cfg(feature = "debounce")]
+impl WriteBuffer {
+    fn addr(&self, addr: &RelativePath) -> RelativePath {
+        self.prefix.clone().sub(addr.clone())
+    }
+}
+
+#[cfg(feature = "debounce")]
+impl std::fmt::Debug for WriteBuffer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("WriteBuffer")
+    }
 }
 
 impl FileSystemStore {
     pub fn new(path: PathBuf) -> Self {
         FileSystemStore {
             base_directory: Arc::new(path),
+            #[cfg(feature = "debounce")]
+            buffer: None,
+        }
+    }
+
+    /// Like [`new`](FileSystemStore::new), but the text writes (`String`s, and the numbers and
+    /// booleans, which are written as text) are buffered: rapid writes to the same file,
+    /// e.g. of a [`LocatedJsonStore`](crate::stores::located::json::LocatedJsonStore) document,
+    /// become one write after nothing was written for `window`. Reading the text of a file
+    /// sees its pending write. See [`DebounceWrapperStore`], which this uses.
+    ///
+    /// A write returns before the file is written, so it's lost if the process exits or crashes
+    /// before it's flushed, and a failed flush is only reported by the next operation.
+    /// Call [`flush`](Store::flush) where the writes have to be on disk, e.g. before exiting.
+    /// The pending writes are also flushed when the last clone of the store is dropped.
+    ///
+    /// Only the text goes through the buffer. The other operations on a file (bytes, metadata,
+    /// existence, streaming) write its pending text first, so they see it, and it doesn't
+    /// overwrite them later. Listings see the files on disk, without the pending new ones.
+    /// [`rebase`](FileSystemStore::rebase)d stores share the buffer.
+    #[cfg(feature = "debounce")]
+    pub fn new_buffered(path: PathBuf, window: Duration) -> Self {
+        let unbuffered = Self::new(path.clone());
+
+        FileSystemStore {
+            buffer: Some(WriteBuffer {
+                store: DebounceWrapperStore::<_, RelativePath, String>::new(unbuffered, window),
+                prefix: crate::address::primitive::UniqueRootAddress.into(),
+            }),
+            ..Self::new(path)
         }
     }
 
+    /// Writes the pending buffered text of `addr`, before an operation that doesn't go through the buffer.
+    ///
+    /// Not an `async fn`: the buffer writes through this same store type, so the future is boxed,
+    /// and its type is spelled out for the compiler to see that the writes are `Send`
+    /// (the buffer's worker is spawned).
+    #[cfg_attr(not(feature = "debounce"), allow(unused_variables))]
+    fn flush_buffered<'s>(&'s self, addr: &RelativePath) -> BoxFuture<'s, StoreResult<(), Self>> {
+        #[cfg(feature = "debounce")]
+        if let Some(buffer) = &self.buffer {
+            let addr = buffer.addr(addr);
+
+            return async move { buffer.store.flush_addr(&addr).await }.boxed();
+        }
+
+        futures::future::ready(Ok(())).boxed()
+    }
+
     pub fn here() -> StoreResult<Self, Self> {
         Ok(Self::new(std::env::current_dir()?))
     }
@@ -133,14 +340,52 @@ impl FileSystemStore {
     pub fn get_complete_path(&self, addr: RelativePath) -> PathBuf {
         self.base_directory.join(addr.0)
     }
+
+    /// A store with `addr` as its base directory, e.g. `store.rebase("src".into())`
+    /// for a store of the `src` subdirectory.
+    ///
+    /// Fails with [`FileStoreError::InvalidPath`] if `addr` is absolute or has `..`, so the new store
+    /// stays under this one's base directory. The check is on the path only: symlinks aren't resolved.
+    pub fn rebase(&self, addr: RelativePath) -> StoreResult<FileSystemStore, Self> {
+        addr.check_contained()?;
+
+        Ok(FileSystemStore {
+            #[cfg(feature = "debounce")]
+            buffer: self.buffer.as_ref().map(|buffer| WriteBuffer {
+                store: buffer.store.clone(),
+                prefix: buffer.addr(&addr),
+            }),
+            ..Self::new(self.get_complete_path(addr))
+        })
+    }
 }
 
 impl Store for FileSystemStore {
     type Error = FileStoreError;
 
     type RootAddress = RelativePath;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            can_write: true,
+            can_list: true,
+            can_query: true,
+            ..Default::default()
+        }
+    }
+
+    /// Writes the pending writes of a [buffered](FileSystemStore::new_buffered) store now.
+    async fn flush(&self) -> StoreResult<(), Self> {
+        #[cfg(feature = "debounce")]
+        if let Some(buffer) = &self.buffer {
+            return buffer.store.flush().boxed_local().await;
+        }
+
+        Ok(())
+    }
 }
 
+/// The default value of a path: the contents of a file, or a marker for a directory.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum FileOrDir {
     File(String),
@@ -153,6 +398,16 @@ impl Addressable<RelativePath> for FileSystemStore {
 
 impl AddressableGet<String, RelativePath> for FileSystemStore {
     async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<String>, Self> {
+        #[cfg(feature = "debounce")]
+        if let Some(buffer) = &self.buffer {
+            // boxed: the buffer reads through this same impl
+            return buffer
+                .store
+                .addr_get(&buffer.addr(addr))
+                .boxed_local()
+                .await;
+        }
+
         match tokio::fs::read(self.get_complete_path(addr.clone())).await {
             Ok(fil) => Ok(Some(String::from_utf8(fil)?)),
             Err(e) => match e.kind() {
@@ -165,6 +420,13 @@ impl AddressableGet<String, RelativePath> for FileSystemStore {
 
 impl AddressableSet<String, RelativePath> for FileSystemStore {
     async fn set_addr(&self, addr: &RelativePath, value: &Option<String>) -> StoreResult<(), Self> {
+        #[cfg(feature = "debounce")]
+        if let (Some(buffer), Some(_)) = (&self.buffer, value) {
+            return buffer.store.set_addr(&buffer.addr(addr), value).await;
+        }
+
+        self.flush_buffered(addr).await?;
+
         let path = self.get_complete_path(addr.clone());
 
         // todo: create dirs?
@@ -176,8 +438,335 @@ impl AddressableSet<String, RelativePath> for FileSystemStore {
     }
 }
 
+impl FileSystemStore {
+    async fn get_parsed<T: FromStr>(&self, addr: &RelativePath) -> StoreResult<Option<T>, Self>
+    where
+        T::Err: std::fmt::Display,
+    {
+        let Some(contents) = AddressableGet::<String, _>::addr_get(self, addr).await? else {
+            return Ok(None);
+        };
+
+        contents
+            .trim()
+            .parse()
+            .map(Some)
+            .map_err(|e| FileStoreError::ParseError(format!("{addr}: {e}")))
+    }
+
+    async fn set_formatted<T: std::fmt::Display>(
+        &self,
+        addr: &RelativePath,
+        value: &Option<T>,
+    ) -> StoreResult<(), Self> {
+        let value = value.as_ref().map(|v| format!("{v}\n"));
+
+        AddressableSet::<String, _>::set_addr(self, addr, &value).await
+    }
+
+    /// Deletes the file at `addr`, if there's one.
+    async fn remove_file(&self, addr: &RelativePath) -> StoreResult<(), Self> {
+        self.flush_buffered(addr).await?;
+
+        match tokio::fs::remove_file(self.get_complete_path(addr.clone())).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+/// Reads a single-value file, like a PID file or a `/sys` entry. Surrounding whitespace is ignored.
+impl AddressableGet<i64, RelativePath> for FileSystemStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<i64>, Self> {
+        self.get_parsed(addr).await
+    }
+}
+
+/// Writes the number and a newline.
+impl AddressableSet<i64, RelativePath> for FileSystemStore {
+    async fn set_addr(&self, addr: &RelativePath, value: &Option<i64>) -> StoreResult<(), Self> {
+        self.set_formatted(addr, value).await
+    }
+}
+
+/// Reads a single-value file, like a PID file or a `/sys` entry. Surrounding whitespace is ignored.
+impl AddressableGet<f64, RelativePath> for FileSystemStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<f64>, Self> {
+        self.get_parsed(addr).await
+    }
+}
+
+/// Writes the number and a newline.
+impl AddressableSet<f64, RelativePath> for FileSystemStore {
+    async fn set_addr(&self, addr: &RelativePath, value: &Option<f64>) -> StoreResult<(), Self> {
+        self.set_formatted(addr, value).await
+    }
+}
+
+/// Reads `true` or `false`. Surrounding whitespace is ignored.
+impl AddressableGet<bool, RelativePath> for FileSystemStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<bool>, Self> {
+        self.get_parsed(addr).await
+    }
+}
+
+/// Writes `true` or `false` and a newline.
+impl AddressableSet<bool, RelativePath> for FileSystemStore {
+    async fn set_addr(&self, addr: &RelativePath, value: &Option<bool>) -> StoreResult<(), Self> {
+        self.set_formatted(addr, value).await
+    }
+}
+
+/// Not atomic: the file is read, compared and written with separate operations,
+/// so another process may change it in between. Swapping in `None` deletes the file.
+impl AddressableCas<String, RelativePath> for FileSystemStore {
+    async fn compare_and_swap(
+        &self,
+        addr: &RelativePath,
+        expected: &Option<String>,
+        new: &Option<String>,
+    ) -> StoreResult<bool, Self> {
+        let current = AddressableGet::<String, _>::addr_get(self, addr).await?;
+
+        if current != *expected {
+            return Ok(false);
+        }
+
+        match new {
+            None => self.remove_file(addr).await?,
+            Some(_) => AddressableSet::<String, _>::set_addr(self, addr, new).await?,
+        }
+        Ok(true)
+    }
+}
+
+/// Not atomic: the file is read and written with separate operations,
+/// so a change made by another process in between is lost. Swapping in `None` deletes the file.
+impl AddressableSwap<String, RelativePath> for FileSystemStore {
+    async fn swap(
+        &self,
+        addr: &RelativePath,
+        value: &Option<String>,
+    ) -> StoreResult<Option<String>, Self> {
+        let previous = AddressableGet::<String, _>::addr_get(self, addr).await?;
+
+        match value {
+            None => self.remove_file(addr).await?,
+            Some(_) => AddressableSet::<String, _>::set_addr(self, addr, value).await?,
+        }
+        Ok(previous)
+    }
+}
+
+impl AddressableGet<Vec<u8>, RelativePath> for FileSystemStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<Vec<u8>>, Self> {
+        self.flush_buffered(addr).await?;
+
+        match tokio::fs::read(self.get_complete_path(addr.clone())).await {
+            Ok(fil) => Ok(Some(fil)),
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(None),
+                _ => Err(e.into()),
+            },
+        }
+    }
+}
+
+impl AddressableSet<Vec<u8>, RelativePath> for FileSystemStore {
+    async fn set_addr(
+        &self,
+        addr: &RelativePath,
+        value: &Option<Vec<u8>>,
+    ) -> StoreResult<(), Self> {
+        self.flush_buffered(addr).await?;
+
+        let path = self.get_complete_path(addr.clone());
+
+        match value {
+            None => Err(FileStoreError::UnsupportedFeature(
+                "Deleting files is not supported".to_owned(),
+            )),
+            Some(contents) => Ok(tokio::fs::write(path, contents).await?),
+        }
+    }
+}
+
+/// Creates the directory, with all the missing parents.
+impl AddressableBranch<RelativePath> for FileSystemStore {
+    async fn ensure_branch(&self, addr: &RelativePath) -> StoreResult<(), Self> {
+        Ok(tokio::fs::create_dir_all(self.get_complete_path(addr.clone())).await?)
+    }
+}
+
+/// Opens the file and hands out the handle, so large files can be streamed.
+impl AddressableReadStream<RelativePath> for FileSystemStore {
+    type Reader = tokio::fs::File;
+
+    async fn read_stream(&self, addr: &RelativePath) -> StoreResult<Option<Self::Reader>, Self> {
+        self.flush_buffered(addr).await?;
+
+        match tokio::fs::File::open(self.get_complete_path(addr.clone())).await {
+            Ok(file) => Ok(Some(file)),
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(None),
+                _ => Err(e.into()),
+            },
+        }
+    }
+}
+
+impl FileSystemStore {
+    /// Read up to `len` bytes of the file, starting at the byte `start`, without
+    /// reading the rest of it. Fewer bytes are returned if the file ends before,
+    /// and none if it ends before `start`. `None` if there's no file.
+    pub async fn read_range(
+        &self,
+        addr: &RelativePath,
+        start: u64,
+        len: usize,
+    ) -> StoreResult<Option<Vec<u8>>, Self> {
+        let Some(mut file) = self.read_stream(addr).await? else {
+            return Ok(None);
+        };
+
+        let size = file.metadata().await?.len();
+        file.seek(SeekFrom::Start(start)).await?;
+
+        let available = size.saturating_sub(start).min(len as u64) as usize;
+        let mut buf = Vec::with_capacity(available);
+        file.take(len as u64).read_to_end(&mut buf).await?;
+
+        Ok(Some(buf))
+    }
+}
+
+/// Tells apart the temporary files of the writers made at the same time.
+static TEMP_FILE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Writes into a temporary file next to the target, which replaces the target
+/// once the writer is shut down. Until then the file keeps its previous contents,
+/// and if the writer is dropped before that, the temporary file is removed.
+pub struct FileWriter {
+    file: tokio::fs::File,
+    temp_path: PathBuf,
+    path: PathBuf,
+    rename: Option<BoxFuture<'static, std::io::Result<()>>>,
+    renamed: bool,
+}
+
+impl FileWriter {
+    async fn create(path: PathBuf) -> std::io::Result<Self> {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = path.with_file_name(format!(
+            ".{name}.{}.{}.tmp",
+            std::process::id(),
+            TEMP_FILE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        Ok(FileWriter {
+            file: tokio::fs::File::create(&temp_path).await?,
+            temp_path,
+            path,
+            rename: None,
+            renamed: false,
+        })
+    }
+}
+
+impl AsyncWrite for FileWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        Pin::new(&mut self.get_mut().file).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+
+        if this.renamed {
+            return Poll::Ready(Ok(()));
+        }
+
+        if this.rename.is_none() {
+            ready!(Pin::new(&mut this.file).poll_shutdown(cx))?;
+
+            this.rename =
+                Some(tokio::fs::rename(this.temp_path.clone(), this.path.clone()).boxed());
+        }
+
+        let result = ready!(this.rename.as_mut().expect("was just set").poll_unpin(cx));
+        this.rename = None;
+        this.renamed = result.is_ok();
+
+        Poll::Ready(result)
+    }
+}
+
+impl Drop for FileWriter {
+    fn drop(&mut self) {
+        if !self.renamed {
+            let _ = std::fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+/// Hands out a [`FileWriter`], so the file is replaced only when it's completely written.
+impl AddressableWriteStream<RelativePath> for FileSystemStore {
+    type Writer = FileWriter;
+
+    async fn write_stream(&self, addr: &RelativePath) -> StoreResult<Self::Writer, Self> {
+        self.flush_buffered(addr).await?;
+
+        Ok(FileWriter::create(self.get_complete_path(addr.clone())).await?)
+    }
+}
+
+impl AddressableGet<FileOrDir, RelativePath> for FileSystemStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<FileOrDir>, Self> {
+        self.flush_buffered(addr).await?;
+
+        match tokio::fs::metadata(self.get_complete_path(addr.clone())).await {
+            Ok(m) if m.is_dir() => Ok(Some(FileOrDir::Dir)),
+            Ok(_) => Ok(AddressableGet::<String, _>::addr_get(self, addr)
+                .await?
+                .map(FileOrDir::File)),
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(None),
+                _ => Err(e.into()),
+            },
+        }
+    }
+}
+
+impl AddressableSet<FileOrDir, RelativePath> for FileSystemStore {
+    async fn set_addr(
+        &self,
+        addr: &RelativePath,
+        value: &Option<FileOrDir>,
+    ) -> StoreResult<(), Self> {
+        match value {
+            None => Err(FileStoreError::UnsupportedFeature("deletion".to_owned())),
+            Some(FileOrDir::Dir) => {
+                self.flush_buffered(addr).await?;
+
+                Ok(tokio::fs::create_dir_all(self.get_complete_path(addr.clone())).await?)
+            }
+            Some(FileOrDir::File(contents)) => self.set_addr(addr, &Some(contents.clone())).await,
+        }
+    }
+}
+
 impl AddressableGet<Existence, RelativePath> for FileSystemStore {
     async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<Existence>, Self> {
+        self.flush_buffered(addr).await?;
+
         let m = tokio::fs::metadata(self.get_complete_path(addr.clone())).await;
 
         match m {
@@ -190,42 +779,251 @@ impl AddressableGet<Existence, RelativePath> for FileSystemStore {
     }
 }
 
+impl AddressableExistsMany<RelativePath> for FileSystemStore {}
+
+impl AddressableGet<Present, RelativePath> for FileSystemStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<Present>, Self> {
+        let e = AddressableGet::<Existence, _>::addr_get(self, addr).await?;
+
+        Ok(Some(Present(e.is_some())))
+    }
+}
+
+/// File metadata. Read it with `location.get::<FileMeta>()`.
+///
+/// Only the writable subset is honored by `location.set::<FileMeta>(...)`:
+/// * `readonly` -- on unix, setting it clears the write bits for everyone, and clearing it
+///   sets the owner's one only; on windows, it's the readonly attribute.
+/// * `modified` -- the modification time, if `Some`. On windows, this requires
+///   the file to be writable, so clear `readonly` first.
+///
+/// `len` and `is_dir` are ignored when writing. Writing to a path that doesn't exist
+/// is an error, and so is writing `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMeta {
+    pub len: u64,
+    pub is_dir: bool,
+    pub readonly: bool,
+    pub modified: Option<SystemTime>,
+}
+
+impl From<std::fs::Metadata> for FileMeta {
+    fn from(m: std::fs::Metadata) -> Self {
+        FileMeta {
+            len: m.len(),
+            is_dir: m.is_dir(),
+            readonly: m.permissions().readonly(),
+            modified: m.modified().ok(),
+        }
+    }
+}
+
+impl AddressableGet<FileMeta, RelativePath> for FileSystemStore {
+    async fn addr_get(&self, addr: &RelativePath) -> StoreResult<Option<FileMeta>, Self> {
+        self.flush_buffered(addr).await?;
+
+        match tokio::fs::metadata(self.get_complete_path(addr.clone())).await {
+            Ok(m) => Ok(Some(m.into())),
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(None),
+                _ => Err(e.into()),
+            },
+        }
+    }
+}
+
+impl AddressableSet<FileMeta, RelativePath> for FileSystemStore {
+    async fn set_addr(
+        &self,
+        addr: &RelativePath,
+        value: &Option<FileMeta>,
+    ) -> StoreResult<(), Self> {
+        self.flush_buffered(addr).await?;
+
+        let path = self.get_complete_path(addr.clone());
+
+        let Some(meta) = value else {
+            return Err(FileStoreError::UnsupportedFeature(
+                "FileMeta can't be deleted".to_owned(),
+            ));
+        };
+
+        let mut permissions = tokio::fs::metadata(&path).await?.permissions();
+
+        if let Some(modified) = meta.modified {
+            // on unix, the owner can set the times through a read-only handle
+            #[cfg(unix)]
+            let file = std::fs::File::open(&path)?;
+            #[cfg(not(unix))]
+            let file = std::fs::OpenOptions::new().write(true).open(&path)?;
+            file.set_modified(modified)?;
+        }
+
+        if permissions.readonly() != meta.readonly {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+
+                let mode = permissions.mode();
+                permissions.set_mode(if meta.readonly {
+                    mode & !0o222
+                } else {
+                    mode | 0o200
+                });
+            }
+            #[cfg(not(unix))]
+            permissions.set_readonly(meta.readonly);
+
+            tokio::fs::set_permissions(&path, permissions).await?;
+        }
+
+        Ok(())
+    }
+}
+
 impl<'a> AddressableList<'a, RelativePath> for FileSystemStore {
     type AddedAddress = RelativePath;
 
     type ItemAddress = RelativePath;
 
-    type ListOfAddressesStream = std::pin::Pin<
-        Box<
-            dyn 'a
-                + futures::Stream<Item = StoreResult<(Self::AddedAddress, Self::ItemAddress), Self>>,
-        >,
-    >;
+    /// `Send`, so listings can be moved to other tasks.
+    type ListOfAddressesStream =
+        BoxStream<'a, StoreResult<(Self::AddedAddress, Self::ItemAddress), Self>>;
 
     fn list(&self, addr: &RelativePath) -> Self::ListOfAddressesStream {
-        let this = self.clone();
+        self.query(addr, EntryKind::All)
+    }
+}
+
+/// Checks the path itself, without reading the directory.
+impl<'a> AddressableChild<'a, RelativePath> for FileSystemStore {
+    async fn child(
+        &self,
+        addr: &RelativePath,
+        name: &str,
+    ) -> StoreResult<Option<RelativePath>, Self> {
+        let mut components = Path::new(name).components();
+
+        // only the names that a listing could return
+        let (Some(std::path::Component::Normal(_)), None) = (components.next(), components.next())
+        else {
+            return Ok(None);
+        };
+
+        let child = addr.clone().sub(RelativePath::from(name));
+        let exists = AddressableGet::<Existence, _>::addr_get(self, &child).await?;
+
+        Ok(exists.map(|_| child))
+    }
+}
+
+/// Which entries to list: `store.sub(dir).query(EntryKind::DirsOnly)`.
+///
+/// The kind comes from the directory entry itself, without an extra `stat`.
+/// Symlinks are neither files nor dirs, so they're only listed with `All`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryKind {
+    FilesOnly,
+    DirsOnly,
+    All,
+}
+
+impl<'a> AddressableQuery<'a, EntryKind, RelativePath> for FileSystemStore {
+    fn query(&self, addr: &RelativePath, kind: EntryKind) -> Self::ListOfAddressesStream {
         let addr = addr.clone();
-        let addr2 = addr.clone();
+
+        self.read_dir(&addr)
+            .try_filter_map(move |de: DirEntry| {
+                let addr = addr.clone();
+
+                async move {
+                    let matches = match kind {
+                        EntryKind::All => true,
+                        EntryKind::FilesOnly => de.file_type().await?.is_file(),
+                        EntryKind::DirsOnly => de.file_type().await?.is_dir(),
+                    };
+
+                    if !matches {
+                        return Ok(None);
+                    }
+
+                    let name = de.file_name();
+
+                    Ok(Some((name.clone().into(), addr.sub(name.into()))))
+                }
+            })
+            .boxed()
+    }
+}
+
+impl FileSystemStore {
+    fn read_dir(&self, addr: &RelativePath) -> BoxStream<'static, StoreResult<DirEntry, Self>> {
+        let path = self.get_complete_path(addr.clone());
 
         stream::once(async move {
-            let stream = tokio_stream::wrappers::ReadDirStream::new(
-                tokio::fs::read_dir(this.get_complete_path(addr.clone())).await?,
-            )
-            .map_err(|e| e.into());
+            let stream =
+                tokio_stream::wrappers::ReadDirStream::new(tokio::fs::read_dir(path).await?)
+                    .map_err(|e| e.into());
 
             Ok::<_, FileStoreError>(stream)
         })
         .try_flatten()
-        .and_then(move |de: DirEntry| {
-            let addr = addr2.clone();
+        .boxed()
+    }
+}
 
-            async move {
-                let name = de.file_name();
+/// The order of [`FileSystemStore::list_ordered`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Name,
+    /// Oldest first.
+    ModifiedTime,
+    /// Smallest first.
+    Size,
+}
 
-                Ok((name.clone().into(), addr.sub(name.into())))
+impl FileSystemStore {
+    /// Like `list`, but in a stable order, e.g. for reproducible output.
+    ///
+    /// The whole directory is read (and, for `ModifiedTime` and `Size`, every entry is `stat`ed)
+    /// before the first item is yielded, so prefer `list` when the order doesn't matter.
+    /// Entries with the same time or size are ordered by name.
+    pub fn list_ordered<'a>(
+        &self,
+        addr: &RelativePath,
+        by: SortKey,
+    ) -> BoxStream<'a, StoreResult<(RelativePath, RelativePath), Self>> {
+        let this = self.clone();
+        let listing = self.list(addr);
+
+        stream::once(async move {
+            let mut entries = vec![];
+
+            for (name, path) in listing.try_collect::<Vec<_>>().await? {
+                let meta = match by {
+                    SortKey::Name => None,
+                    _ => Some(tokio::fs::metadata(this.get_complete_path(path.clone())).await?),
+                };
+
+                let key = match meta {
+                    None => (None, 0),
+                    Some(m) if by == SortKey::ModifiedTime => (Some(m.modified()?), 0),
+                    Some(m) => (None, m.len()),
+                };
+
+                entries.push((key, name, path));
             }
+
+            entries.sort_by(|(a, a_name, _), (b, b_name, _)| {
+                a.cmp(b).then_with(|| a_name.0.cmp(&b_name.0))
+            });
+
+            Ok::<_, FileStoreError>(stream::iter(
+                entries.into_iter().map(|(_, name, path)| Ok((name, path))),
+            ))
         })
-        .boxed_local()
+        .try_flatten()
+        .boxed()
     }
 }
 
@@ -248,6 +1046,45 @@ impl<'a> AddressableTree<'a, RelativePath, FilePath> for FileSystemStore {
             )))
         }
     }
+
+    /// The kinds come from the directory entries; only symlinks are `stat`ed,
+    /// as `branch_or_leaf` follows them.
+    fn list_classified(
+        &self,
+        addr: &RelativePath,
+    ) -> LocalBoxStream<'a, StoreResult<BranchOrLeaf<RelativePath, FilePath>, Self>>
+    where
+        Self: 'a,
+        FilePath: 'a,
+    {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        self.read_dir(&addr)
+            .and_then(move |de| {
+                let this = this.clone();
+                let path = addr.clone().sub(de.file_name().into());
+
+                async move {
+                    let typ = de.file_type().await?;
+
+                    if typ.is_dir() {
+                        Ok(BranchOrLeaf::Branch(path))
+                    } else if typ.is_file() {
+                        Ok(BranchOrLeaf::Leaf(path.into()))
+                    } else {
+                        this.branch_or_leaf(path).await
+                    }
+                }
+            })
+            .boxed_local()
+    }
+}
+
+impl MappableKeys for FilePath {
+    fn map_last_key(&self, f: &dyn Fn(&str) -> Option<String>) -> Option<Self> {
+        self.0.map_last_key(f).map(FilePath)
+    }
 }
 
 impl Address for FilePath {
@@ -255,7 +1092,633 @@ impl Address for FilePath {
         self.0.own_name()
     }
 
+    fn display_name(&self) -> String {
+        self.0.display_name()
+    }
+
     fn as_parts(&self) -> Vec<String> {
         self.0.as_parts()
     }
 }
+
+impl Addressable<FilePath> for FileSystemStore {
+    type DefaultValue = String;
+}
+
+/// Files read like their [`RelativePath`]s, e.g. the leaves of a walk.
+impl<V> AddressableGet<V, FilePath> for FileSystemStore
+where
+    FileSystemStore: AddressableGet<V, RelativePath>,
+{
+    async fn addr_get(&self, addr: &FilePath) -> StoreResult<Option<V>, Self> {
+        self.addr_get(&addr.0).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use futures::TryStreamExt;
+
+    use crate::{
+        address::{
+            traits::{AddressableList, AddressableTree, BranchOrLeaf},
+            Address, ResolveAddress,
+        },
+        store::{RootKind, Store, StoreEx},
+        test_util::temp_dir,
+    };
+
+    use super::{
+        EntryKind, FileMeta, FilePath, FileStoreError, FileSystemStore, RelativePath, SortKey,
+    };
+
+    #[test]
+    fn test_parse_relative_path() {
+        assert!("a/b.txt".parse::<RelativePath>().is_ok());
+        assert!("./a".parse::<RelativePath>().is_ok());
+        assert!("/etc".parse::<RelativePath>().is_err());
+        assert!("a/../../etc".parse::<RelativePath>().is_err());
+    }
+
+    #[test]
+    fn test_display_name() {
+        let path: RelativePath = "a/b.txt".parse().unwrap();
+        assert_eq!(path.own_name(), "b.txt");
+        assert_eq!(path.display_name(), "b.txt");
+    }
+
+    #[tokio::test]
+    async fn test_child() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir_all(dir.join("a")).await?;
+
+        let store = FileSystemStore::new(dir.clone());
+        store
+            .path("a/b.txt")?
+            .set(&Some("hello".to_owned()))
+            .await?;
+
+        let a = store.path("a")?;
+        assert_eq!(a.child("b.txt").await?, Some("a/b.txt".parse()?));
+        assert_eq!(a.child("c.txt").await?, None);
+        assert_eq!(a.child("../a").await?, None);
+        assert_eq!(a.child("").await?, None);
+
+        // it doesn't read the directory, which doesn't exist
+        let missing = store.path("missing")?;
+        assert!(missing.list().try_collect::<Vec<_>>().await.is_err());
+        assert_eq!(missing.child("b.txt").await?, None);
+
+        tokio::fs::remove_dir_all(dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_file_meta() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let store = FileSystemStore::new(dir.clone());
+        let loc = store.path("file.txt")?;
+
+        assert_eq!(loc.get::<FileMeta>().await?, None);
+
+        let meta = FileMeta {
+            len: 0,
+            is_dir: false,
+            readonly: true,
+            modified: None,
+        };
+        assert!(loc.set(&Some(meta.clone())).await.is_err());
+
+        loc.set(&Some("hello".to_owned())).await?;
+
+        let read = loc.get::<FileMeta>().await?.unwrap();
+        assert_eq!(read.len, 5);
+        assert!(!read.readonly);
+
+        loc.set(&Some(meta.clone())).await?;
+        assert!(loc.get::<FileMeta>().await?.unwrap().readonly);
+
+        loc.set(&Some(FileMeta {
+            readonly: false,
+            ..meta
+        }))
+        .await?;
+        assert!(!loc.get::<FileMeta>().await?.unwrap().readonly);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = std::fs::metadata(dir.join("file.txt"))?
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o222, 0o200);
+        }
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query_entry_kind() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir_all(dir.join("sub1")).await?;
+        tokio::fs::create_dir_all(dir.join("sub2/nested")).await?;
+        tokio::fs::write(dir.join("a.txt"), "a").await?;
+        tokio::fs::write(dir.join("sub1/b.txt"), "b").await?;
+
+        let root = FileSystemStore::new(dir.clone()).root();
+
+        let names = |kind| {
+            root.query(kind)
+                .map_ok(|(name, _)| name.to_string())
+                .try_collect::<BTreeSet<_>>()
+        };
+
+        assert_eq!(
+            names(EntryKind::FilesOnly).await?,
+            BTreeSet::from(["a.txt".to_owned()])
+        );
+        assert_eq!(
+            names(EntryKind::DirsOnly).await?,
+            BTreeSet::from(["sub1".to_owned(), "sub2".to_owned()])
+        );
+        assert_eq!(names(EntryKind::All).await?.len(), 3);
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    static_assertions::assert_impl_all!(
+        <FileSystemStore as AddressableList<'static, RelativePath>>::ListOfAddressesStream: Send
+    );
+
+    #[tokio::test]
+    async fn test_list_in_task() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        let store = FileSystemStore::new(dir.clone());
+        store.path("a")?.ensure_branch().await?;
+
+        let listing = store.root().list();
+        let listed = tokio::spawn(listing.try_collect::<Vec<_>>()).await??;
+        assert_eq!(listed.len(), 1);
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ensure_branch() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        let store = FileSystemStore::new(dir.clone());
+
+        let deep = store.path("a/b/c")?;
+        deep.ensure_branch().await?;
+        deep.ensure_branch().await?;
+        assert!(tokio::fs::metadata(dir.join("a/b/c")).await?.is_dir());
+
+        let file = store.path("a/file.txt")?;
+        file.set_string("x").await?;
+        assert!(file.ensure_branch().await.is_err());
+        assert_eq!(file.get_string().await?, Some("x".to_owned()));
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_is_branch() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        let store = FileSystemStore::new(dir.clone());
+
+        store.path("a")?.ensure_branch().await?;
+        store.path("a/file.txt")?.set_string("x").await?;
+
+        assert!(store.path("a")?.is_branch().await?);
+        assert!(!store.path("a")?.is_leaf().await?);
+        assert!(store.path("a/file.txt")?.is_leaf().await?);
+        assert!(store.path("a/nope")?.is_branch().await.is_err());
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compare_and_swap() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let file = FileSystemStore::new(dir.clone()).path("file.txt")?;
+
+        assert!(file.compare_and_swap(&None, &Some("a".to_owned())).await?);
+        assert!(!file.compare_and_swap(&None, &Some("b".to_owned())).await?);
+        assert!(
+            file.compare_and_swap(&Some("a".to_owned()), &Some("c".to_owned()))
+                .await?
+        );
+        assert_eq!(file.get_string().await?, Some("c".to_owned()));
+
+        assert!(!file.compare_and_swap(&Some("a".to_owned()), &None).await?);
+        assert!(file.compare_and_swap(&Some("c".to_owned()), &None).await?);
+        assert_eq!(file.get_string().await?, None);
+        assert!(!dir.join("file.txt").exists());
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_child() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+        tokio::fs::write(dir.join("a.txt"), "a").await?;
+        tokio::fs::write(dir.join("config.json"), "{}").await?;
+
+        let root = FileSystemStore::new(dir.clone()).root();
+
+        let found = root.find_child(|p| p.own_name() == "config.json").await?;
+        assert_eq!(found, Some("config.json".parse()?));
+        assert_eq!(root.find_child(|p| p.own_name() == "nope").await?, None);
+
+        let (path, contents) = root.find_child_value(|v: &String| v == "a").await?.unwrap();
+        assert_eq!(
+            (path.own_name(), contents),
+            ("a.txt".to_owned(), "a".to_owned())
+        );
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_parsed_values() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+        tokio::fs::write(dir.join("pid"), "42\n").await?;
+
+        let store = FileSystemStore::new(dir.clone());
+
+        assert_eq!(store.path("pid")?.get::<i64>().await?, Some(42));
+        assert_eq!(store.path("pid")?.get::<f64>().await?, Some(42.0));
+        assert!(matches!(
+            store.path("pid")?.get::<bool>().await,
+            Err(FileStoreError::ParseError(_))
+        ));
+        assert_eq!(store.path("missing")?.get::<i64>().await?, None);
+
+        store.path("flag")?.set(&Some(true)).await?;
+        assert_eq!(
+            store.path("flag")?.get_string().await?,
+            Some("true\n".to_owned())
+        );
+        assert_eq!(store.path("flag")?.get::<bool>().await?, Some(true));
+
+        store.path("ratio")?.set(&Some(0.5f64)).await?;
+        assert_eq!(store.path("ratio")?.get::<f64>().await?, Some(0.5));
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_ordered() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let store = FileSystemStore::new(dir.clone());
+        let now = std::time::SystemTime::now();
+
+        for (name, contents, age) in [("b", "1", 10), ("c", "333", 30), ("a", "22", 20)] {
+            tokio::fs::write(dir.join(name), contents).await?;
+            std::fs::File::options()
+                .write(true)
+                .open(dir.join(name))?
+                .set_modified(now - std::time::Duration::from_secs(age))?;
+        }
+
+        let names = |by| {
+            store
+                .list_ordered(&store.root().address, by)
+                .map_ok(|(name, _)| name.to_string())
+                .try_collect::<Vec<_>>()
+        };
+
+        assert_eq!(names(SortKey::Name).await?, vec!["a", "b", "c"]);
+        assert_eq!(names(SortKey::ModifiedTime).await?, vec!["c", "a", "b"]);
+        assert_eq!(names(SortKey::Size).await?, vec!["b", "a", "c"]);
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rebase() -> Result<(), anyhow::Error> {
+        let store = FileSystemStore::here()?;
+        let src = store.rebase("src".into())?;
+
+        let lib = src.path("lib.rs")?;
+        assert_eq!(
+            src.get_complete_path(lib.address.clone()),
+            store.get_complete_path("src/lib.rs".into())
+        );
+        assert!(lib.exists().await?);
+
+        assert!(matches!(
+            store.rebase("../src".into()),
+            Err(FileStoreError::InvalidPath(_))
+        ));
+        assert!(matches!(
+            src.rebase("/etc".into()),
+            Err(FileStoreError::InvalidPath(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_root_kind() {
+        assert_eq!(FileSystemStore::new(".".into()).root_kind(), RootKind::Tree);
+    }
+
+    #[tokio::test]
+    async fn test_write_stream() -> Result<(), anyhow::Error> {
+        use tokio::io::AsyncWriteExt;
+
+        use crate::address::traits::AddressableWriteStream;
+
+        let dir = temp_dir();
+        let store = FileSystemStore::new(dir.clone());
+        store.root().ensure_branch().await?;
+        store.path("data.txt")?.set_bytes(b"old").await?;
+
+        let file = "data.txt".into();
+
+        let mut writer = store.write_stream(&file).await?;
+        writer.write_all(b"dropped").await?;
+        drop(writer);
+        assert_eq!(
+            store.path("data.txt")?.get_bytes().await?,
+            Some(b"old".to_vec())
+        );
+
+        let mut writer = store.write_stream(&file).await?;
+        writer.write_all(b"new").await?;
+        writer.flush().await?;
+        assert_eq!(
+            store.path("data.txt")?.get_bytes().await?,
+            Some(b"old".to_vec())
+        );
+        writer.shutdown().await?;
+        assert_eq!(
+            store.path("data.txt")?.get_bytes().await?,
+            Some(b"new".to_vec())
+        );
+
+        let mut entries = tokio::fs::read_dir(&dir).await?;
+        let mut names = vec![];
+        while let Some(entry) = entries.next_entry().await? {
+            names.push(entry.file_name());
+        }
+        assert_eq!(names, vec!["data.txt"]);
+
+        tokio::fs::remove_dir_all(dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_read_range() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        let store = FileSystemStore::new(dir.clone());
+        store.root().ensure_branch().await?;
+        store.path("data.bin")?.set_bytes(b"0123456789").await?;
+
+        let file = "data.bin".into();
+        assert_eq!(store.read_range(&file, 3, 4).await?, Some(b"3456".to_vec()));
+        assert_eq!(store.read_range(&file, 8, 10).await?, Some(b"89".to_vec()));
+        assert_eq!(store.read_range(&file, 20, 5).await?, Some(vec![]));
+        assert_eq!(
+            store.read_range(&file, 8, usize::MAX).await?,
+            Some(b"89".to_vec())
+        );
+        assert_eq!(store.read_range(&"missing.bin".into(), 0, 5).await?, None);
+
+        tokio::fs::remove_dir_all(dir).await?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve() {
+        let resolve = |from: &str, relative: &str| {
+            RelativePath::from(from)
+                .resolve(relative)
+                .map(|p| p.to_string())
+        };
+
+        assert_eq!(resolve("a/b", "..").unwrap(), "a");
+        assert_eq!(resolve("a/b", ".").unwrap(), "a/b");
+        assert_eq!(resolve("a/b", "../c/./d").unwrap(), "a/c/d");
+        assert_eq!(
+            resolve("a", "../..//x").unwrap_err().to_string(),
+            "`..` goes above the root: ../..//x"
+        );
+        assert!(matches!(
+            resolve("a", "/etc"),
+            Err(FileStoreError::InvalidPath(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_list_classified() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir_all(dir.join("sub")).await?;
+        tokio::fs::write(dir.join("a.txt"), "a").await?;
+        tokio::fs::write(dir.join("sub/b.txt"), "b").await?;
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(dir.join("sub"), dir.join("link"))?;
+
+        let store = FileSystemStore::new(dir.clone());
+        let root = store.root().address;
+
+        let classified = store
+            .list_classified(&root)
+            .map_ok(|bl| (bl.to_string(), bl.unit()))
+            .try_collect::<BTreeSet<_>>()
+            .await?;
+
+        let mut expected = BTreeSet::new();
+        let mut items = store.list(&root);
+        while let Some((_, item)) = items.try_next().await? {
+            let bl = store.branch_or_leaf(item).await?;
+            expected.insert((bl.to_string(), bl.unit()));
+        }
+
+        assert_eq!(classified, expected);
+        assert!(classified.contains(&("sub".to_owned(), BranchOrLeaf::Branch(()))));
+        assert!(classified.contains(&("a.txt".to_owned(), BranchOrLeaf::Leaf(()))));
+        #[cfg(unix)]
+        assert!(classified.contains(&("link".to_owned(), BranchOrLeaf::Branch(()))));
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_leaves_with_values() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir_all(dir.join("sub/deeper")).await?;
+        tokio::fs::write(dir.join("a.txt"), "a").await?;
+        tokio::fs::write(dir.join("sub/b.txt"), "b").await?;
+        tokio::fs::write(dir.join("sub/deeper/c.txt"), "c").await?;
+
+        let store = FileSystemStore::new(dir.clone());
+
+        let files = store
+            .root()
+            .stream_leaves_with_values::<FilePath, String>()
+            .map_ok(|(file, contents)| (file.to_string(), contents))
+            .try_collect::<BTreeSet<_>>()
+            .await?;
+
+        assert_eq!(
+            files,
+            BTreeSet::from([
+                ("a.txt".to_owned(), "a".to_owned()),
+                ("sub/b.txt".to_owned(), "b".to_owned()),
+                ("sub/deeper/c.txt".to_owned(), "c".to_owned()),
+            ])
+        );
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_swap() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let file = FileSystemStore::new(dir.clone()).path("file.txt")?;
+
+        assert_eq!(file.swap(&Some("a".to_owned())).await?, None);
+        assert_eq!(
+            file.swap(&Some("b".to_owned())).await?,
+            Some("a".to_owned())
+        );
+        assert_eq!(file.get_string().await?, Some("b".to_owned()));
+
+        assert_eq!(file.swap(&None).await?, Some("b".to_owned()));
+        assert!(!dir.join("file.txt").exists());
+        assert_eq!(file.swap(&None).await?, None);
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_to_writer() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let store = FileSystemStore::new(dir.clone());
+        let loc = store.path("file.txt")?;
+
+        let mut out = vec![];
+        assert_eq!(loc.copy_to_writer(&mut out).await?, None);
+
+        loc.set(&Some("hello".repeat(10_000))).await?;
+        assert_eq!(loc.copy_to_writer(&mut out).await?, Some(50_000));
+        assert_eq!(out, "hello".repeat(10_000).into_bytes());
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "debounce")]
+    #[tokio::test]
+    async fn test_buffered_then_bytes() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        tokio::fs::create_dir_all(dir.join("sub")).await?;
+
+        let store = FileSystemStore::new_buffered(dir.clone(), std::time::Duration::from_secs(60));
+        let file = store.path("file.txt")?;
+
+        file.set(&Some("text".to_owned())).await?;
+        assert_eq!(file.get::<Vec<u8>>().await?, Some(b"text".to_vec()));
+
+        file.set(&Some("newer text".to_owned())).await?;
+        file.set(&Some(b"bytes".to_vec())).await?;
+        store.flush().await?;
+        assert_eq!(tokio::fs::read(dir.join("file.txt")).await?, b"bytes");
+
+        // a rebased store shares the buffer
+        store
+            .path("sub/a.txt")?
+            .set(&Some("text".to_owned()))
+            .await?;
+        let sub = store.rebase("sub".into())?;
+        assert_eq!(
+            sub.path("a.txt")?.get::<String>().await?,
+            Some("text".to_owned())
+        );
+        sub.path("a.txt")?.set(&Some(b"bytes".to_vec())).await?;
+        store.flush().await?;
+        assert_eq!(tokio::fs::read(dir.join("sub/a.txt")).await?, b"bytes");
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[cfg(all(feature = "debounce", feature = "json"))]
+    #[tokio::test]
+    async fn test_buffered() -> Result<(), anyhow::Error> {
+        use crate::stores::located::json::LocatedJsonStore;
+
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let store = FileSystemStore::new_buffered(dir.clone(), std::time::Duration::from_secs(60));
+        let json = LocatedJsonStore::new(store.path("config.json")?);
+
+        for i in 0..100 {
+            json.path("counter")?.setv(&Some(i.into())).await?;
+        }
+
+        assert_eq!(
+            json.path("counter")?.getv().await?,
+            Some(serde_json::json!(99))
+        );
+        assert!(!dir.join("config.json").exists());
+
+        // through the JSON store
+        json.flush().await?;
+
+        let on_disk: serde_json::Value =
+            serde_json::from_str(&tokio::fs::read_to_string(dir.join("config.json")).await?)?;
+        assert_eq!(on_disk, serde_json::json!({"counter": 99}));
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use std::{collections::HashMap, sync::Arc};
+
+use futures::{stream, StreamExt};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::{
+    address::{
+        traits::{
+            AddressableChild, AddressableDelete, AddressableGet, AddressableList, AddressableSet,
+        },
+        Addressable, StringKey, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum HashMapStoreError {}
+
+/// [`StringKey`]s can't fail to parse.
+impl From<!> for HashMapStoreError {
+    fn from(value: !) -> Self {
+        value
+    }
+}
+
+/// Flat in-memory key-value store.
+///
+/// Values are addressed by [`StringKey`]s, separated by `SEP` (`.` by default, like dotted JSON paths).
+/// The keys are stored as they are: there's no hierarchy, except that listing a key
+/// lists the keys that start with it and the separator. The root (`store.root()`)
+/// is the empty key, and listing it lists all the keys. Read the whole map with
+/// [`snapshot`](HashMapStore::snapshot).
+#[derive(Debug, Clone, Default)]
+pub struct HashMapStore<V: Clone, const SEP: char = '.'> {
+    map: Arc<RwLock<HashMap<String, V>>>,
+}
+
+impl<V: Clone, const SEP: char> HashMapStore<V, SEP> {
+    pub fn new(map: HashMap<String, V>) -> Self {
+        HashMapStore {
+            map: Arc::new(RwLock::new(map)),
+        }
+    }
+
+    /// Clone out the current map.
+    pub async fn snapshot(&self) -> HashMap<String, V> {
+        self.map.read().await.clone()
+    }
+}
+
+/// The map. It's read with `try_read`, so serializing fails if it's being written at the same time.
+#[cfg(feature = "serde")]
+impl<V: Clone + serde::Serialize, const SEP: char> serde::Serialize for HashMapStore<V, SEP> {
+    fn serialize<Ser: serde::Serializer>(&self, serializer: Ser) -> Result<Ser::Ok, Ser::Error> {
+        let map = self.map.try_read().map_err(serde::ser::Error::custom)?;
+
+        map.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, V: Clone + serde::Deserialize<'de>, const SEP: char> serde::Deserialize<'de>
+    for HashMapStore<V, SEP>
+{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(Self::new(HashMap::deserialize(deserializer)?))
+    }
+}
+
+impl<V: Clone, const SEP: char> Store for HashMapStore<V, SEP> {
+    type Error = HashMapStoreError;
+
+    type RootAddress = StringKey<SEP>;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            can_write: true,
+            can_list: true,
+            can_delete: true,
+            ..Default::default()
+        }
+    }
+
+    fn root_kind(&self) -> RootKind {
+        RootKind::List
+    }
+}
+
+impl<V: Clone, const SEP: char> Addressable<StringKey<SEP>> for HashMapStore<V, SEP> {
+    type DefaultValue = V;
+}
+
+impl<V: Clone, const SEP: char> AddressableGet<V, StringKey<SEP>> for HashMapStore<V, SEP> {
+    async fn addr_get(&self, addr: &StringKey<SEP>) -> StoreResult<Option<V>, Self> {
+        Ok(self.map.read().await.get(&addr.0).cloned())
+    }
+}
+
+impl<V: Clone, const SEP: char> AddressableSet<V, StringKey<SEP>> for HashMapStore<V, SEP> {
+    async fn set_addr(&self, addr: &StringKey<SEP>, value: &Option<V>) -> StoreResult<(), Self> {
+        let mut map = self.map.write().await;
+
+        match value {
+            Some(value) => {
+                map.insert(addr.0.clone(), value.clone());
+            }
+            None => {
+                map.remove(&addr.0);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<V: Clone, const SEP: char> AddressableDelete<StringKey<SEP>> for HashMapStore<V, SEP> {
+    async fn delete(&self, addr: &StringKey<SEP>) -> StoreResult<(), Self> {
+        self.map.write().await.remove(&addr.0);
+        Ok(())
+    }
+}
+
+impl<'a, V: 'a + Clone, const SEP: char> AddressableList<'a, StringKey<SEP>>
+    for HashMapStore<V, SEP>
+{
+    type AddedAddress = StringKey<SEP>;
+
+    type ItemAddress = StringKey<SEP>;
+
+    /// All the keys starting with `addr` and a separator, sorted.
+    fn list(&self, addr: &StringKey<SEP>) -> Self::ListOfAddressesStream {
+        let map = self.map.clone();
+        let addr = addr.clone();
+        let prefix = if addr.0.is_empty() {
+            String::new()
+        } else {
+            format!("{}{SEP}", addr.0)
+        };
+
+        stream::once(async move {
+            let mut keys = map
+                .read()
+                .await
+                .keys()
+                .filter_map(|k| k.strip_prefix(&prefix))
+                .map(str::to_owned)
+                .collect::<Vec<_>>();
+            keys.sort();
+
+            stream::iter(
+                keys.into_iter()
+                    .map(move |k| Ok((StringKey(k.clone()), addr.clone().sub(StringKey(k))))),
+            )
+        })
+        .flatten()
+        .boxed_local()
+    }
+}
+
+impl<'a, V: 'a + Clone, const SEP: char> AddressableChild<'a, StringKey<SEP>>
+    for HashMapStore<V, SEP>
+{
+}
+
+#[cfg(test)]
+mod test {
+    use futures::TryStreamExt;
+
+    use crate::{
+        address::{primitive::Present, StringKey},
+        store::{RootKind, Store, StoreEx},
+    };
+
+    use super::HashMapStore;
+
+    #[tokio::test]
+    async fn test_hashmap() -> Result<(), anyhow::Error> {
+        let store = HashMapStore::<i32>::default();
+
+        store.path("db.port")?.setv(&Some(5432)).await?;
+        store.path("db.replicas")?.setv(&Some(2)).await?;
+        store.path("debug")?.setv(&Some(1)).await?;
+        store.path("debug")?.delete().await?;
+
+        assert_eq!(store.path("db.port")?.getv().await?, Some(5432));
+        assert_eq!(store.path("db")?.getv().await?, None);
+        assert_eq!(store.path("db.port")?.present().await?, Present(true));
+        assert_eq!(store.path("debug")?.present().await?, Present(false));
+        assert_eq!(
+            store
+                .root()
+                .list()
+                .map_ok(|(_, addr)| addr)
+                .try_collect::<Vec<_>>()
+                .await?,
+            vec![StringKey::from("db.port"), StringKey::from("db.replicas")]
+        );
+        assert_eq!(
+            store
+                .path("db")?
+                .list()
+                .map_ok(|(added, _)| added)
+                .try_collect::<Vec<_>>()
+                .await?,
+            vec![StringKey::from("port"), StringKey::from("replicas")]
+        );
+        assert_eq!(
+            store.path("db")?.child("port").await?,
+            Some(StringKey::from("db.port"))
+        );
+        assert_eq!(store.path("db")?.child("host").await?, None);
+        assert_eq!(store.snapshot().await.len(), 2);
+        assert_eq!(store.root_kind(), RootKind::List);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn test_serde() -> Result<(), anyhow::Error> {
+        let store = HashMapStore::<Vec<i32>>::default();
+        store.path("a.b")?.setv(&Some(vec![1, 2])).await?;
+
+        let serialized = serde_json::to_value(&store)?;
+        assert_eq!(serialized, serde_json::json!({"a.b": [1, 2]}));
+
+        let restored: HashMapStore<Vec<i32>> = serde_json::from_value(serialized)?;
+        assert_eq!(restored.snapshot().await, store.snapshot().await);
+        assert_eq!(restored.path("a.b")?.getv().await?, Some(vec![1, 2]));
+
+        Ok(())
+    }
+}
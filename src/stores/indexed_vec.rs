@@ -1,17 +1,28 @@
 use std::sync::Arc;
 
 use derive_more::From;
+use futures::{stream, StreamExt, TryStreamExt};
 use thiserror::Error;
 use tokio::sync::RwLock;
 
 use crate::{
-    address::{traits::AddressableGet, Address, Addressable},
-    store::Store,
+    address::{
+        primitive::{UniqueRootAddress, WholeStore},
+        traits::{
+            AddressableGet, AddressableList, AddressableListFrom, AddressableSet, AddressableTree,
+            BranchOrLeaf,
+        },
+        Address, Addressable, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
 };
 
 #[derive(From, Debug, Error)]
 pub enum IndexedVecStoreError {}
 
+/// Vector indexed by Id derived from the value.
+///
+/// The root (`store.root()`) is the whole vector: read it or replace it at once.
 pub struct IndexedVecStore<
     V: Clone,
     IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone,
@@ -24,13 +35,48 @@ pub struct IndexedVecStore<
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Id<IdType>(IdType);
 
-impl<IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static> Address for Id<IdType> {
+crate::id_address! {
+    impl[IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static] Id<IdType> { 0 }
+}
+
+/// Address for walking the store as a (flat) tree: the root, or one of the items.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum IndexedVecTreeAddress<IdType> {
+    Root,
+    Item(Id<IdType>),
+}
+
+impl<IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static> Address
+    for IndexedVecTreeAddress<IdType>
+{
     fn own_name(&self) -> String {
-        self.0.to_string()
+        match self {
+            IndexedVecTreeAddress::Root => "".to_owned(),
+            IndexedVecTreeAddress::Item(id) => id.own_name(),
+        }
     }
 
     fn as_parts(&self) -> Vec<String> {
-        vec![self.0.to_string()]
+        match self {
+            IndexedVecTreeAddress::Root => vec![],
+            IndexedVecTreeAddress::Item(id) => id.as_parts(),
+        }
+    }
+}
+
+impl<IdType> From<UniqueRootAddress> for IndexedVecTreeAddress<IdType> {
+    fn from(_: UniqueRootAddress) -> Self {
+        IndexedVecTreeAddress::Root
+    }
+}
+
+impl<IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static> SubAddress<Id<IdType>>
+    for IndexedVecTreeAddress<IdType>
+{
+    type Output = IndexedVecTreeAddress<IdType>;
+
+    fn sub(self, sub: Id<IdType>) -> Self::Output {
+        IndexedVecTreeAddress::Item(sub)
     }
 }
 
@@ -56,7 +102,20 @@ impl<
 {
     type Error = IndexedVecStoreError;
 
-    type RootAddress = crate::address::primitive::UniqueRootAddress;
+    type RootAddress = UniqueRootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            can_write: true,
+            can_list: true,
+            can_delete: true,
+            ..Default::default()
+        }
+    }
+
+    fn root_kind(&self) -> RootKind {
+        RootKind::List
+    }
 }
 impl<
         V: Clone,
@@ -73,7 +132,7 @@ impl<
         F: Fn(&V) -> IdType,
     > AddressableGet<V, Id<IdType>> for Arc<IndexedVecStore<V, IdType, F>>
 {
-    async fn addr_get(&self, addr: &Id<IdType>) -> crate::store::StoreResult<Option<V>, Self> {
+    async fn addr_get(&self, addr: &Id<IdType>) -> StoreResult<Option<V>, Self> {
         Ok(self
             .vec
             .read()
@@ -84,18 +143,185 @@ impl<
     }
 }
 
+impl<
+        V: Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone,
+        F: Fn(&V) -> IdType,
+    > Addressable<UniqueRootAddress> for Arc<IndexedVecStore<V, IdType, F>>
+{
+    type DefaultValue = Vec<V>;
+}
+
+impl<
+        V: Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone,
+        F: Fn(&V) -> IdType,
+    > AddressableGet<Vec<V>, UniqueRootAddress> for Arc<IndexedVecStore<V, IdType, F>>
+{
+    async fn addr_get(&self, _addr: &UniqueRootAddress) -> StoreResult<Option<Vec<V>>, Self> {
+        Ok(Some(self.vec.read().await.clone()))
+    }
+}
+
+impl<
+        V: Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone,
+        F: Fn(&V) -> IdType,
+    > AddressableGet<WholeStore<Vec<V>>, UniqueRootAddress> for Arc<IndexedVecStore<V, IdType, F>>
+{
+    async fn addr_get(
+        &self,
+        _addr: &UniqueRootAddress,
+    ) -> StoreResult<Option<WholeStore<Vec<V>>>, Self> {
+        Ok(Some(WholeStore(self.vec.read().await.clone())))
+    }
+}
+
+impl<
+        V: Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone,
+        F: Fn(&V) -> IdType,
+    > AddressableSet<Vec<V>, UniqueRootAddress> for Arc<IndexedVecStore<V, IdType, F>>
+{
+    /// Writing `None` clears the vector.
+    async fn set_addr(
+        &self,
+        _addr: &UniqueRootAddress,
+        value: &Option<Vec<V>>,
+    ) -> StoreResult<(), Self> {
+        *self.vec.write().await = value.clone().unwrap_or_default();
+        Ok(())
+    }
+}
+
+impl<
+        V: Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static,
+        F: Fn(&V) -> IdType,
+    > Addressable<IndexedVecTreeAddress<IdType>> for Arc<IndexedVecStore<V, IdType, F>>
+{
+}
+
+impl<
+        'a,
+        V: 'a + Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static,
+        F: 'a + Fn(&V) -> IdType,
+    > AddressableList<'a, IndexedVecTreeAddress<IdType>> for Arc<IndexedVecStore<V, IdType, F>>
+{
+    type AddedAddress = Id<IdType>;
+
+    type ItemAddress = IndexedVecTreeAddress<IdType>;
+
+    fn list(&self, addr: &IndexedVecTreeAddress<IdType>) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        stream::once(async move {
+            let ids = match addr {
+                IndexedVecTreeAddress::Root => this
+                    .vec
+                    .read()
+                    .await
+                    .iter()
+                    .map(|v| Id((this.get_id)(v)))
+                    .collect(),
+                IndexedVecTreeAddress::Item(_) => vec![],
+            };
+
+            Ok(stream::iter(ids.into_iter().map(|id| {
+                Ok((id.clone(), IndexedVecTreeAddress::Item(id)))
+            })))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
+
+/// The cursor is the index of the next item in the vector.
+impl<
+        'a,
+        V: 'a + Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static,
+        F: 'a + Fn(&V) -> IdType,
+    > AddressableListFrom<'a, IndexedVecTreeAddress<IdType>>
+    for Arc<IndexedVecStore<V, IdType, F>>
+{
+    type ListCursor = usize;
+
+    fn list_from(
+        &self,
+        addr: &IndexedVecTreeAddress<IdType>,
+        cursor: Option<usize>,
+    ) -> Self::ListFromStream {
+        let this = self.clone();
+        let addr = addr.clone();
+        let start = cursor.unwrap_or(0);
+
+        stream::once(async move {
+            let ids: Vec<_> = match addr {
+                IndexedVecTreeAddress::Root => this
+                    .vec
+                    .read()
+                    .await
+                    .iter()
+                    .enumerate()
+                    .skip(start)
+                    .map(|(ix, v)| (ix + 1, Id((this.get_id)(v))))
+                    .collect(),
+                IndexedVecTreeAddress::Item(_) => vec![],
+            };
+
+            Ok(stream::iter(ids.into_iter().map(|(next, id)| {
+                Ok((next, id.clone(), IndexedVecTreeAddress::Item(id)))
+            })))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
+
+/// Every item is a leaf.
+impl<
+        'a,
+        V: 'a + Clone,
+        IdType: ToString + PartialEq + Eq + std::fmt::Debug + Clone + 'static,
+        F: 'a + Fn(&V) -> IdType,
+    > AddressableTree<'a, IndexedVecTreeAddress<IdType>, Id<IdType>>
+    for Arc<IndexedVecStore<V, IdType, F>>
+{
+    async fn branch_or_leaf(
+        &self,
+        addr: IndexedVecTreeAddress<IdType>,
+    ) -> StoreResult<BranchOrLeaf<IndexedVecTreeAddress<IdType>, Id<IdType>>, Self> {
+        Ok(match addr {
+            IndexedVecTreeAddress::Root => BranchOrLeaf::Branch(addr),
+            IndexedVecTreeAddress::Item(id) => BranchOrLeaf::Leaf(id),
+        })
+    }
+}
+
 #[cfg(test)]
 #[cfg(feature = "json")]
 mod test {
     use serde_json::json;
 
+    use futures::TryStreamExt;
+
     use crate::{
-        store::StoreEx,
-        stores::indexed_vec::{Id, IndexedVecStore},
+        address::traits::BranchOrLeaf,
+        store::{RootKind, Store, StoreEx},
+        stores::indexed_vec::{Id, IndexedVecStore, IndexedVecTreeAddress},
     };
 
     // TODO: make it a real wrapper
 
+    #[test]
+    fn test_root_kind() {
+        let s = IndexedVecStore::new(vec![json!(1)], |v| v.as_i64().unwrap());
+        assert_eq!(s.root_kind(), RootKind::List);
+    }
+
     #[tokio::test]
     async fn test() {
         let s = IndexedVecStore::new(
@@ -111,6 +337,34 @@ mod test {
 
         println!("{v:?}");
 
+        let all = s
+            .sub(IndexedVecTreeAddress::Root)
+            .walk_tree_recursively()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+
+        assert_eq!(
+            all,
+            vec![
+                BranchOrLeaf::Leaf(Id(1)),
+                BranchOrLeaf::Leaf(Id(3)),
+                BranchOrLeaf::Leaf(Id(2))
+            ]
+        );
+
+        let root = s.sub(IndexedVecTreeAddress::Root);
+        let (cursor, first, _) = root.list_from(None).try_next().await.unwrap().unwrap();
+        assert_eq!(first, Id(1));
+
+        let rest = root
+            .list_from(Some(cursor))
+            .map_ok(|(_, id, _)| id)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(rest, vec![Id(3), Id(2)]);
+
         // panic!("lol");
         // Ok(())
     }
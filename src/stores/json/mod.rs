@@ -1,24 +1,32 @@
 use crate::{
-    address::primitive::UniqueRootAddress,
-    store::{Store, StoreEx},
-    wrappers::filter_addresses::FilterAddressesWrapperError,
+    address::{primitive::UniqueRootAddress, traits::AddressableGet, Address},
+    location::Location,
+    store::{Store, StoreEx, StoreResult},
+    wrappers::{
+        audit::AuditWrapperError, filter_addresses::FilterAddressesWrapperError,
+        remap::MapKeyWrapperError,
+    },
 };
 
 use serde_json::Value;
 
 pub mod paths;
+pub mod query;
 pub(crate) mod traverse;
 
 pub use paths::*;
 
-use super::{cell::MemoryCellStore, located::json::LocatedJsonStore};
+use super::{
+    cell::MemoryCellStore,
+    located::json::{LocatedJsonStore, LocatedJsonStoreError},
+};
 
 // todo: how to make this automatic?
 // mb create a "wrapper error" struct...
 // ... or let a store handle this...
 impl From<paths::JsonPathParseError>
     for crate::wrappers::filter_addresses::FilterAddressesWrapperError<
-        FilterAddressesWrapperError<anyhow::Error>,
+        FilterAddressesWrapperError<LocatedJsonStoreError>,
     >
 {
     fn from(value: paths::JsonPathParseError) -> Self {
@@ -28,21 +36,125 @@ impl From<paths::JsonPathParseError>
     }
 }
 
-impl From<JsonPathParseError> for FilterAddressesWrapperError<anyhow::Error> {
+impl From<JsonPathParseError> for FilterAddressesWrapperError<LocatedJsonStoreError> {
     fn from(value: JsonPathParseError) -> Self {
         FilterAddressesWrapperError::StoreError(value.into())
     }
 }
 
+impl From<JsonPathParseError> for MapKeyWrapperError<LocatedJsonStoreError> {
+    fn from(value: JsonPathParseError) -> Self {
+        MapKeyWrapperError::StoreError(value.into())
+    }
+}
+
+impl From<JsonPathParseError> for AuditWrapperError<LocatedJsonStoreError> {
+    fn from(value: JsonPathParseError) -> Self {
+        AuditWrapperError::StoreError(value.into())
+    }
+}
+
+/// The type of a JSON value, without the value itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ValueKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl From<&Value> for ValueKind {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => ValueKind::Null,
+            Value::Bool(_) => ValueKind::Bool,
+            Value::Number(_) => ValueKind::Number,
+            Value::String(_) => ValueKind::String,
+            Value::Array(_) => ValueKind::Array,
+            Value::Object(_) => ValueKind::Object,
+        }
+    }
+}
+
+/// A JSON value that keeps a key set to `null` apart from a missing key.
+///
+/// Reading it is never `None`. Reading a `Value` gives `Some(Value::Null)` and `None`
+/// for these two cases, and [`Existence`](crate::address::primitive::Existence) is
+/// present for `Null` but not for `Absent`.
+///
+/// It's written with [`set_entry`](crate::stores::located::json::LocatedJsonStore::set_entry):
+/// `Absent` deletes the key, `Null` stores `null`.
+/// In a [`LocatedJsonStore`](crate::stores::located::json::LocatedJsonStore), the root
+/// of an empty document is `Absent`, and writing either there empties the document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonEntry {
+    Absent,
+    Null,
+    Value(Value),
+}
+
+impl From<Option<Value>> for JsonEntry {
+    fn from(value: Option<Value>) -> Self {
+        match value {
+            None => JsonEntry::Absent,
+            Some(Value::Null) => JsonEntry::Null,
+            Some(v) => JsonEntry::Value(v),
+        }
+    }
+}
+
+impl From<JsonEntry> for Option<Value> {
+    fn from(value: JsonEntry) -> Self {
+        match value {
+            JsonEntry::Absent => None,
+            JsonEntry::Null => Some(Value::Null),
+            JsonEntry::Value(v) => Some(v),
+        }
+    }
+}
+
 pub type JsonValueStore = LocatedJsonStore<UniqueRootAddress, MemoryCellStore<String>>;
 pub type JsonValueStoreError = <JsonValueStore as Store>::Error;
 
 pub fn json_value_store(val: Value) -> Result<JsonValueStore, JsonValueStoreError> {
-    let cell_store = MemoryCellStore::new(Some(serde_json::to_string(&val)?));
+    let cell_store = MemoryCellStore::from_json(&val)?;
 
     Ok(LocatedJsonStore::new(cell_store.root()))
 }
 
+/// Copy the value at a location into memory, e.g. to edit it and discard the changes.
+///
+/// The snapshot can be browsed and edited by [`JsonPath`]s.
+///
+/// ```
+/// use serde_json::json;
+///
+/// use anystore::stores::json::*;
+/// use anystore::store::StoreEx;
+///
+/// # tokio_test::block_on(async {
+/// let store = json_value_store(json!({"a": {"b": 1}}))?;
+///
+/// let snapshot = snapshot_to_memory(&store.path("a")?).await?;
+/// snapshot
+///     .sub(JsonPath(vec![JsonPathPart::Key("b".to_owned())]))
+///     .setv(&Some(json!(2)))
+///     .await?;
+///
+/// assert_eq!(snapshot.root().getv().await?, Some(json!({"b": 2})));
+/// assert_eq!(store.path("a.b")?.getv().await?, Some(json!(1)));
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+pub async fn snapshot_to_memory<A: Address, S: AddressableGet<Value, A>>(
+    location: &Location<A, S>,
+) -> StoreResult<MemoryCellStore<Value>, S> {
+    Ok(MemoryCellStore::new(location.get::<Value>().await?))
+}
+
 #[cfg(test)]
 mod test_tree {
 
@@ -56,7 +168,7 @@ mod test_tree {
     use crate::{store::StoreEx, wrappers::filter_addresses::FilterAddressesWrapperStore};
 
     use super::paths::*;
-    use crate::address::primitive::UniqueRootAddress;
+    use crate::address::primitive::{Present, UniqueRootAddress};
     use crate::address::SubAddress;
 
     #[tokio::test]
@@ -67,7 +179,7 @@ mod test_tree {
                 "_ignore": {"haha": {"_yes": 3}}
         });
 
-        let cell_store = MemoryCellStore::new(Some(serde_json::to_string(&val)?));
+        let cell_store = MemoryCellStore::from_json(&val)?;
         let json_store = LocatedJsonStore::new(cell_store.root());
 
         let store =
@@ -122,6 +234,11 @@ mod test_tree {
         some.set(&None).await?;
         println!("{:?}", cell_store.root().getv().await);
         assert_eq!(false, some.exists().await?);
+        assert_eq!(some.get::<Present>().await?, Some(Present(false)));
+        assert_eq!(
+            root.clone().path("wow")?.get::<Present>().await?,
+            Some(Present(true))
+        );
 
         println!(
             "list base {:?}",
@@ -134,4 +251,27 @@ mod test_tree {
         // Err("seems fine".to_owned().into())
         Ok(())
     }
+
+    #[tokio::test]
+    pub async fn test_sorted_list() -> Result<(), Box<dyn std::error::Error>> {
+        let store = super::json_value_store(json!({"b": 1, "c": {"z": 1, "y": 2}, "a": 3}))?;
+
+        let keys = store
+            .root()
+            .sorted_list()
+            .map_ok(|(k, _)| k.to_key())
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(keys, vec!["a", "b", "c"]);
+
+        let keys = store
+            .path("c")?
+            .sorted_list()
+            .map_ok(|(_, addr)| addr.to_string())
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(keys, vec!["c.y", "c.z"]);
+
+        Ok(())
+    }
 }
@@ -1,12 +1,32 @@
-use std::fmt::Display;
+use std::{fmt::Display, str::FromStr};
 
-use derive_more::{Display, From, IntoIterator};
+use derive_more::IntoIterator;
 use thiserror::Error;
 
-use crate::address::{primitive::UniqueRootAddress, Address, PathAddress, SubAddress};
+use crate::{
+    address::{primitive::UniqueRootAddress, Address, PathAddress, ResolveAddress, SubAddress},
+    wrappers::{filter_addresses::AddedParts, remap::MappableKeys},
+};
 
-#[derive(From, Display, Debug, Error)]
-pub struct JsonPathParseError(String);
+/// Error returned by [`JsonPath::path`]: the message, the character index
+/// in `input` where parsing failed, and the input itself.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("{message}")]
+pub struct JsonPathParseError {
+    pub message: String,
+    pub position: usize,
+    pub input: String,
+}
+
+impl JsonPathParseError {
+    fn new(message: &str, position: usize, input: &str) -> Self {
+        JsonPathParseError {
+            message: message.to_owned(),
+            position,
+            input: input.to_owned(),
+        }
+    }
+}
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum JsonPathPart {
@@ -26,6 +46,10 @@ impl JsonPathPart {
 impl Display for JsonPathPart {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            JsonPathPart::Key(key) if key.is_empty() || key.contains(['.', '[', ']']) => {
+                let escaped = key.replace('\\', "\\\\").replace('"', "\\\"");
+                write!(f, "[\"{escaped}\"]")
+            }
             JsonPathPart::Key(key) => write!(f, ".{key}"),
             JsonPathPart::Index(ix) => write!(f, "[{ix}]"),
         }
@@ -51,11 +75,12 @@ impl Display for JsonPath {
 
 impl Address for JsonPath {
     fn own_name(&self) -> String {
-        match self.0.last() {
-            None => "".to_owned(),
-            Some(JsonPathPart::Index(i)) => format!("[{i}]"),
-            Some(JsonPathPart::Key(s)) => format!(".{s}"),
-        }
+        self.0.last().map(|p| p.to_string()).unwrap_or_default()
+    }
+
+    /// The last key without punctuation, or the last index.
+    fn display_name(&self) -> String {
+        self.0.last().map(|p| p.to_key()).unwrap_or_default()
     }
 
     fn as_parts(&self) -> Vec<String> {
@@ -63,6 +88,33 @@ impl Address for JsonPath {
     }
 }
 
+impl MappableKeys for JsonPathPart {
+    fn map_last_key(&self, f: &dyn Fn(&str) -> Option<String>) -> Option<Self> {
+        match self {
+            JsonPathPart::Key(key) => f(key).map(JsonPathPart::Key),
+            JsonPathPart::Index(ix) => Some(JsonPathPart::Index(*ix)),
+        }
+    }
+}
+
+impl MappableKeys for JsonPath {
+    fn map_last_key(&self, f: &dyn Fn(&str) -> Option<String>) -> Option<Self> {
+        let mut path = self.0.clone();
+
+        if let Some(last) = path.last_mut() {
+            *last = last.map_last_key(f)?;
+        }
+
+        Some(JsonPath(path))
+    }
+}
+
+impl AddedParts<JsonPathPart> for JsonPath {
+    fn added_parts(&self) -> Vec<JsonPathPart> {
+        self.0.clone()
+    }
+}
+
 impl From<UniqueRootAddress> for JsonPath {
     fn from(_: UniqueRootAddress) -> Self {
         JsonPath(vec![])
@@ -92,50 +144,181 @@ impl PathAddress for JsonPath {
 
     type Output = JsonPath;
 
+    /// Keys are separated by `.`, indices are written as `[0]`, and keys that can't be
+    /// written bare (empty ones, or ones containing `.`, `[` or `]`) are quoted: `["a.b"]`,
+    /// with `"` and `\\` escaped by a backslash.
     fn path(self, str: &str) -> Result<Self::Output, Self::Error> {
-        let keys =
-            str.split('.')
-                .map(|chunk| {
-                    let mut chars: Vec<char> = chunk.chars().collect();
-                    let mut keys: Vec<JsonPathPart> = vec![];
-
-                    'eatindex: while chars.last() == Some(&']') {
-                        chars.pop();
-
-                        let mut ix = vec![];
-                        loop {
-                            let chr = chars
-                                .pop()
-                                .ok_or(JsonPathParseError("mismatched ]".to_string()))?;
-
-                            if chr == '[' {
-                                keys.push(JsonPathPart::Index(
-                                    ix.into_iter().rev().collect::<String>().parse().map_err(
-                                        |_| JsonPathParseError("error parsing index".to_string()),
-                                    )?,
-                                ));
-                                continue 'eatindex;
-                            } else {
-                                ix.push(chr);
-                            }
-                        }
+        let chars: Vec<char> = str.chars().collect();
+        let mut keys = vec![];
+        let mut key = String::new();
+        let mut ix = 0;
+
+        while ix < chars.len() {
+            match chars[ix] {
+                '.' => {
+                    if !key.is_empty() {
+                        keys.push(JsonPathPart::Key(std::mem::take(&mut key)));
                     }
+                    ix += 1;
+                }
+                '[' => {
+                    if !key.is_empty() {
+                        keys.push(JsonPathPart::Key(std::mem::take(&mut key)));
+                    }
+
+                    let open = ix;
+                    let (part, close) = if chars.get(open + 1) == Some(&'"') {
+                        parse_quoted_key(&chars, open, str)?
+                    } else {
+                        parse_index(&chars, open, str)?
+                    };
+                    keys.push(part);
+                    ix = close + 1;
 
-                    if !chars.is_empty() {
-                        keys.push(JsonPathPart::Key(chars.into_iter().collect()));
+                    if let Some(c) = chars.get(ix) {
+                        if *c != '.' && *c != '[' {
+                            return Err(JsonPathParseError::new(
+                                "expected . or [ after ]",
+                                ix,
+                                str,
+                            ));
+                        }
                     }
+                }
+                ']' => return Err(JsonPathParseError::new("mismatched ]", ix, str)),
+                c => {
+                    key.push(c);
+                    ix += 1;
+                }
+            }
+        }
 
-                    Ok(keys.into_iter().rev())
-                })
-                .collect::<Result<Vec<_>, JsonPathParseError>>()?
-                .into_iter()
-                .flatten()
-                .collect::<Vec<_>>();
+        if !key.is_empty() {
+            keys.push(JsonPathPart::Key(key));
+        }
 
         Ok(self.sub(JsonPath(keys)))
     }
 }
 
+/// Every `/`-separated part is parsed like in [`path`](JsonPath::path),
+/// e.g. `../sibling.items[2]`. A `/` in a quoted key doesn't separate parts.
+impl ResolveAddress for JsonPath {
+    type Error = JsonPathParseError;
+
+    fn resolve(mut self, relative: &str) -> Result<Self, Self::Error> {
+        let chars: Vec<char> = relative.chars().collect();
+        let mut segments = vec![];
+        let mut start = 0;
+        let mut quoted = false;
+        let mut ix = 0;
+
+        while ix < chars.len() {
+            match chars[ix] {
+                '\\' if quoted => ix += 1,
+                '"' if quoted => quoted = false,
+                '"' if ix > 0 && chars[ix - 1] == '[' => quoted = true,
+                '/' if !quoted => {
+                    segments.push((start, ix));
+                    start = ix + 1;
+                }
+                _ => {}
+            }
+            ix += 1;
+        }
+        segments.push((start, chars.len()));
+
+        for (start, end) in segments {
+            let segment: String = chars[start..end].iter().collect();
+
+            match segment.as_str() {
+                "" | "." => {}
+                ".." => {
+                    if self.0.pop().is_none() {
+                        return Err(JsonPathParseError::new(
+                            "`..` goes above the root",
+                            start,
+                            relative,
+                        ));
+                    }
+                }
+                _ => {
+                    self = self.path(&segment).map_err(|e| JsonPathParseError {
+                        position: start + e.position,
+                        input: relative.to_owned(),
+                        ..e
+                    })?
+                }
+            }
+        }
+
+        Ok(self)
+    }
+}
+
+/// `[123]` starting at `open`. Returns the index and the position of `]`.
+fn parse_index(
+    chars: &[char],
+    open: usize,
+    str: &str,
+) -> Result<(JsonPathPart, usize), JsonPathParseError> {
+    let close = chars[open + 1..]
+        .iter()
+        .position(|c| *c == ']' || *c == '[')
+        .map(|p| open + 1 + p)
+        .filter(|p| chars[*p] == ']')
+        .ok_or_else(|| JsonPathParseError::new("mismatched [", open, str))?;
+
+    chars[open + 1..close]
+        .iter()
+        .collect::<String>()
+        .parse()
+        .map(|ix| (JsonPathPart::Index(ix), close))
+        .map_err(|_| JsonPathParseError::new("error parsing index", open, str))
+}
+
+/// `["key"]` starting at `open`. Returns the key and the position of `]`.
+fn parse_quoted_key(
+    chars: &[char],
+    open: usize,
+    str: &str,
+) -> Result<(JsonPathPart, usize), JsonPathParseError> {
+    let mut key = String::new();
+    let mut ix = open + 2;
+
+    loop {
+        match chars.get(ix) {
+            None => return Err(JsonPathParseError::new("unterminated key", open + 1, str)),
+            Some('\\') => {
+                let escaped = chars
+                    .get(ix + 1)
+                    .ok_or_else(|| JsonPathParseError::new("unterminated key", open + 1, str))?;
+                key.push(*escaped);
+                ix += 2;
+            }
+            Some('"') => break,
+            Some(c) => {
+                key.push(*c);
+                ix += 1;
+            }
+        }
+    }
+
+    if chars.get(ix + 1) != Some(&']') {
+        return Err(JsonPathParseError::new("mismatched [", open, str));
+    }
+
+    Ok((JsonPathPart::Key(key), ix + 1))
+}
+
+impl FromStr for JsonPath {
+    type Err = JsonPathParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonPath(vec![]).path(s)
+    }
+}
+
 impl From<JsonPath> for String {
     fn from(value: JsonPath) -> Self {
         value.to_string()
@@ -147,3 +330,121 @@ impl From<JsonPathPart> for String {
         value.to_string()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use proptest::prelude::*;
+
+    use crate::address::{Address, PathAddress, ResolveAddress};
+
+    use super::{JsonPath, JsonPathPart};
+
+    #[test]
+    fn test_resolve() {
+        let resolve = |from: &str, relative: &str| {
+            JsonPath(vec![])
+                .path(from)
+                .unwrap()
+                .resolve(relative)
+                .map(|p| p.to_string())
+        };
+
+        assert_eq!(resolve("a.b", "..").unwrap(), "a");
+        assert_eq!(resolve("a.b", ".").unwrap(), "a.b");
+        assert_eq!(resolve("a.b", "./c").unwrap(), "a.b.c");
+        assert_eq!(resolve("a.b[1]", "../../c/./d[0]").unwrap(), "a.c.d[0]");
+        assert_eq!(resolve("a", "..").unwrap(), "");
+        assert_eq!(
+            JsonPath(vec![]).resolve("[\"x/y\"]/z").unwrap(),
+            JsonPath(vec![
+                JsonPathPart::Key("x/y".to_owned()),
+                JsonPathPart::Key("z".to_owned())
+            ])
+        );
+
+        let err = resolve("a", "../..").unwrap_err();
+        assert_eq!(err.position, 3);
+        assert_eq!(err.input, "../..");
+
+        let err = resolve("a", "../b[x]").unwrap_err();
+        assert_eq!(err.position, 4);
+        assert_eq!(err.input, "../b[x]");
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        let err = JsonPath(vec![]).path("a[b").unwrap_err();
+        assert_eq!(err.position, 1);
+        assert_eq!(err.input, "a[b");
+        assert_eq!(err.to_string(), "mismatched [");
+
+        let err = JsonPath(vec![]).path("a]").unwrap_err();
+        assert_eq!(err.position, 1);
+        assert_eq!(err.to_string(), "mismatched ]");
+
+        let err = JsonPath(vec![]).path("x.y[z]").unwrap_err();
+        assert_eq!(err.position, 3);
+        assert_eq!(err.to_string(), "error parsing index");
+
+        assert_eq!(
+            JsonPath(vec![]).path("x.y[1][2]").unwrap(),
+            JsonPath(vec![
+                JsonPathPart::Key("x".to_owned()),
+                JsonPathPart::Key("y".to_owned()),
+                JsonPathPart::Index(1),
+                JsonPathPart::Index(2),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_display_name() {
+        let path: JsonPath = r#"a["b.c"][3]"#.parse().unwrap();
+        assert_eq!(path.own_name(), "[3]");
+        assert_eq!(path.display_name(), "3");
+
+        let parent = JsonPath(path.0[..2].to_vec());
+        assert_eq!(parent.own_name(), r#"["b.c"]"#);
+        assert_eq!(parent.display_name(), "b.c");
+
+        assert_eq!(JsonPath(vec![]).display_name(), "");
+    }
+
+    #[test]
+    fn test_quoted_keys() {
+        let path = JsonPath(vec![])
+            .path(r#"a["b.c"][0]["\"q\"\\"].d"#)
+            .unwrap();
+        assert_eq!(
+            path,
+            JsonPath(vec![
+                JsonPathPart::Key("a".to_owned()),
+                JsonPathPart::Key("b.c".to_owned()),
+                JsonPathPart::Index(0),
+                JsonPathPart::Key("\"q\"\\".to_owned()),
+                JsonPathPart::Key("d".to_owned()),
+            ])
+        );
+        assert_eq!(path.to_string(), r#"a["b.c"][0]."q"\.d"#);
+
+        let err = JsonPath(vec![]).path(r#"a["b"#).unwrap_err();
+        assert_eq!(err.position, 2);
+        assert_eq!(err.to_string(), "unterminated key");
+    }
+
+    fn part() -> impl Strategy<Value = JsonPathPart> {
+        prop_oneof![
+            any::<usize>().prop_map(JsonPathPart::Index),
+            "[a-z.\\[\\]\"\\\\ ]{0,6}".prop_map(JsonPathPart::Key),
+            any::<String>().prop_map(JsonPathPart::Key),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_display_round_trip(parts in prop::collection::vec(part(), 0..6)) {
+            let path = JsonPath(parts);
+            prop_assert_eq!(path.to_string().parse::<JsonPath>(), Ok(path));
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use serde_json::Value;
+
+use super::paths::{JsonPath, JsonPathParseError, JsonPathPart};
+
+/// A JSONPath expression, to find values by
+/// [`AddressableQuery`](crate::address::traits::AddressableQuery) in JSON stores.
+///
+/// Only a subset of JSONPath is supported:
+///
+/// - `$` -- the queried value; every expression starts with it
+/// - `.key`, `['key']`, `["key"]` -- a key of an object
+/// - `[2]` -- an index of an array
+/// - `.*`, `[*]` -- all the keys of an object or the items of an array
+/// - `..key`, `..*`, `..[2]` -- recursive descent: the same, for the value and all its descendants
+///
+/// Filters, slices, unions, and negative indices are not supported.
+/// The matches come in document order. `$` itself isn't a match.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JsonQuery(pub String);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Selector {
+    Part(JsonPathPart),
+    Wildcard,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    recursive: bool,
+    selector: Selector,
+}
+
+impl JsonQuery {
+    /// The paths of the matching values, relative to `value`.
+    pub fn matches(&self, value: &Value) -> Result<Vec<JsonPath>, JsonPathParseError> {
+        let mut current = vec![(vec![], value)];
+
+        for step in self.parse()? {
+            let mut next = vec![];
+
+            for (path, value) in current {
+                if step.recursive {
+                    let mut nodes = vec![];
+                    descendants(path, value, &mut nodes);
+
+                    for (path, value) in nodes {
+                        select(&step.selector, path, value, &mut next);
+                    }
+                } else {
+                    select(&step.selector, path, value, &mut next);
+                }
+            }
+
+            current = next;
+        }
+
+        Ok(current
+            .into_iter()
+            .filter(|(path, _)| !path.is_empty())
+            .map(|(path, _)| JsonPath(path))
+            .collect())
+    }
+
+    fn parse(&self) -> Result<Vec<Step>, JsonPathParseError> {
+        let input = &self.0;
+        let chars: Vec<char> = input.chars().collect();
+        let error = |message: &str, position: usize| JsonPathParseError {
+            message: message.to_owned(),
+            position,
+            input: input.to_owned(),
+        };
+
+        if chars.first() != Some(&'$') {
+            return Err(error("expected $", 0));
+        }
+
+        let mut steps = vec![];
+        let mut ix = 1;
+
+        while ix < chars.len() {
+            let recursive = chars[ix..].starts_with(&['.', '.']);
+
+            let selector = match chars[ix] {
+                '.' => {
+                    ix += if recursive { 2 } else { 1 };
+
+                    if chars.get(ix) == Some(&'[') {
+                        if !recursive {
+                            return Err(error("unexpected [", ix));
+                        }
+                        continue_bracket(&chars, &mut ix, &error)?
+                    } else {
+                        let start = ix;
+                        while ix < chars.len() && !matches!(chars[ix], '.' | '[' | ']') {
+                            ix += 1;
+                        }
+
+                        match chars[start..ix].iter().collect::<String>().as_str() {
+                            "" => return Err(error("expected a key", start)),
+                            "*" => Selector::Wildcard,
+                            key => Selector::Part(JsonPathPart::Key(key.to_owned())),
+                        }
+                    }
+                }
+                '[' => continue_bracket(&chars, &mut ix, &error)?,
+                c => return Err(error(&format!("unexpected {c}"), ix)),
+            };
+
+            steps.push(Step {
+                recursive,
+                selector,
+            });
+        }
+
+        Ok(steps)
+    }
+}
+
+/// `[*]`, `[2]`, `['key']` or `["key"]` at `ix`, moving `ix` past it.
+fn continue_bracket(
+    chars: &[char],
+    ix: &mut usize,
+    error: &dyn Fn(&str, usize) -> JsonPathParseError,
+) -> Result<Selector, JsonPathParseError> {
+    let open = *ix;
+    let close = chars[open..]
+        .iter()
+        .position(|c| *c == ']')
+        .map(|p| open + p)
+        .ok_or_else(|| error("mismatched [", open))?;
+    *ix = close + 1;
+
+    let inner: String = chars[open + 1..close].iter().collect();
+
+    if inner == "*" {
+        return Ok(Selector::Wildcard);
+    }
+
+    for quote in ['\'', '"'] {
+        if inner.len() >= 2 && inner.starts_with(quote) && inner.ends_with(quote) {
+            let key = inner[1..inner.len() - 1].to_owned();
+            return Ok(Selector::Part(JsonPathPart::Key(key)));
+        }
+    }
+
+    inner
+        .parse()
+        .map(|i| Selector::Part(JsonPathPart::Index(i)))
+        .map_err(|_| error("error parsing index", open))
+}
+
+fn children(value: &Value) -> Vec<(JsonPathPart, &Value)> {
+    match value {
+        Value::Object(obj) => obj
+            .iter()
+            .map(|(k, v)| (JsonPathPart::Key(k.to_owned()), v))
+            .collect(),
+        Value::Array(arr) => arr
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (JsonPathPart::Index(i), v))
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn select<'v>(
+    selector: &Selector,
+    path: Vec<JsonPathPart>,
+    value: &'v Value,
+    out: &mut Vec<(Vec<JsonPathPart>, &'v Value)>,
+) {
+    for (part, child) in children(value) {
+        let selected = match selector {
+            Selector::Wildcard => true,
+            Selector::Part(p) => *p == part,
+        };
+
+        if selected {
+            let mut path = path.clone();
+            path.push(part);
+            out.push((path, child));
+        }
+    }
+}
+
+/// The value and all its descendants, depth first.
+fn descendants<'v>(
+    path: Vec<JsonPathPart>,
+    value: &'v Value,
+    out: &mut Vec<(Vec<JsonPathPart>, &'v Value)>,
+) {
+    out.push((path.clone(), value));
+
+    for (part, child) in children(value) {
+        let mut path = path.clone();
+        path.push(part);
+        descendants(path, child, out);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::JsonQuery;
+
+    fn matches(query: &str) -> Vec<String> {
+        let val = json!({
+                "wow": {"hello": "yes"},
+                "another": {"seriously": {"throrougly": 7}, "basic": [1,2,3,{"hello": "_why"},{"_why": "ya"}]},
+                "_ignore": {"haha": {"_yes": 3}}
+        });
+
+        JsonQuery(query.to_owned())
+            .matches(&val)
+            .unwrap()
+            .iter()
+            .map(|p| p.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_wildcards() {
+        assert_eq!(matches("$.wow.hello"), vec!["wow.hello"]);
+        assert_eq!(matches("$['wow'][\"hello\"]"), vec!["wow.hello"]);
+        assert_eq!(matches("$.*"), vec!["_ignore", "another", "wow"]);
+        assert_eq!(
+            matches("$.another.basic[*]"),
+            (0..5)
+                .map(|i| format!("another.basic[{i}]"))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            matches("$.another.basic[3].hello"),
+            vec!["another.basic[3].hello"]
+        );
+        assert!(matches("$.nope.*").is_empty());
+    }
+
+    #[test]
+    fn test_recursive_descent() {
+        assert_eq!(
+            matches("$..hello"),
+            vec!["another.basic[3].hello", "wow.hello"]
+        );
+        assert_eq!(matches("$.another..[1]"), vec!["another.basic[1]"]);
+        assert_eq!(
+            matches("$._ignore..*"),
+            vec!["_ignore.haha", "_ignore.haha._yes"]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for query in ["wow", "$.", "$[1", "$[x]", "$.a]"] {
+            assert!(
+                JsonQuery(query.to_owned()).matches(&json!({})).is_err(),
+                "{query}"
+            );
+        }
+    }
+}
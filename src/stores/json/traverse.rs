@@ -123,13 +123,7 @@ pub fn get_subvalue<'a>(
             }
 
             match cur {
-                Value::Array(arr) => {
-                    if arr.len() < *ix {
-                        return Ok(None);
-                    }
-
-                    Ok(Some(&arr[*ix]))
-                }
+                Value::Array(arr) => Ok(arr.get(*ix)),
                 _ => {
                     return Err(format!(
                         "get_subvalue: Incompatible value for index {next} of {cur}",
@@ -156,3 +150,56 @@ pub fn get_pathvalue<'a>(
 
     Ok(Some(c))
 }
+
+/// Writes the value at the path, creating the containers on the way.
+///
+/// `None` deletes: removes the key from an object, or sets an array item to `null`.
+pub fn set_pathvalue(
+    cur: &mut Value,
+    path: &[JsonPathPart],
+    value: &Option<Value>,
+) -> Result<(), JsonTraverseError> {
+    match value {
+        // Set
+        Some(value) => {
+            let insert_at = get_mut_pathvalue(cur, path, true)?.unwrap();
+
+            *insert_at = value.clone();
+
+            Ok(())
+        }
+
+        // Delete
+        None => {
+            let Some((last, path)) = path.split_last() else {
+                *cur = Value::Null;
+                return Ok(());
+            };
+
+            let delete_from = get_mut_pathvalue(cur, path, false)?;
+
+            match delete_from {
+                None => Ok(()),
+                Some(Value::Null) => Ok(()),
+
+                Some(delete_from) => match (last, delete_from) {
+                    (JsonPathPart::Key(key), Value::Object(obj)) => {
+                        obj.remove(key);
+                        Ok(())
+                    }
+                    (JsonPathPart::Index(ix), Value::Array(arr)) => {
+                        if arr.len() <= *ix {
+                        } else if arr.len() == *ix {
+                            arr.pop();
+                        } else {
+                            arr[*ix] = Value::Null;
+                        }
+
+                        Ok(())
+                    }
+                    (_, value) => Err(format!("Incompatible value at key {last}: {value}").into()),
+                },
+            }
+        }
+    }
+}
@@ -1,60 +1,71 @@
-use std::sync::Arc;
+use std::{collections::HashSet, hash::Hash, marker::PhantomData, sync::Arc};
 
-use futures::{stream, StreamExt, TryStreamExt};
-use tokio::sync::{RwLock, RwLockReadGuard};
+#[cfg(feature = "fs")]
+use futures::{future::LocalBoxFuture, FutureExt};
+use futures::{stream, stream::LocalBoxStream, StreamExt, TryStreamExt};
+use thiserror::Error;
+use tokio::sync::{broadcast, RwLock, RwLockReadGuard};
 
-use serde_json::Value;
+use serde_json::{Map, Value};
 
 use crate::{
     address::{
-        primitive::Existence,
+        primitive::{Existence, Present, WholeStore},
         traits::{
-            AddressableGet, AddressableInsert, AddressableList, AddressableSet, AddressableTree,
-            BranchOrLeaf,
+            AddressableBranch, AddressableCas, AddressableChild, AddressableDelete,
+            AddressableExistsMany, AddressableGet, AddressableInsert, AddressableList,
+            AddressableQuery, AddressableSet, AddressableSwap, AddressableTree, BranchOrLeaf,
         },
         Address, Addressable, SubAddress,
     },
     location::Location,
-    store::{Store, StoreResult},
-    stores::json::paths::*,
+    store::{Capabilities, Store, StoreResult},
     stores::json::traverse::*,
+    stores::json::{paths::*, query::JsonQuery, JsonEntry, ValueKind},
 };
-// todo: stop using anyhow, implement wrapper error
-use anyhow::anyhow;
-
-// #[derive(Debug, Display, Error)]
-type LocatedJsonStoreError = anyhow::Error;
-
-// #[derive(Debug, Error)]
-// pub enum LocatedJsonStoreError {
-//     #[error("StoreError({0})")]
-//     StoreError(dyn std::error::Error),
-//     // #[error("CustomError({0})")]
-//     // CustomError(String),
-
-//     // #[error("SerdeError({0})")]
-//     // SerdeError(
-//     //     #[backtrace]
-//     //     #[source]
-//     //     serde_json::Error,
-//     // ),
-
-//     // #[error("ParseError({0})")]
-//     // ParseError(
-//     //     #[backtrace]
-//     //     #[source]
-//     //     JsonPathParseError,
-//     // ),
-
-//     // #[error("TraverseError({0})")]
-//     // TraverseError(
-//     //     #[backtrace]
-//     //     #[source]
-//     //     JsonTraverseError,
-//     // ),
-// }
-
-/// Turn any store of Strings into JSON store
+
+#[cfg(feature = "fs")]
+use crate::address::traits::{AddressableReadStream, AddressableWriteStream};
+#[cfg(feature = "file-locks")]
+use crate::stores::fs::{FileSystemStore, RelativePath};
+#[derive(Debug, Error)]
+pub enum LocatedJsonStoreError {
+    #[error("StoreError({0})")]
+    StoreError(Box<dyn std::error::Error + Send + Sync>),
+
+    #[error("CustomError({0})")]
+    CustomError(String),
+
+    #[error("SerdeError({0})")]
+    SerdeError(#[from] serde_json::Error),
+
+    #[error("ParseError({0})")]
+    ParseError(#[from] JsonPathParseError),
+
+    #[error("TraverseError({0})")]
+    TraverseError(#[from] JsonTraverseError),
+
+    #[error("MissingValue({0})")]
+    MissingValue(#[from] MissingValue),
+}
+
+/// There's no value at the path, when one is required, see
+/// [`Location::path_typed`](crate::location::Location::path_typed).
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+#[error("no value at {0}")]
+pub struct MissingValue(pub JsonPath);
+
+impl LocatedJsonStoreError {
+    fn store<E: std::error::Error + Send + Sync + 'static>(e: E) -> Self {
+        LocatedJsonStoreError::StoreError(Box::new(e))
+    }
+}
+
+/// Turn any store of Strings (or bytes, see [`JsonDocument`]) into JSON store
+///
+/// The root (`store.root()`) is the empty path, i.e. the whole JSON document.
+/// An empty (or missing) document is the same as a `null` one, and has no root value:
+/// the root doesn't exist. Deleting the root, or writing `null` there, empties the document.
 ///
 #[cfg_attr(not(all(feature = "json", feature = "fs")), doc = "```ignore")]
 #[cfg_attr(all(feature = "json", feature = "fs"), doc = "```")]
@@ -93,46 +104,281 @@ type LocatedJsonStoreError = anyhow::Error;
 /// # }).unwrap()
 /// ```
 #[derive(Clone)]
-pub struct LocatedJsonStore<A: Address, S: Addressable<A>> {
-    pub pretty: bool,
+pub struct LocatedJsonStore<A: Address, S: Addressable<A>, D: JsonDocument = String> {
+    pub format: JsonFormat,
+
+    document: PhantomData<fn() -> D>,
 
     location: Arc<RwLock<Location<A, S>>>,
+
+    changes: broadcast::Sender<(JsonPath, ChangeKind)>,
+
+    #[cfg(feature = "file-locks")]
+    lock_file: Option<Arc<std::path::PathBuf>>,
+
+    #[cfg(feature = "fs")]
+    streams: Option<DocumentStreams<A, S>>,
 }
 
-impl<A: Address, S: Addressable<A>> LocatedJsonStore<A, S>
-where
-    S::Error: std::error::Error,
-{
-    /// Wrap a store of Strings into a JSON store
-    pub fn new(location: Location<A, S>) -> Self {
-        LocatedJsonStore {
-            location: Arc::new(RwLock::new(location)),
+/// How many changes a slow subscriber can fall behind before it misses some,
+/// see [`LocatedJsonStore::subscribe`].
+const CHANGES_CAPACITY: usize = 256;
+
+/// What a write did at a path, see [`LocatedJsonStore::subscribe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Set,
+    Deleted,
+}
+
+impl ChangeKind {
+    fn of<V>(value: &Option<V>) -> Self {
+        match value {
+            Some(_) => ChangeKind::Set,
+            None => ChangeKind::Deleted,
+        }
+    }
+}
+
+/// How [`LocatedJsonStore`] serializes the document on every write.
+///
+/// The default is compact JSON, with keys in the order `serde_json` keeps them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JsonFormat {
+    pub pretty: bool,
+    /// Sort object keys, recursively.
+    pub sort_keys: bool,
+    /// Number of spaces per level, only used with `pretty`.
+    pub indent: usize,
+}
+
+impl Default for JsonFormat {
+    fn default() -> Self {
+        JsonFormat {
             pretty: false,
+            sort_keys: false,
+            indent: 2,
+        }
+    }
+}
+
+impl JsonFormat {
+    /// `null` is the empty document.
+    fn serialize_document<D: JsonDocument>(&self, value: &Value) -> Result<D, serde_json::Error> {
+        let mut out = vec![];
+        if !value.is_null() {
+            self.write(&mut out, value)?;
+        }
+
+        Ok(D::from_json(out))
+    }
+
+    fn write<W: std::io::Write>(&self, writer: W, value: &Value) -> Result<(), serde_json::Error> {
+        let sorted;
+        let value = if self.sort_keys {
+            sorted = sort_keys(value);
+            &sorted
+        } else {
+            value
+        };
+
+        if !self.pretty {
+            return serde_json::to_writer(writer, value);
+        }
+
+        let indent = " ".repeat(self.indent);
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        let mut ser = serde_json::Serializer::with_formatter(writer, formatter);
+        serde::Serialize::serialize(value, &mut ser)
+    }
+}
+
+/// What [`LocatedJsonStore`] keeps the document as in the underlying store: a `String`
+/// (the default), or `Vec<u8>` for stores of bytes (see [`LocatedJsonStore::new_bytes`]).
+pub trait JsonDocument: Clone + 'static {
+    /// An empty or whitespace-only document is `null`.
+    fn parse(&self) -> Result<Value, serde_json::Error>;
+
+    /// From the serialized JSON, which is valid UTF-8.
+    fn from_json(json: Vec<u8>) -> Self;
+}
+
+impl JsonDocument for String {
+    fn parse(&self) -> Result<Value, serde_json::Error> {
+        if self.trim().is_empty() {
+            return Ok(Value::Null);
+        }
+
+        serde_json::from_str(self)
+    }
+
+    fn from_json(json: Vec<u8>) -> Self {
+        // serde_json only ever writes valid UTF-8
+        String::from_utf8(json).expect("serde_json produced invalid UTF-8")
+    }
+}
+
+impl JsonDocument for Vec<u8> {
+    fn parse(&self) -> Result<Value, serde_json::Error> {
+        if self.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Value::Null);
+        }
+
+        serde_json::from_slice(self)
+    }
+
+    fn from_json(json: Vec<u8>) -> Self {
+        json
+    }
+}
+
+/// A missing document is `null`, like an empty one.
+fn parse_document<D: JsonDocument>(doc: Option<D>) -> Result<Value, serde_json::Error> {
+    doc.map_or(Ok(Value::Null), |doc| doc.parse())
+}
+
+/// The value at `path` in a parsed document. The root of an empty document (`null`) doesn't exist.
+fn document_pathvalue<'v>(
+    doc: &'v Value,
+    path: &[JsonPathPart],
+) -> Result<Option<&'v Value>, JsonTraverseError> {
+    if path.is_empty() && doc.is_null() {
+        return Ok(None);
+    }
+
+    get_pathvalue(doc, path)
+}
+
+/// Like `parse_document`, but reads from `reader` without collecting it first.
+#[cfg(feature = "fs")]
+fn parse_document_from_reader<R: std::io::BufRead>(
+    mut reader: R,
+) -> Result<Value, serde_json::Error> {
+    loop {
+        let buf = reader.fill_buf().map_err(serde_json::Error::io)?;
+        if buf.is_empty() {
+            return Ok(Value::Null);
+        }
+
+        let whitespace = buf.iter().take_while(|b| b.is_ascii_whitespace()).count();
+        if whitespace < buf.len() {
+            reader.consume(whitespace);
+            return serde_json::from_reader(reader);
+        }
+        reader.consume(whitespace);
+    }
+}
+
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (k, v) in patch {
+                match target.get_mut(&k) {
+                    Some(t) => merge(t, v),
+                    None => {
+                        target.insert(k, v);
+                    }
+                }
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+fn sort_keys(value: &Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut entries = obj.iter().collect::<Vec<_>>();
+            entries.sort_by_key(|(k, _)| *k);
+
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k.clone(), sort_keys(v)))
+                    .collect(),
+            )
         }
+        Value::Array(arr) => Value::Array(arr.iter().map(sort_keys).collect()),
+        v => v.clone(),
+    }
+}
+
+impl<A: Address, S: Addressable<A>> LocatedJsonStore<A, S, String> {
+    /// Wrap a store of Strings into a JSON store. For large files, see
+    /// [`new_streaming`](LocatedJsonStore::new_streaming).
+    pub fn new(location: Location<A, S>) -> Self {
+        Self::new_with_format(location, JsonFormat::default())
     }
 
     /// Wrap a store of Strings into a JSON store,
     /// that formats JSON with pretty print
     pub fn new_pretty(location: Location<A, S>) -> Self {
+        Self::new_with_format(
+            location,
+            JsonFormat {
+                pretty: true,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Wrap a store of Strings into a JSON store, with a custom output format
+    pub fn new_with_format(location: Location<A, S>, format: JsonFormat) -> Self {
+        Self::new_document(location, format)
+    }
+}
+
+impl<A: Address, S: Addressable<A>> LocatedJsonStore<A, S, Vec<u8>> {
+    /// Wrap a store of bytes into a JSON store. The bytes are parsed as they are,
+    /// without converting them to a `String` first.
+    pub fn new_bytes(location: Location<A, S>) -> Self {
+        Self::new_bytes_with_format(location, JsonFormat::default())
+    }
+
+    /// Wrap a store of bytes into a JSON store, with a custom output format
+    pub fn new_bytes_with_format(location: Location<A, S>, format: JsonFormat) -> Self {
+        Self::new_document(location, format)
+    }
+}
+
+impl<A: Address, D: JsonDocument, S: Addressable<A>> LocatedJsonStore<A, S, D> {
+    fn new_document(location: Location<A, S>, format: JsonFormat) -> Self {
         LocatedJsonStore {
             location: Arc::new(RwLock::new(location)),
-            pretty: true,
+            format,
+            document: PhantomData,
+            changes: broadcast::channel(CHANGES_CAPACITY).0,
+            #[cfg(feature = "file-locks")]
+            lock_file: None,
+            #[cfg(feature = "fs")]
+            streams: None,
         }
     }
 
+    /// Get the changed path and the kind of the change after every successful write,
+    /// e.g. to refresh only the affected part of a UI. Clones of the store share the changes.
+    ///
+    /// A write to a path also changes everything under it, but only the path itself is sent.
+    /// Inserts send the paths of the new items. A subscriber that falls behind by more than
+    /// 256 changes gets [`RecvError::Lagged`](broadcast::error::RecvError::Lagged).
+    pub fn subscribe(&self) -> broadcast::Receiver<(JsonPath, ChangeKind)> {
+        self.changes.subscribe()
+    }
+
+    fn notify(&self, path: &JsonPath, kind: ChangeKind) {
+        // no subscribers is fine
+        let _ = self.changes.send((path.clone(), kind));
+    }
+
     async fn lock_read_value(&self) -> StoreResult<(RwLockReadGuard<()>, Value), Self>
     where
-        S: AddressableGet<String, A>,
+        S: AddressableGet<D, A>,
     {
         let loc = self.location.read().await;
+        #[cfg(feature = "file-locks")]
+        let _file_lock = self.lock_file(false).await?;
 
-        let value = loc
-            .get::<String>()
-            .await?
-            // .map_err(LocatedJsonStoreError::StoreError)
-            .map(|s| serde_json::from_str(&s))
-            .transpose()?
-            .unwrap_or(Value::Null);
+        let value = self.read_document(&loc).await?;
 
         let lock = RwLockReadGuard::map(loc, |_| &());
 
@@ -141,245 +387,1935 @@ where
 
     async fn change_value<R, F: FnOnce(&mut Value) -> R>(&self, mutator: F) -> StoreResult<R, Self>
     where
-        S: AddressableGet<String, A> + AddressableSet<String, A>,
+        S: AddressableGet<D, A> + AddressableSet<D, A>,
+    {
+        self.change_value_if(|value| (true, mutator(value))).await
+    }
+
+    /// Like `change_value`, but only writes if the mutator returns `true`.
+    async fn change_value_if<R, F: FnOnce(&mut Value) -> (bool, R)>(
+        &self,
+        mutator: F,
+    ) -> StoreResult<R, Self>
+    where
+        S: AddressableGet<D, A> + AddressableSet<D, A>,
     {
         let loc = self.location.write().await;
+        #[cfg(feature = "file-locks")]
+        let _file_lock = self.lock_file(true).await?;
 
-        let str = loc.get::<String>().await?;
+        let mut value = self.read_document(&loc).await?;
 
-        // .map_err(LocatedJsonStoreError::StoreError)
-        let mut value = str
-            .map(|s| serde_json::from_str(&s))
-            .transpose()?
-            .unwrap_or(Value::Null);
+        let (changed, result) = mutator(&mut value);
+        if !changed {
+            return Ok(result);
+        }
 
-        let result = mutator(&mut value);
+        self.write_document(&loc, value).await?;
 
-        let stored = if self.pretty {
-            serde_json::to_string_pretty(&value)
-        } else {
-            serde_json::to_string(&value)
-        }?;
+        Ok(result)
+    }
 
-        loc.set(&Some(stored))
-            .await
-            // .map_err(LocatedJsonStoreError::StoreError)
-            ?;
+    async fn read_document(&self, loc: &Location<A, S>) -> StoreResult<Value, Self>
+    where
+        S: AddressableGet<D, A>,
+    {
+        #[cfg(feature = "fs")]
+        if let Some(streams) = &self.streams {
+            return (streams.read)(loc).await;
+        }
 
-        Ok(result)
+        Ok(parse_document(
+            loc.get::<D>().await.map_err(LocatedJsonStoreError::store)?,
+        )?)
     }
-}
 
-impl<A: Address, S: Addressable<A>> Store for LocatedJsonStore<A, S> {
-    type Error = LocatedJsonStoreError;
-    type RootAddress = JsonPath;
-}
+    async fn write_document(&self, loc: &Location<A, S>, value: Value) -> StoreResult<(), Self>
+    where
+        S: AddressableSet<D, A>,
+    {
+        #[cfg(feature = "fs")]
+        if let Some(streams) = &self.streams {
+            return (streams.write)(loc, value, self.format).await;
+        }
 
-impl<A: Address, S: Addressable<A>> Addressable<JsonPath> for LocatedJsonStore<A, S> {
-    type DefaultValue = Value;
+        let stored = self.format.serialize_document(&value)?;
+
+        loc.set(&Some(stored))
+            .await
+            .map_err(LocatedJsonStoreError::store)
+    }
 }
 
-impl<A: Address, S: AddressableGet<String, A>> AddressableGet<Value, JsonPath>
-    for LocatedJsonStore<A, S>
-where
-    <S as Store>::Error: std::error::Error,
-{
-    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Value>, Self> {
-        let (_, value) = self.lock_read_value().await?;
+/// Held while the document is read or changed, see [`LocatedJsonStore::new_with_file_lock`].
+#[cfg(feature = "file-locks")]
+struct FileLock(std::fs::File);
 
-        return Ok(get_pathvalue(&value, &addr.0[..])?
-            // .map_err(LocatedJsonStoreError::TraverseError)
-            .cloned());
+#[cfg(feature = "file-locks")]
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs2::FileExt::unlock(&self.0);
     }
 }
 
-impl<A: Address, S: AddressableGet<String, A> + AddressableSet<String, A>>
-    AddressableSet<Value, JsonPath> for LocatedJsonStore<A, S>
-where
-    <S as Store>::Error: std::error::Error,
-{
-    async fn set_addr(&self, addr: &JsonPath, value: &Option<Value>) -> StoreResult<(), Self> {
-        self.change_value(|cur| {
-            let addr = &addr.0;
-
-            match value {
-                // Set
-                Some(value) => {
-                    let insert_at = get_mut_pathvalue(cur, &addr[..], true)?.unwrap();
+/// Cross-process locking, for documents in files shared by several processes.
+#[cfg(feature = "file-locks")]
+impl LocatedJsonStore<RelativePath, FileSystemStore> {
+    /// Like [`new_with_format`](LocatedJsonStore::new_with_format), but the reads and
+    /// read-modify-writes also take an advisory lock on `<file>.lock` next to the file,
+    /// so other processes (or other stores over the same file) doing the same don't lose updates.
+    ///
+    /// Caveats:
+    /// - The lock is advisory: only the stores that lock the same file wait for each other.
+    ///   Anything else writing the file directly isn't stopped.
+    /// - It's `flock` on Unix and `LockFileEx` on Windows. Network file systems (e.g. NFS)
+    ///   may not support it, or not across machines.
+    /// - The `.lock` file is created when needed and never removed.
+    /// - Taking the lock blocks a thread of tokio's blocking pool while waiting.
+    ///
+    /// The document is streamed, like with [`new_streaming`](LocatedJsonStore::new_streaming).
+    pub fn new_with_file_lock(
+        location: Location<RelativePath, FileSystemStore>,
+        format: JsonFormat,
+    ) -> Self {
+        let mut lock_file = location
+            .store
+            .get_complete_path(location.address.clone())
+            .into_os_string();
+        lock_file.push(".lock");
 
-                    *insert_at = value.clone();
+        LocatedJsonStore {
+            lock_file: Some(Arc::new(lock_file.into())),
+            ..Self::new_streaming(location, format)
+        }
+    }
+}
 
-                    Ok(())
-                }
+#[cfg(feature = "file-locks")]
+impl<A: Address, D: JsonDocument, S: Addressable<A>> LocatedJsonStore<A, S, D> {
+    /// Exclusive for writing, shared for reading. `None` if the store doesn't lock files.
+    async fn lock_file(&self, exclusive: bool) -> StoreResult<Option<FileLock>, Self> {
+        let Some(path) = self.lock_file.clone() else {
+            return Ok(None);
+        };
+
+        let lock = tokio::task::spawn_blocking(move || {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
 
-                // Delete
-                None => {
-                    let Some((last, path)) = addr.split_last() else {
-                    *cur = Value::Null;
-                    return Ok(());
-                };
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&*path)?;
 
-                    let delete_from = get_mut_pathvalue(cur, path, false)?;
-
-                    match delete_from {
-                        None => Ok(()),
-                        Some(Value::Null) => Ok(()),
-
-                        Some(delete_from) => match (last, delete_from) {
-                            (JsonPathPart::Key(key), Value::Object(obj)) => {
-                                obj.remove(key);
-                                Ok(())
-                            }
-                            (JsonPathPart::Index(ix), Value::Array(arr)) => {
-                                if arr.len() <= *ix {
-                                } else if arr.len() == *ix {
-                                    arr.pop();
-                                } else {
-                                    arr[*ix] = Value::Null;
-                                }
-
-                                Ok(())
-                            }
-                            (_, value) => {
-                                Err(anyhow!("Incompatible value at key {last}: {value}",))
-                            }
-                        },
-                    }
-                }
+            if exclusive {
+                fs2::FileExt::lock_exclusive(&file)?;
+            } else {
+                fs2::FileExt::lock_shared(&file)?;
             }
+
+            Ok::<_, std::io::Error>(FileLock(file))
         })
-        .await?
+        .await
+        .map_err(LocatedJsonStoreError::store)?
+        .map_err(LocatedJsonStoreError::store)?;
+
+        Ok(Some(lock))
     }
 }
 
-impl<A: Address, S: AddressableGet<String, A>> AddressableGet<Existence, JsonPath>
-    for LocatedJsonStore<A, S>
+/// How a store made with [`new_streaming`](LocatedJsonStore::new_streaming) reads and writes
+/// the document, instead of getting and setting it whole.
+#[cfg(feature = "fs")]
+#[derive(Clone)]
+struct DocumentStreams<A: Address, S: Addressable<A>> {
+    read: ReadDocument<A, S>,
+    write: WriteDocument<A, S>,
+}
+
+#[cfg(feature = "fs")]
+type ReadDocument<A, S> =
+    for<'l> fn(
+        &'l Location<A, S>,
+    ) -> LocalBoxFuture<'l, StoreResult<Value, LocatedJsonStore<A, S>>>;
+
+#[cfg(feature = "fs")]
+type WriteDocument<A, S> =
+    for<'l> fn(
+        &'l Location<A, S>,
+        Value,
+        JsonFormat,
+    ) -> LocalBoxFuture<'l, StoreResult<(), LocatedJsonStore<A, S>>>;
+
+#[cfg(feature = "fs")]
+impl<A: Address, S: AddressableReadStream<A> + AddressableWriteStream<A>>
+    LocatedJsonStore<A, S, String>
 where
-    <S as Store>::Error: std::error::Error,
+    S::Reader: Send + 'static,
+    S::Writer: Send + 'static,
 {
-    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Existence>, Self> {
-        let v: Option<Value> =
-            <LocatedJsonStore<A, S> as AddressableGet<Value, JsonPath>>::addr_get(self, addr)
-                .await?;
-
-        Ok(v.map(|_| Existence))
+    /// Wrap a store that can stream its values, e.g. a file in a
+    /// [`FileSystemStore`](crate::stores::fs::FileSystemStore), into a JSON store, for large documents.
+    ///
+    /// The document is still parsed into a whole `Value`, but the text of it is never held in memory:
+    /// it's parsed while it's read, and serialized while it's written.
+    /// Unless the store replaces values only once they are completely written, as
+    /// [`FileSystemStore`](crate::stores::fs::FileSystemStore) does, a failed write can leave the
+    /// document truncated.
+    pub fn new_streaming(location: Location<A, S>, format: JsonFormat) -> Self {
+        LocatedJsonStore {
+            streams: Some(DocumentStreams {
+                read: |loc| read_streamed(loc).boxed_local(),
+                write: |loc, value, format| write_streamed(loc, value, format).boxed_local(),
+            }),
+            ..Self::new_with_format(location, format)
+        }
     }
 }
 
-impl<'a, A: Address, S: 'a + AddressableGet<String, A>> AddressableList<'a, JsonPath>
-    for LocatedJsonStore<A, S>
+/// Parses the document while it's read. A missing document is `null`.
+#[cfg(feature = "fs")]
+async fn read_streamed<A: Address, S: AddressableReadStream<A> + AddressableWriteStream<A>>(
+    loc: &Location<A, S>,
+) -> StoreResult<Value, LocatedJsonStore<A, S>>
 where
-    S::Error: std::error::Error,
+    S::Reader: Send + 'static,
 {
-    type AddedAddress = JsonPathPart;
-
-    type ItemAddress = JsonPath;
-
-    fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
-        let this = self.clone();
-        let addr = addr.clone();
+    let Some(reader) = loc
+        .store
+        .read_stream(&loc.address)
+        .await
+        .map_err(LocatedJsonStoreError::store)?
+    else {
+        return Ok(Value::Null);
+    };
+
+    let reader = std::io::BufReader::new(tokio_util::io::SyncIoBridge::new(reader));
+
+    Ok(
+        tokio::task::spawn_blocking(move || parse_document_from_reader(reader))
+            .await
+            .map_err(LocatedJsonStoreError::store)??,
+    )
+}
 
-        stream::once(async move {
-            let value = this.lock_read_value().await?.1;
+/// Serializes the document while it's written. `null` is written as an empty document.
+#[cfg(feature = "fs")]
+async fn write_streamed<A: Address, S: AddressableReadStream<A> + AddressableWriteStream<A>>(
+    loc: &Location<A, S>,
+    value: Value,
+    format: JsonFormat,
+) -> StoreResult<(), LocatedJsonStore<A, S>>
+where
+    S::Writer: Send + 'static,
+{
+    let writer = loc
+        .store
+        .write_stream(&loc.address)
+        .await
+        .map_err(LocatedJsonStoreError::store)?;
+
+    tokio::task::spawn_blocking(move || {
+        let mut writer = std::io::BufWriter::new(tokio_util::io::SyncIoBridge::new(writer));
+        if !value.is_null() {
+            format.write(&mut writer, &value)?;
+        }
 
-            let val: StoreResult<_, Self> =
-                try { get_pathvalue(&value, &addr.0[..])?.ok_or(anyhow!("Path doesn't exist"))? };
+        let mut bridge = writer.into_inner().map_err(|e| e.into_error())?;
+        std::io::Write::flush(&mut bridge)?;
+        bridge.shutdown()
+    })
+    .await
+    .map_err(LocatedJsonStoreError::store)?
+    .map_err(LocatedJsonStoreError::store)
+}
 
-            let vec = match val {
-                Ok(Value::Array(arr)) => (0..arr.len())
-                    .map(JsonPathPart::Index)
-                    .map(|i| Ok((i.clone(), addr.clone().sub(i))))
-                    .collect(),
-                Ok(Value::Object(obj)) => obj
-                    .keys()
-                    .map(|k| JsonPathPart::Key(k.to_owned()))
-                    .map(|i| Ok((i.clone(), addr.clone().sub(i))))
-                    .collect(),
-                Err(e) => vec![Err(e)],
-                _ => vec![Err(anyhow!("Can't list: {val:?}"))],
-            };
+/// Mutations collected by [`LocatedJsonStore::batch`].
+#[derive(Debug, Default, Clone)]
+pub struct JsonBatch {
+    writes: Vec<(JsonPath, Option<Value>)>,
+}
 
-            Ok::<_, Self::Error>(stream::iter(vec.into_iter()))
-        })
-        .try_flatten()
-        .boxed_local()
+impl JsonBatch {
+    /// Same as `set` at this path: `None` deletes the value.
+    pub fn write(&mut self, path: JsonPath, value: Option<Value>) -> &mut Self {
+        self.writes.push((path, value));
+        self
     }
 }
 
-impl<'a, A: Address, S: 'a + AddressableGet<String, A>> AddressableTree<'a, JsonPath, JsonPath>
-    for LocatedJsonStore<A, S>
-where
-    S::Error: std::error::Error,
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A> + AddressableSet<D, A>>
+    LocatedJsonStore<A, S, D>
 {
-    async fn branch_or_leaf(
-        &self,
-        addr: JsonPath,
-    ) -> StoreResult<BranchOrLeaf<JsonPath, JsonPath>, Self> {
-        let value = self.lock_read_value().await?.1;
-        let val = get_pathvalue(&value, &addr.0[..])?.ok_or(anyhow!("Path doesn't exist"))?;
+    /// Apply several writes at once: the underlying string is read, parsed,
+    /// serialized and written only once, under a single lock.
+    ///
+    /// If any of the writes fails, none of them are applied.
+    pub async fn batch<F: FnOnce(&mut JsonBatch)>(&self, f: F) -> StoreResult<(), Self> {
+        let mut batch = JsonBatch::default();
+        f(&mut batch);
+
+        if batch.writes.is_empty() {
+            return Ok(());
+        }
 
-        Ok(match val {
-            Value::Array(_) => BranchOrLeaf::Branch(addr),
-            Value::Object(_) => BranchOrLeaf::Branch(addr),
+        self.change_value(|cur| {
+            let mut new = cur.clone();
 
-            _ => BranchOrLeaf::Leaf(addr),
+            for (path, value) in &batch.writes {
+                set_pathvalue(&mut new, &path.0[..], value)?;
+            }
+
+            *cur = new;
+            Ok::<_, JsonTraverseError>(())
         })
+        .await??;
+
+        for (path, value) in &batch.writes {
+            self.notify(path, ChangeKind::of(value));
+        }
+
+        Ok(())
     }
-}
 
-impl<'a, A: Address, S: 'a + AddressableGet<String, A> + AddressableSet<String, A>>
-    AddressableInsert<'a, Value, JsonPath> for LocatedJsonStore<A, S>
-where
-    S::Error: std::error::Error,
-{
-    fn insert(&self, addr: &JsonPath, items: Vec<Value>) -> Self::ListOfAddressesStream {
-        let addr = addr.clone();
-        let this = self.clone();
+    /// Write a [`JsonEntry`]: `Absent` deletes the key, `Null` stores `null`.
+    ///
+    /// It's not an `AddressableSet` impl, so that `set(&None)` on a JSON location stays
+    /// unambiguous.
+    pub async fn set_entry(&self, addr: &JsonPath, entry: JsonEntry) -> StoreResult<(), Self> {
+        AddressableSet::<Value, _>::set_addr(self, addr, &entry.into()).await
+    }
 
-        stream::once(async move {
-            let addr = addr.clone();
-            let path = addr.0.clone();
-            let paths = this
-                .change_value(move |cur| {
-                    let insert_at = get_mut_pathvalue(cur, &path[..], true)?.unwrap();
+    /// Write an object, the counterpart of reading a `Map<String, Value>`.
+    /// Like [`set_entry`](LocatedJsonStore::set_entry), it's not an `AddressableSet` impl.
+    pub async fn set_object(
+        &self,
+        addr: &JsonPath,
+        object: Map<String, Value>,
+    ) -> StoreResult<(), Self> {
+        AddressableSet::<Value, _>::set_addr(self, addr, &Some(Value::Object(object))).await
+    }
 
-                    if insert_at.is_null() {
-                        *insert_at = Value::Array(vec![]);
-                    }
+    /// Replace the value at `addr` with `value`, under a single lock, so nobody reads
+    /// a half-replaced subtree. The siblings of `addr` are kept, its old children are not.
+    pub async fn replace_subtree(&self, addr: &JsonPath, value: Value) -> StoreResult<(), Self> {
+        self.replace_subtree_checked(addr, value, |_, _| Ok(()))
+            .await
+    }
 
-                    let arr = match insert_at {
-                        Value::Array(at) => at,
-                        _ => {
-                            return Err::<_, Self::Error>(anyhow!(
-                                "Can't insert into non-array value"
-                            ))
-                        }
-                    };
+    /// Like [`replace_subtree`](LocatedJsonStore::replace_subtree), but first calls `validate`
+    /// with the current value (if any) and the new one, e.g. to check the shape of a config.
+    /// If it returns an error, nothing is written and the error is returned as `CustomError`.
+    pub async fn replace_subtree_checked<
+        F: FnOnce(Option<&Value>, &Value) -> Result<(), String>,
+    >(
+        &self,
+        addr: &JsonPath,
+        value: Value,
+        validate: F,
+    ) -> StoreResult<(), Self> {
+        self.change_value_if(|cur| {
+            let replace = || -> StoreResult<(), Self> {
+                validate(document_pathvalue(cur, &addr.0[..])?, &value)
+                    .map_err(LocatedJsonStoreError::CustomError)?;
+                set_pathvalue(cur, &addr.0[..], &Some(value))?;
+                Ok(())
+            };
+            let result = replace();
 
-                    let ixes = arr.len()..arr.len() + items.len();
+            (result.is_ok(), result)
+        })
+        .await??;
 
-                    arr.extend(items);
+        self.notify(addr, ChangeKind::Set);
 
-                    Ok(ixes
-                        .map(JsonPathPart::Index)
-                        .map(move |i| (i.clone(), addr.clone().sub(i))))
-                })
-                .await??;
+        Ok(())
+    }
+
+    /// Deep-merge `patch` into the value at `addr`, under a single lock: objects are merged key
+    /// by key, anything else (including arrays and `null`) replaces what's there.
+    /// Unlike [`replace_subtree`](LocatedJsonStore::replace_subtree), keys missing from `patch` are kept.
+    pub async fn merge_subtree(&self, addr: &JsonPath, patch: Value) -> StoreResult<(), Self> {
+        self.change_value(|cur| {
+            let at = get_mut_pathvalue(cur, &addr.0[..], true)?.unwrap();
+            merge(at, patch);
 
-            Ok::<_, Self::Error>(stream::iter(paths.map(Ok)))
+            Ok::<_, JsonTraverseError>(())
         })
-        .try_flatten()
-        .boxed_local()
-    }
-}
+        .await??;
+
+        self.notify(addr, ChangeKind::Set);
+
+        Ok(())
+    }
+
+    /// Like `insert`, but skips the items whose key (by `key`) is already in the array,
+    /// e.g. for idempotent seeding. Only the first of the items with the same key is inserted.
+    ///
+    /// The document is written only if something was inserted.
+    pub async fn insert_unique<K: Eq + Hash, F: Fn(&Value) -> K>(
+        &self,
+        addr: &JsonPath,
+        items: Vec<Value>,
+        key: F,
+    ) -> StoreResult<InsertUniqueResult, Self> {
+        let path = addr.0.clone();
+
+        let (inserted, skipped) = self
+            .change_value_if(move |cur| {
+                let insert_at = match get_mut_pathvalue(cur, &path[..], true) {
+                    Ok(at) => at.unwrap(),
+                    Err(e) => return (false, Err(e.into())),
+                };
+
+                if insert_at.is_null() {
+                    *insert_at = Value::Array(vec![]);
+                }
+
+                let Value::Array(arr) = insert_at else {
+                    return (
+                        false,
+                        Err(LocatedJsonStoreError::CustomError(
+                            "Can't insert into non-array value".to_owned(),
+                        )),
+                    );
+                };
+
+                let mut keys = arr.iter().map(&key).collect::<HashSet<_>>();
+                let mut inserted = vec![];
+                let mut skipped = vec![];
+
+                for item in items {
+                    if keys.insert(key(&item)) {
+                        inserted.push(arr.len());
+                        arr.push(item);
+                    } else {
+                        skipped.push(item);
+                    }
+                }
+
+                (!inserted.is_empty(), Ok((inserted, skipped)))
+            })
+            .await??;
+
+        let inserted: Vec<JsonPath> = inserted
+            .into_iter()
+            .map(|i| addr.clone().sub(JsonPathPart::Index(i)))
+            .collect();
+
+        for path in &inserted {
+            self.notify(path, ChangeKind::Set);
+        }
+
+        Ok(InsertUniqueResult { inserted, skipped })
+    }
+
+    /// Move the item of the array at `addr` from index `from` to index `to`, under a single lock,
+    /// shifting the items in between. Fails if either index is out of range.
+    /// Moving an item to where it is writes nothing and notifies nobody.
+    pub async fn reorder(&self, addr: &JsonPath, from: usize, to: usize) -> StoreResult<(), Self> {
+        let moved = self
+            .change_value_if(|cur| {
+                let arr = match get_mut_pathvalue(cur, &addr.0[..], false) {
+                    Ok(Some(Value::Array(arr))) => arr,
+                    Ok(_) => {
+                        return (
+                            false,
+                            Err(LocatedJsonStoreError::CustomError(
+                                "Can't reorder non-array value".to_owned(),
+                            )),
+                        )
+                    }
+                    Err(e) => return (false, Err(e.into())),
+                };
+
+                if from >= arr.len() || to >= arr.len() {
+                    return (
+                        false,
+                        Err(LocatedJsonStoreError::CustomError(format!(
+                            "Can't move {from} to {to} in an array of {}",
+                            arr.len()
+                        ))),
+                    );
+                }
+
+                if from == to {
+                    return (false, Ok(false));
+                }
+
+                let item = arr.remove(from);
+                arr.insert(to, item);
+
+                (true, Ok(true))
+            })
+            .await??;
+
+        if moved {
+            self.notify(addr, ChangeKind::Set);
+        }
+
+        Ok(())
+    }
+
+    /// Like `insert`, but streams the inserted values with their addresses,
+    /// so they don't have to be read again.
+    pub fn insert_with_values<'a>(
+        &self,
+        addr: &JsonPath,
+        items: Vec<Value>,
+    ) -> LocalBoxStream<'a, StoreResult<(JsonPath, Value), Self>>
+    where
+        Self: 'a,
+    {
+        let values = items.clone();
+
+        self.insert(addr, items)
+            .zip(stream::iter(values))
+            .map(|(inserted, value)| inserted.map(|(_, path)| (path, value)))
+            .boxed_local()
+    }
+}
+
+/// What [`LocatedJsonStore::insert_unique`] did with the items.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InsertUniqueResult {
+    /// The addresses of the inserted items, in order.
+    pub inserted: Vec<JsonPath>,
+    /// The items that weren't inserted, because their key was already there.
+    pub skipped: Vec<Value>,
+}
+
+impl<A: Address, D: JsonDocument, S: Addressable<A>> Store for LocatedJsonStore<A, S, D> {
+    type Error = LocatedJsonStoreError;
+    type RootAddress = JsonPath;
+
+    fn capabilities(&self) -> Capabilities {
+        Capabilities {
+            can_write: true,
+            can_list: true,
+            can_delete: true,
+            ..Default::default()
+        }
+    }
+
+    /// Flushes the underlying store.
+    async fn flush(&self) -> StoreResult<(), Self> {
+        let loc = self.location.read().await;
+
+        loc.store
+            .flush()
+            .await
+            .map_err(LocatedJsonStoreError::store)
+    }
+}
+
+impl<A: Address, D: JsonDocument, S: Addressable<A>> Addressable<JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    type DefaultValue = Value;
+}
+
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>> AddressableGet<Value, JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Value>, Self> {
+        let (_, value) = self.lock_read_value().await?;
+
+        return Ok(document_pathvalue(&value, &addr.0[..])?.cloned());
+    }
+}
+
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A> + AddressableSet<D, A>>
+    AddressableSet<Value, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn set_addr(&self, addr: &JsonPath, value: &Option<Value>) -> StoreResult<(), Self> {
+        self.change_value(|cur| set_pathvalue(cur, &addr.0[..], value))
+            .await??;
+
+        self.notify(addr, ChangeKind::of(value));
+
+        Ok(())
+    }
+}
+
+/// Atomic: the value is compared and written under the store's lock.
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A> + AddressableSet<D, A>>
+    AddressableCas<Value, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn compare_and_swap(
+        &self,
+        addr: &JsonPath,
+        expected: &Option<Value>,
+        new: &Option<Value>,
+    ) -> StoreResult<bool, Self> {
+        let written = self
+            .change_value_if(|cur| match document_pathvalue(cur, &addr.0[..]) {
+                Ok(current) if current == expected.as_ref() => {
+                    (true, set_pathvalue(cur, &addr.0[..], new).map(|_| true))
+                }
+                Ok(_) => (false, Ok(false)),
+                Err(e) => (false, Err(e)),
+            })
+            .await??;
+
+        if written {
+            self.notify(addr, ChangeKind::of(new));
+        }
+
+        Ok(written)
+    }
+}
+
+/// Atomic: the value is read and written under the store's lock.
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A> + AddressableSet<D, A>>
+    AddressableSwap<Value, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn swap(
+        &self,
+        addr: &JsonPath,
+        value: &Option<Value>,
+    ) -> StoreResult<Option<Value>, Self> {
+        let previous = self
+            .change_value_if(|cur| match document_pathvalue(cur, &addr.0[..]) {
+                Ok(previous) => {
+                    let previous = previous.cloned();
+                    (
+                        true,
+                        set_pathvalue(cur, &addr.0[..], value).map(|_| previous),
+                    )
+                }
+                Err(e) => (false, Err(e)),
+            })
+            .await??;
+
+        self.notify(addr, ChangeKind::of(value));
+
+        Ok(previous)
+    }
+}
+
+/// Removes the key or the array item; deleting the root empties the document.
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A> + AddressableSet<D, A>>
+    AddressableDelete<JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn delete(&self, addr: &JsonPath) -> StoreResult<(), Self> {
+        AddressableSet::<Value, _>::set_addr(self, addr, &None).await
+    }
+}
+
+/// Creates an empty object where there's nothing or `null`.
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A> + AddressableSet<D, A>>
+    AddressableBranch<JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn ensure_branch(&self, addr: &JsonPath) -> StoreResult<(), Self> {
+        let (_, value) = self.lock_read_value().await?;
+        if let Some(Value::Object(_) | Value::Array(_)) = get_pathvalue(&value, &addr.0[..])? {
+            return Ok(());
+        }
+
+        let created = self
+            .change_value(|cur| {
+                let at = get_mut_pathvalue(cur, &addr.0[..], true)?
+                    .expect("create_on_miss always returns a value");
+
+                match at {
+                    Value::Object(_) | Value::Array(_) => Ok(false),
+                    Value::Null => {
+                        *at = Value::Object(Default::default());
+                        Ok(true)
+                    }
+                    v => Err(LocatedJsonStoreError::CustomError(format!(
+                        "{addr} is not a container: {v}"
+                    ))),
+                }
+            })
+            .await??;
+
+        if created {
+            self.notify(addr, ChangeKind::Set);
+        }
+
+        Ok(())
+    }
+}
+
+/// Only readable at the root: the whole document. `None` if it's empty.
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>>
+    AddressableGet<WholeStore<Value>, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<WholeStore<Value>>, Self> {
+        if !addr.0.is_empty() {
+            return Err(LocatedJsonStoreError::CustomError(format!(
+                "WholeStore can only be read at the root, not at {addr}"
+            )));
+        }
+
+        let (_, value) = self.lock_read_value().await?;
+
+        Ok((!value.is_null()).then_some(WholeStore(value)))
+    }
+}
+
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>> AddressableGet<Existence, JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Existence>, Self> {
+        let v: Option<Value> =
+            <LocatedJsonStore<A, S, D> as AddressableGet<Value, JsonPath>>::addr_get(self, addr)
+                .await?;
+
+        Ok(v.map(|_| Existence))
+    }
+}
+
+/// Parses the document once for all the addresses.
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>> AddressableExistsMany<JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    async fn exists_many(&self, addrs: &[JsonPath]) -> StoreResult<Vec<bool>, Self> {
+        let (_, value) = self.lock_read_value().await?;
+
+        addrs
+            .iter()
+            .map(|addr| Ok(document_pathvalue(&value, &addr.0[..])?.is_some()))
+            .collect()
+    }
+}
+
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>> AddressableGet<Present, JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Present>, Self> {
+        let e = AddressableGet::<Existence, _>::addr_get(self, addr).await?;
+
+        Ok(Some(Present(e.is_some())))
+    }
+}
+
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>> AddressableGet<JsonEntry, JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<JsonEntry>, Self> {
+        let v = AddressableGet::<Value, _>::addr_get(self, addr).await?;
+
+        Ok(Some(v.into()))
+    }
+}
+
+/// The object at the address. `None` if there's no object there, including if it's
+/// another kind of value.
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>>
+    AddressableGet<Map<String, Value>, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<Map<String, Value>>, Self> {
+        let v = AddressableGet::<Value, _>::addr_get(self, addr).await?;
+
+        Ok(match v {
+            Some(Value::Object(map)) => Some(map),
+            _ => None,
+        })
+    }
+}
+
+impl<'a, A: Address, D: JsonDocument, S: 'a + AddressableGet<D, A>> AddressableList<'a, JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    type AddedAddress = JsonPathPart;
+
+    type ItemAddress = JsonPath;
+
+    fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        stream::once(async move {
+            let value = this.lock_read_value().await?.1;
+
+            let val: StoreResult<_, Self> = try {
+                get_pathvalue(&value, &addr.0[..])?.ok_or(LocatedJsonStoreError::CustomError(
+                    "Path doesn't exist".to_owned(),
+                ))?
+            };
+
+            let vec = match val {
+                Ok(Value::Array(arr)) => (0..arr.len())
+                    .map(JsonPathPart::Index)
+                    .map(|i| Ok((i.clone(), addr.clone().sub(i))))
+                    .collect(),
+                Ok(Value::Object(obj)) => obj
+                    .keys()
+                    .map(|k| JsonPathPart::Key(k.to_owned()))
+                    .map(|i| Ok((i.clone(), addr.clone().sub(i))))
+                    .collect(),
+                Err(e) => vec![Err(e)],
+                _ => vec![Err(LocatedJsonStoreError::CustomError(format!(
+                    "Can't list: {val:?}"
+                )))],
+            };
+
+            Ok::<_, Self::Error>(stream::iter(vec.into_iter()))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
+
+/// Looks the key or the index up in the value, without listing it.
+/// The name is the part as `own_name` writes it: `.key`, `["some.key"]` or `[1]`.
+impl<'a, A: Address, D: JsonDocument, S: 'a + AddressableGet<D, A>> AddressableChild<'a, JsonPath>
+    for LocatedJsonStore<A, S, D>
+{
+    async fn child(&self, addr: &JsonPath, name: &str) -> StoreResult<Option<JsonPath>, Self> {
+        // only the names that a listing could return
+        let part = match name.parse::<JsonPath>().map(|p| p.0) {
+            Ok(parts) if parts.len() == 1 && parts[0].to_string() == name => parts[0].clone(),
+            _ => return Ok(None),
+        };
+
+        let (_, value) = self.lock_read_value().await?;
+
+        let exists = match (document_pathvalue(&value, &addr.0[..])?, &part) {
+            (Some(Value::Object(obj)), JsonPathPart::Key(key)) => obj.contains_key(key),
+            (Some(Value::Array(arr)), JsonPathPart::Index(ix)) => *ix < arr.len(),
+            _ => false,
+        };
+
+        Ok(exists.then(|| addr.clone().sub(part)))
+    }
+}
+
+/// Finds the values under `addr` matching a [`JsonQuery`], in document order.
+///
+/// Unlike `list`, the matches can be deeper than the direct children of `addr`:
+/// the item address is the whole path of the match, and the added address is its last part.
+impl<'a, A: Address, D: JsonDocument, S: 'a + AddressableGet<D, A>>
+    AddressableQuery<'a, JsonQuery, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    fn query(&self, addr: &JsonPath, query: JsonQuery) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        stream::once(async move {
+            let value = this.lock_read_value().await?.1;
+
+            let val = get_pathvalue(&value, &addr.0[..])?.ok_or(
+                LocatedJsonStoreError::CustomError("Path doesn't exist".to_owned()),
+            )?;
+
+            let matches = query.matches(val)?;
+
+            Ok::<_, Self::Error>(stream::iter(matches.into_iter().filter_map(move |path| {
+                let part = path.0.last()?.clone();
+                Some(Ok((part, addr.clone().sub(path))))
+            })))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
+
+impl<A: Address, D: JsonDocument, S: AddressableGet<D, A>> LocatedJsonStore<A, S, D> {
+    /// Like `list`, but also tells the kind of every item,
+    /// so you don't need to check whether it's a branch or a leaf.
+    pub fn list_typed<'a>(
+        &self,
+        addr: &JsonPath,
+    ) -> LocalBoxStream<'a, StoreResult<(JsonPathPart, JsonPath, ValueKind), Self>>
+    where
+        S: 'a,
+    {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        stream::once(async move {
+            let value = this.lock_read_value().await?.1;
+
+            let val = get_pathvalue(&value, &addr.0[..])?.ok_or(
+                LocatedJsonStoreError::CustomError("Path doesn't exist".to_owned()),
+            )?;
+
+            let vec: Vec<_> = match val {
+                Value::Array(arr) => arr
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| (JsonPathPart::Index(i), v.into()))
+                    .collect(),
+                Value::Object(obj) => obj
+                    .iter()
+                    .map(|(k, v)| (JsonPathPart::Key(k.to_owned()), v.into()))
+                    .collect(),
+                _ => {
+                    return Err(LocatedJsonStoreError::CustomError(format!(
+                        "Can't list: {val:?}"
+                    )))
+                }
+            };
+
+            Ok(stream::iter(vec.into_iter().map(move |(i, kind)| {
+                Ok((i.clone(), addr.clone().sub(i), kind))
+            })))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+
+    /// Like `list`, but also tells how many items there are, before any of them are consumed,
+    /// e.g. to show "1–20 of 137" in a UI. The items come in the same order as in `list`:
+    /// indices for arrays, and keys in the order `serde_json` keeps them for objects.
+    pub async fn list_with_total<'a>(
+        &self,
+        addr: &JsonPath,
+    ) -> StoreResult<
+        (
+            usize,
+            LocalBoxStream<'a, StoreResult<(JsonPathPart, JsonPath), Self>>,
+        ),
+        Self,
+    >
+    where
+        S: 'a,
+    {
+        let value = self.lock_read_value().await?.1;
+
+        let val = get_pathvalue(&value, &addr.0[..])?.ok_or(LocatedJsonStoreError::CustomError(
+            "Path doesn't exist".to_owned(),
+        ))?;
+
+        let parts: Vec<_> = match val {
+            Value::Array(arr) => (0..arr.len()).map(JsonPathPart::Index).collect(),
+            Value::Object(obj) => obj.keys().cloned().map(JsonPathPart::Key).collect(),
+            _ => {
+                return Err(LocatedJsonStoreError::CustomError(format!(
+                    "Can't list: {val:?}"
+                )))
+            }
+        };
+
+        let addr = addr.clone();
+        let total = parts.len();
+        let items = stream::iter(
+            parts
+                .into_iter()
+                .map(move |i| Ok((i.clone(), addr.clone().sub(i)))),
+        );
+
+        Ok((total, items.boxed_local()))
+    }
+}
+
+impl<'a, A: Address, D: JsonDocument, S: 'a + AddressableGet<D, A>>
+    AddressableTree<'a, JsonPath, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    async fn branch_or_leaf(
+        &self,
+        addr: JsonPath,
+    ) -> StoreResult<BranchOrLeaf<JsonPath, JsonPath>, Self> {
+        let value = self.lock_read_value().await?.1;
+        let val = get_pathvalue(&value, &addr.0[..])?.ok_or(LocatedJsonStoreError::CustomError(
+            "Path doesn't exist".to_owned(),
+        ))?;
+
+        Ok(match val {
+            Value::Array(_) => BranchOrLeaf::Branch(addr),
+            Value::Object(_) => BranchOrLeaf::Branch(addr),
+
+            _ => BranchOrLeaf::Leaf(addr),
+        })
+    }
+}
+
+impl<'a, A: Address, D: JsonDocument, S: 'a + AddressableGet<D, A> + AddressableSet<D, A>>
+    AddressableInsert<'a, Value, JsonPath> for LocatedJsonStore<A, S, D>
+{
+    fn insert(&self, addr: &JsonPath, items: Vec<Value>) -> Self::ListOfAddressesStream {
+        let addr = addr.clone();
+        let this = self.clone();
+
+        stream::once(async move {
+            let addr = addr.clone();
+            let path = addr.0.clone();
+            let paths = this
+                .change_value(move |cur| {
+                    let insert_at = get_mut_pathvalue(cur, &path[..], true)?.unwrap();
+
+                    if insert_at.is_null() {
+                        *insert_at = Value::Array(vec![]);
+                    }
+
+                    let arr = match insert_at {
+                        Value::Array(at) => at,
+                        _ => {
+                            return Err::<_, Self::Error>(LocatedJsonStoreError::CustomError(
+                                "Can't insert into non-array value".to_owned(),
+                            ))
+                        }
+                    };
+
+                    let ixes = arr.len()..arr.len() + items.len();
+
+                    arr.extend(items);
+
+                    Ok(ixes
+                        .map(JsonPathPart::Index)
+                        .map(move |i| (i.clone(), addr.clone().sub(i))))
+                })
+                .await??
+                .collect::<Vec<_>>();
+
+            for (_, path) in &paths {
+                this.notify(path, ChangeKind::Set);
+            }
+
+            Ok::<_, Self::Error>(stream::iter(paths.into_iter().map(Ok)))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
 
 #[cfg(test)]
 #[cfg(feature = "json")]
 mod test {
-    use serde_json::json;
+    use serde_json::{json, Map, Value};
+
+    use crate::{
+        address::{primitive::UniqueRootAddress, traits::BranchOrLeaf, PathAddress},
+        store::{RootKind, Store, StoreEx},
+        stores::{
+            cell::MemoryCellStore,
+            json::{
+                json_value_store,
+                paths::{JsonPath, JsonPathPart},
+                query::JsonQuery,
+                JsonEntry, ValueKind,
+            },
+            located::json::{ChangeKind, LocatedJsonStore},
+        },
+        test_util::{CountingStore, ListCountingStore, ReversedStore},
+    };
+    use futures::{StreamExt, TryStreamExt};
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn test_format() -> Result<(), anyhow::Error> {
+        use crate::{
+            stores::{fs::FileSystemStore, located::json::JsonFormat},
+            test_util::temp_dir,
+        };
+
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+        let file = FileSystemStore::new(dir.clone()).path("config.json")?;
+
+        let store = LocatedJsonStore::new_with_format(
+            file.clone(),
+            JsonFormat {
+                pretty: true,
+                sort_keys: true,
+                indent: 4,
+            },
+        );
+        store
+            .root()
+            .setv(&Some(json!({"b": 1, "a": {"d": [1], "c": 2}})))
+            .await?;
+
+        let expected = r#"{
+    "a": {
+        "c": 2,
+        "d": [
+            1
+        ]
+    },
+    "b": 1
+}"#;
+        assert_eq!(file.get_string().await?.as_deref(), Some(expected));
+
+        store.path("a.c")?.setv(&Some(json!(3))).await?;
+        assert_eq!(
+            file.get_string().await?.as_deref(),
+            Some(expected.replace("2", "3").as_str())
+        );
+
+        LocatedJsonStore::new(file.clone())
+            .path("a.c")?
+            .setv(&Some(json!(2)))
+            .await?;
+        assert_eq!(
+            file.get_string().await?.as_deref(),
+            Some(r#"{"a":{"c":2,"d":[1]},"b":1}"#)
+        );
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ensure_branch() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"list": [1], "leaf": 3}))?;
+
+        store.path("a.b")?.ensure_branch().await?;
+        store.path("list")?.ensure_branch().await?;
+        assert!(store.path("leaf")?.ensure_branch().await.is_err());
+
+        assert_eq!(
+            store.root().getv().await?,
+            Some(json!({"a": {"b": {}}, "list": [1], "leaf": 3}))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_json_entry() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"null": null, "value": 1}))?;
+
+        let null = store.path("null")?;
+        let value = store.path("value")?;
+        let absent = store.path("absent")?;
+
+        assert_eq!(null.get::<JsonEntry>().await?, Some(JsonEntry::Null));
+        assert_eq!(
+            value.get::<JsonEntry>().await?,
+            Some(JsonEntry::Value(json!(1)))
+        );
+        assert_eq!(absent.get::<JsonEntry>().await?, Some(JsonEntry::Absent));
+
+        store.set_entry(&absent.address, JsonEntry::Null).await?;
+        store.set_entry(&value.address, JsonEntry::Absent).await?;
+        store
+            .set_entry(&null.address, JsonEntry::Value(json!("x")))
+            .await?;
+
+        assert_eq!(
+            store.root().getv().await?,
+            Some(json!({"null": "x", "absent": null}))
+        );
+
+        store.set_entry(&absent.address, JsonEntry::Absent).await?;
+        assert_eq!(absent.get::<JsonEntry>().await?, Some(JsonEntry::Absent));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_compare_and_swap() -> Result<(), anyhow::Error> {
+        let counting = CountingStore::new(MemoryCellStore::from_json(&json!({"version": 1}))?);
+        let store = LocatedJsonStore::new(counting.root());
+        let version = store.path("version")?;
+
+        assert!(
+            !version
+                .compare_and_swap(&Some(json!(2)), &Some(json!(3)))
+                .await?
+        );
+        assert_eq!(counting.writes(), 0);
+
+        assert!(
+            version
+                .compare_and_swap(&Some(json!(1)), &Some(json!(2)))
+                .await?
+        );
+        assert!(
+            store
+                .path("new")?
+                .compare_and_swap(&None, &Some(json!("x")))
+                .await?
+        );
+
+        assert_eq!(
+            store.root().getv().await?,
+            Some(json!({"version": 2, "new": "x"}))
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_swap() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": {"b": 1}}))?;
+
+        assert_eq!(
+            store.path("a.b")?.swap(&Some(json!(2))).await?,
+            Some(json!(1))
+        );
+        assert_eq!(store.path("a.c")?.swap(&Some(json!(3))).await?, None);
+        assert_eq!(
+            store.path("a")?.swap(&None).await?,
+            Some(json!({"b": 2, "c": 3}))
+        );
+        assert_eq!(store.root().getv().await?, Some(json!({})));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_page() -> Result<(), anyhow::Error> {
+        let obj = (0..25)
+            .map(|i| (format!("k{i:02}"), json!(i)))
+            .collect::<serde_json::Map<_, _>>();
+        let root = json_value_store(obj.into())?.root();
+
+        let page = root.list_page(0, 10).await?;
+        assert_eq!(page.items.len(), 10);
+        assert_eq!(page.items[0].to_string(), "k00");
+        assert!(page.has_more);
+
+        let page = root.list_page(20, 10).await?;
+        assert_eq!(
+            page.items.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+            vec!["k20", "k21", "k22", "k23", "k24"]
+        );
+        assert!(!page.has_more);
+
+        let page = root.list_page(15, 10).await?;
+        assert_eq!(page.items.len(), 10);
+        assert!(!page.has_more);
+
+        assert!(root.list_page(30, 10).await?.items.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_list_with_total() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"obj": {"b": 1, "a": 2, "c": 3}}))?;
+
+        let (total, items) = store.list_with_total(&store.path("obj")?.address).await?;
+        assert_eq!(total, 3);
+
+        let items = items
+            .take(2)
+            .map_ok(|(p, _)| p.to_key())
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(items, vec!["a", "b"]);
+
+        assert!(store
+            .list_with_total(&store.path("obj.a")?.address)
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_is_branch() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"obj": {"a": 1}, "arr": [], "s": "x"}))?;
+
+        assert!(store.path("obj")?.is_branch().await?);
+        assert!(store.path("arr")?.is_branch().await?);
+        assert!(store.path("s")?.is_leaf().await?);
+        assert!(!store.path("obj.a")?.is_branch().await?);
+        assert!(store.path("nope")?.is_leaf().await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_query() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": {"b": [{"id": 1}, {"id": 2}]}, "id": 0}))?;
+
+        let found = store
+            .path("a")?
+            .query(JsonQuery("$..id".to_owned()))
+            .map_ok(|(p, addr)| (p.to_key(), addr.to_string()))
+            .try_collect::<Vec<_>>()
+            .await?;
+        assert_eq!(
+            found,
+            vec![
+                ("id".to_owned(), "a.b[0].id".to_owned()),
+                ("id".to_owned(), "a.b[1].id".to_owned())
+            ]
+        );
+
+        assert!(store
+            .root()
+            .query(JsonQuery("id".to_owned()))
+            .try_collect::<Vec<_>>()
+            .await
+            .is_err());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn test_streaming() -> Result<(), anyhow::Error> {
+        use crate::{
+            stores::{fs::FileSystemStore, located::json::JsonFormat},
+            test_util::temp_dir,
+        };
+
+        let dir = temp_dir();
+        tokio::fs::create_dir(&dir).await?;
+
+        let fs = FileSystemStore::new(dir.clone());
+        let format = JsonFormat {
+            pretty: true,
+            ..Default::default()
+        };
+        let store = LocatedJsonStore::new_streaming(fs.path("doc.json")?, format);
+        let a = store.path("a")?;
+
+        assert_eq!(a.getv().await?, None);
+
+        a.setv(&Some(json!({"b": [1, 2]}))).await?;
+        assert_eq!(a.getv().await?, Some(json!({"b": [1, 2]})));
+        assert_eq!(store.path("a.b[1]")?.getv().await?, Some(json!(2)));
+        assert_eq!(
+            fs.path("doc.json")?.get_string().await?,
+            Some(format.serialize_document::<String>(&json!({"a": {"b": [1, 2]}}))?)
+        );
+
+        store.root().setv(&None).await?;
+        assert_eq!(
+            fs.path("doc.json")?.get_string().await?,
+            Some(String::new())
+        );
+
+        tokio::fs::write(dir.join("doc.json"), " \n").await?;
+        assert_eq!(a.getv().await?, None);
+
+        tokio::fs::write(dir.join("doc.json"), "{\"a\": ").await?;
+        assert!(a.getv().await.is_err());
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_unique() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"users": [{"id": 1, "name": "a"}]}))?;
+        let users = store.path("users")?.address;
+        let id = |v: &serde_json::Value| v["id"].clone();
+
+        let result = store
+            .insert_unique(
+                &users,
+                vec![
+                    json!({"id": 1, "name": "b"}),
+                    json!({"id": 2, "name": "c"}),
+                    json!({"id": 2, "name": "d"}),
+                ],
+                id,
+            )
+            .await?;
+
+        assert_eq!(result.inserted, vec![store.path("users[1]")?.address]);
+        assert_eq!(
+            result.skipped,
+            vec![json!({"id": 1, "name": "b"}), json!({"id": 2, "name": "d"})]
+        );
+        assert_eq!(
+            store.path("users")?.getv().await?,
+            Some(json!([{"id": 1, "name": "a"}, {"id": 2, "name": "c"}]))
+        );
+
+        let again = store
+            .insert_unique(&users, vec![json!({"id": 2})], id)
+            .await?;
+        assert!(again.inserted.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_map() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"server": {"port": 80, "host": "a"}, "x": 1}))?;
+        let server = store.path("server")?;
+
+        let mut map = server.get::<Map<String, Value>>().await?.unwrap();
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec!["host", "port"]);
+
+        map.insert("port".to_owned(), json!(8080));
+        map.remove("host");
+        store.set_object(&server.address, map).await?;
+
+        assert_eq!(server.getv().await?, Some(json!({"port": 8080})));
+        assert_eq!(store.path("x")?.get::<Map<String, Value>>().await?, None);
+        assert_eq!(store.path("y")?.get::<Map<String, Value>>().await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_child() -> Result<(), anyhow::Error> {
+        let store =
+            json_value_store(json!({"a": {"x": 1, "0": 2, "y.z": 3}, "b": [1, 2], "c": 3}))?;
+
+        assert_eq!(
+            store.path("a")?.child(".x").await?,
+            Some(store.path("a.x")?.address)
+        );
+        assert_eq!(
+            store.path("a")?.child(".0").await?,
+            Some(store.path("a[\"0\"]")?.address)
+        );
+        assert_eq!(
+            store.path("a")?.child("[\"y.z\"]").await?,
+            Some(store.path("a[\"y.z\"]")?.address)
+        );
+        assert_eq!(store.path("a")?.child(".y").await?, None);
+        assert_eq!(store.path("a")?.child("x").await?, None);
+        assert_eq!(store.path("a")?.child(".y.z").await?, None);
+        assert_eq!(
+            store.path("b")?.child("[1]").await?,
+            Some(store.path("b[1]")?.address)
+        );
+        assert_eq!(store.path("b")?.child("[2]").await?, None);
+        assert_eq!(store.path("b")?.child("[01]").await?, None);
+
+        // the value is looked up, not listed: listing these fails
+        for path in ["c", "missing"] {
+            let location = store.path(path)?;
+            assert!(location.list().try_collect::<Vec<_>>().await.is_err());
+            assert_eq!(location.child(".x").await?, None);
+        }
+
+        // the default lists the children, and finds the same ones
+        let listing = ListCountingStore::new(store.clone());
+
+        for (path, name) in [("a", ".x"), ("a", "[\"y.z\"]"), ("a", ".y"), ("b", "[1]")] {
+            let lists = listing.lists();
+
+            assert_eq!(
+                listing.path(path)?.child(name).await?,
+                store.path(path)?.child(name).await?
+            );
+            assert_eq!(listing.lists(), lists + 1);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reorder() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"list": ["a", "b", "c", "d"], "x": 1}))?;
+        let list = store.path("list")?;
+        let mut changes = store.subscribe();
+
+        store.reorder(&list.address, 0, 2).await?;
+        assert_eq!(list.getv().await?, Some(json!(["b", "c", "a", "d"])));
+        assert!(changes.try_recv().is_ok());
+
+        store.reorder(&list.address, 1, 1).await?;
+        assert_eq!(list.getv().await?, Some(json!(["b", "c", "a", "d"])));
+        assert!(changes.try_recv().is_err());
+
+        store.reorder(&list.address, 3, 0).await?;
+        assert_eq!(list.getv().await?, Some(json!(["d", "b", "c", "a"])));
+
+        assert!(store.reorder(&list.address, 1, 4).await.is_err());
+        assert!(store
+            .reorder(&store.path("x")?.address, 0, 0)
+            .await
+            .is_err());
+        assert_eq!(list.getv().await?, Some(json!(["d", "b", "c", "a"])));
 
-    use crate::{store::StoreEx, stores::json::json_value_store};
-    use futures::TryStreamExt;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_child() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"users": [{"id": 1}, {"id": 2}, {"id": 3}]}))?;
+        let users = store.path("users")?;
+
+        assert_eq!(
+            users
+                .find_child(|p| p.0.last() == Some(&JsonPathPart::Index(1)))
+                .await?,
+            Some(store.path("users[1]")?.address)
+        );
+        assert_eq!(
+            users
+                .find_child(|p| p.0.last() == Some(&JsonPathPart::Index(3)))
+                .await?,
+            None
+        );
+
+        assert_eq!(
+            users
+                .find_child_value(|v: &serde_json::Value| v["id"] == 3)
+                .await?,
+            Some((store.path("users[2]")?.address, json!({"id": 3})))
+        );
+
+        Ok(())
+    }
+
+    #[cfg(feature = "file-locks")]
+    #[tokio::test]
+    async fn test_file_lock() -> Result<(), anyhow::Error> {
+        use crate::{stores::fs::FileSystemStore, test_util::temp_dir};
+
+        let dir = temp_dir();
+        let file = FileSystemStore::new(dir.clone()).path("shared.json")?;
+
+        // separate stores don't share the in-memory lock
+        let first = LocatedJsonStore::new_with_file_lock(file.clone(), Default::default());
+        let second = LocatedJsonStore::new_with_file_lock(file, Default::default());
+
+        let write_all = |store: LocatedJsonStore<_, _>, prefix: &'static str| async move {
+            for i in 0..50 {
+                store
+                    .path(&format!("{prefix}{i}"))?
+                    .setv(&Some(json!(i)))
+                    .await?;
+            }
+            Ok::<_, anyhow::Error>(())
+        };
+
+        let (a, b) = tokio::join!(write_all(first.clone(), "a"), write_all(second, "b"));
+        a?;
+        b?;
+
+        let value = first.root().getv().await?.unwrap();
+        assert_eq!(value.as_object().unwrap().len(), 100);
+        assert_eq!(value["a49"], json!(49));
+        assert_eq!(value["b0"], json!(0));
+
+        tokio::fs::remove_dir_all(dir).await?;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": 1, "list": []}))?;
+        let mut changes = store.subscribe();
+
+        store.path("b.c")?.setv(&Some(json!(2))).await?;
+        store.path("a")?.setv(&None).await?;
+        store
+            .path("list")?
+            .insert(vec![json!(1)])
+            .try_collect::<Vec<_>>()
+            .await?;
+        // failed writes aren't sent
+        assert!(store.path("b.c.d")?.setv(&Some(json!(3))).await.is_err());
+
+        let mut received = vec![];
+        while let Ok((path, kind)) = changes.try_recv() {
+            received.push((path.to_string(), kind));
+        }
+
+        assert_eq!(
+            received,
+            vec![
+                ("b.c".to_owned(), ChangeKind::Set),
+                ("a".to_owned(), ChangeKind::Deleted),
+                ("list[0]".to_owned(), ChangeKind::Set),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_values() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": 1, "b": "two", "c": null, "d": {"e": 3}}))?;
+
+        let scalars = store
+            .root()
+            .values::<serde_json::Value>()
+            .try_filter(|v| futures::future::ready(!v.is_object() && !v.is_array()))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        // `null` is a present value
+        assert_eq!(scalars, vec![json!(1), json!("two"), json!(null)]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_flatten_to_map() -> Result<(), anyhow::Error> {
+        let original = json!({"db": {"host": "localhost", "ports": [1, 2]}, "a.b": {"": true}});
+        let store = json_value_store(original.clone())?;
+
+        let map = store.root().flatten_to_map().await?;
+        assert_eq!(map.path("db.ports[1]")?.getv().await?, Some(json!(2)));
+        assert_eq!(map.snapshot().await.len(), 4);
+
+        let copy = json_value_store(json!(null))?;
+        copy.root().apply_map(&map).await?;
+        assert_eq!(copy.root().getv().await?, Some(original));
+
+        // changes to the map aren't written back by themselves
+        map.path("db.host")?.setv(&Some(json!("remote"))).await?;
+        assert_eq!(
+            store.path("db.host")?.getv().await?,
+            Some(json!("localhost"))
+        );
+
+        store.root().apply_map(&map).await?;
+        assert_eq!(store.path("db.host")?.getv().await?, Some(json!("remote")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_value_eq() -> Result<(), anyhow::Error> {
+        use crate::wrappers::metrics::MetricsWrapperStore;
+
+        let store = json_value_store(json!({"a": {"b": [1, 2]}, "c": [1, 2]}))?;
+        let other = MetricsWrapperStore::new(json_value_store(json!({"d": [1, 2]}))?);
+
+        let d = other.path("d")?;
+        assert!(
+            store
+                .path("a.b")?
+                .value_eq::<serde_json::Value, _, _>(&d)
+                .await?
+        );
+        assert!(
+            !store
+                .path("a")?
+                .value_eq::<serde_json::Value, _, _>(&d)
+                .await?
+        );
+        assert!(
+            store
+                .path("x")?
+                .value_eq::<serde_json::Value, _, _>(&other.path("y")?)
+                .await?
+        );
+
+        // the same value at another address of the same store
+        assert!(
+            store
+                .path("a.b")?
+                .value_eq::<serde_json::Value, _, _>(&store.path("c")?)
+                .await?
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_insert_with_values() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"list": [0]}))?;
+        let items = vec![json!({"id": 1}), json!("two"), json!([3])];
+
+        let inserted = store
+            .insert_with_values(&store.path("list")?.address, items.clone())
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        assert_eq!(
+            inserted
+                .iter()
+                .map(|(path, _)| path.to_string())
+                .collect::<Vec<_>>(),
+            vec!["list[1]", "list[2]", "list[3]"]
+        );
+        assert_eq!(
+            inserted.into_iter().map(|(_, v)| v).collect::<Vec<_>>(),
+            items
+        );
+        assert_eq!(store.path("list[2]")?.getv().await?, Some(json!("two")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_walk_with_values() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": {"b": 1, "c": [true, null]}, "d": "x"}))?;
+
+        let walked = store
+            .root()
+            .walk_with_values::<_, serde_json::Value>(2)
+            .map_ok(|bl| match bl {
+                BranchOrLeaf::Branch(br) => (br.to_string(), None),
+                BranchOrLeaf::Leaf((leaf, v)) => (leaf.to_string(), Some(v)),
+            })
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        assert_eq!(
+            walked,
+            vec![
+                ("a".to_owned(), None),
+                ("a.b".to_owned(), Some(json!(1))),
+                ("a.c".to_owned(), None),
+                ("a.c[0]".to_owned(), Some(json!(true))),
+                ("a.c[1]".to_owned(), Some(json!(null))),
+                ("d".to_owned(), Some(json!("x"))),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stream_leaves_with_values() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": {"b": 1, "c": [true]}, "d": "x"}))?;
+
+        let leaves = store
+            .path("a")?
+            .stream_leaves_with_values::<_, serde_json::Value>()
+            .map_ok(|(leaf, v)| (leaf.to_string(), v))
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        assert_eq!(
+            leaves,
+            vec![
+                ("a.b".to_owned(), json!(1)),
+                ("a.c[0]".to_owned(), json!(true)),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_walk_tree_sorted() -> Result<(), anyhow::Error> {
+        let store = ReversedStore {
+            inner: json_value_store(json!({
+                "wow": {"hello": "yes"},
+                "another": {"seriously": {"throrougly": 7}, "basic": [1, {"b": 2, "a": 1}]},
+            }))?,
+        };
+
+        // the listings come out of order
+        assert_eq!(
+            store
+                .path("another.basic[1]")?
+                .list()
+                .map_ok(|(_, p)| p.to_string())
+                .try_collect::<Vec<_>>()
+                .await?,
+            vec!["another.basic[1].b", "another.basic[1].a"]
+        );
+
+        let walk = || {
+            store
+                .root()
+                .walk_tree_sorted::<JsonPath>()
+                .map_ok(|bl| bl.to_string())
+                .try_collect::<Vec<_>>()
+        };
+
+        let first = walk().await?;
+        assert_eq!(
+            first,
+            vec![
+                "another",
+                "another.basic",
+                "another.basic[0]",
+                "another.basic[1]",
+                "another.basic[1].a",
+                "another.basic[1].b",
+                "another.seriously",
+                "another.seriously.throrougly",
+                "wow",
+                "wow.hello",
+            ]
+        );
+
+        for _ in 0..5 {
+            assert_eq!(walk().await?, first);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_root_kind() -> Result<(), anyhow::Error> {
+        assert_eq!(json_value_store(json!([1]))?.root_kind(), RootKind::Tree);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_which_exist() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"users": {"alice": 1, "bob": null}, "n": [1, 2]}))?;
+
+        assert_eq!(
+            store
+                .path("users")?
+                .which_exist(["carol", "bob", "alice"].map(|k| JsonPathPart::Key(k.to_owned())))
+                .await?,
+            vec![
+                store.path("users.bob")?.address,
+                store.path("users.alice")?.address
+            ]
+        );
+        assert_eq!(
+            store
+                .path("n")?
+                .which_exist([JsonPathPart::Index(2), JsonPathPart::Index(0)])
+                .await?,
+            vec![store.path("n[0]")?.address]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_replace_and_merge_subtree() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"db": {"host": "a", "port": 1}, "other": true}))?;
+        let db = store.path("db")?.address;
+
+        store
+            .merge_subtree(&db, json!({"port": 2, "pool": {"size": 5}}))
+            .await?;
+        assert_eq!(
+            store.root().getv().await?,
+            Some(json!({"db": {"host": "a", "port": 2, "pool": {"size": 5}}, "other": true}))
+        );
+
+        store.replace_subtree(&db, json!({"port": 3})).await?;
+        assert_eq!(
+            store.root().getv().await?,
+            Some(json!({"db": {"port": 3}, "other": true}))
+        );
+
+        let has_host =
+            |_: Option<&serde_json::Value>, new: &serde_json::Value| match new.get("host") {
+                Some(_) => Ok(()),
+                None => Err("db needs a host".to_owned()),
+            };
+        assert!(store
+            .replace_subtree_checked(&db, json!({"port": 4}), has_host)
+            .await
+            .is_err());
+        assert_eq!(store.path("db")?.getv().await?, Some(json!({"port": 3})));
+
+        store
+            .replace_subtree_checked(&db, json!({"host": "b"}), has_host)
+            .await?;
+        assert_eq!(store.path("db")?.getv().await?, Some(json!({"host": "b"})));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_empty_document() -> Result<(), anyhow::Error> {
+        for empty in ["", "  \n\t"] {
+            let cell = MemoryCellStore::new(Some(empty.to_owned()));
+            let store = LocatedJsonStore::new(cell.root());
+
+            assert_eq!(store.root().getv().await?, None);
+            assert!(!store.root().exists().await?);
+            assert_eq!(store.path("a")?.getv().await?, None);
+
+            store.path("a")?.setv(&Some(json!(1))).await?;
+            assert_eq!(cell.snapshot().await.as_deref(), Some(r#"{"a":1}"#));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bytes_document() -> Result<(), anyhow::Error> {
+        let cell = MemoryCellStore::new(Some(br#"{"a": {"b": "\u00e9"}}"#.to_vec()));
+        let store = LocatedJsonStore::new_bytes(cell.root());
+
+        assert_eq!(store.path("a.b")?.getv().await?, Some(json!("é")));
+
+        store.path("a.c")?.setv(&Some(json!([1, 2]))).await?;
+        assert_eq!(
+            cell.snapshot().await,
+            Some(r#"{"a":{"b":"é","c":[1,2]}}"#.as_bytes().to_vec())
+        );
+
+        store.root().delete().await?;
+        assert_eq!(cell.snapshot().await, Some(vec![]));
+        assert_eq!(store.root().getv().await?, None);
+
+        // not UTF-8, so only the JSON parser rejects it
+        cell.root().setv(&Some(b"\xff".to_vec())).await?;
+        assert!(store.root().getv().await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_delete_document() -> Result<(), anyhow::Error> {
+        let cell = MemoryCellStore::from_json(&json!({"a": 1}))?;
+        let store = LocatedJsonStore::new(cell.root());
+
+        store.root().delete().await?;
+        assert_eq!(cell.snapshot().await.as_deref(), Some(""));
+        assert!(!store.root().exists().await?);
+        assert_eq!(store.root().getv().await?, None);
+        assert_eq!(
+            store.root().get::<JsonEntry>().await?,
+            Some(JsonEntry::Absent)
+        );
+
+        // a `null` document is the same as an empty one
+        store.root().setv(&Some(json!(null))).await?;
+        assert_eq!(cell.snapshot().await.as_deref(), Some(""));
+        let null_cell = MemoryCellStore::new(Some("null".to_owned()));
+        assert!(
+            !LocatedJsonStore::new(null_cell.root())
+                .root()
+                .exists()
+                .await?
+        );
+
+        // only the root: `null` values inside are kept
+        store.path("a")?.setv(&Some(json!(null))).await?;
+        assert!(store.path("a")?.exists().await?);
+        assert!(store.root().exists().await?);
+        assert!(
+            store
+                .root()
+                .compare_and_swap(&Some(json!({"a": null})), &None)
+                .await?
+        );
+        assert!(
+            store
+                .root()
+                .compare_and_swap(&None, &Some(json!(1)))
+                .await?
+        );
+        assert_eq!(store.root().swap(&None).await?, Some(json!(1)));
+        assert_eq!(store.root().swap(&Some(json!([]))).await?, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_batch() -> Result<(), anyhow::Error> {
+        let counting = CountingStore::default();
+        let store = LocatedJsonStore::new(counting.root());
+        let root = JsonPath::from(UniqueRootAddress);
+
+        store
+            .batch(|b| {
+                for i in 0..50 {
+                    b.write(root.clone().path(&format!("k{i}")).unwrap(), Some(json!(i)));
+                }
+                b.write(root.clone().path("k0").unwrap(), None);
+            })
+            .await?;
+
+        assert_eq!(counting.writes(), 1);
+        assert_eq!(store.path("k1")?.getv().await?, Some(json!(1)));
+        assert_eq!(store.path("k49")?.getv().await?, Some(json!(49)));
+        assert_eq!(store.path("k0")?.getv().await?, None);
+
+        // a failing write discards the whole batch
+        let failed = store
+            .batch(|b| {
+                b.write(root.clone().path("k2").unwrap(), Some(json!("changed")));
+                b.write(root.clone().path("k1[0]").unwrap(), Some(json!(0)));
+            })
+            .await;
+        assert!(failed.is_err());
+        assert_eq!(store.path("k2")?.getv().await?, Some(json!(2)));
+
+        Ok(())
+    }
 
     #[tokio::test]
     async fn test() -> Result<(), anyhow::Error> {
@@ -401,6 +2337,7 @@ mod test {
         assert_eq!(vc[1].1.to_string(), "list[4]");
 
         let vc: Vec<_> = root
+            .clone()
             .path("test.deeper")?
             .insert(vec![json!({"a": 1}), json!({"b": 2})])
             .try_collect()
@@ -410,6 +2347,14 @@ mod test {
         assert_eq!(vc[0].0.to_string(), "[0]");
         assert_eq!(vc[1].1.to_string(), "test.deeper[1]");
 
+        let test = root.path("test")?;
+        let typed: Vec<_> = test.store.list_typed(&test.address).try_collect().await?;
+
+        assert_eq!(typed.len(), 2);
+        assert_eq!(typed[0].1.to_string(), "test.a");
+        assert_eq!(typed[0].2, ValueKind::Number);
+        assert_eq!(typed[1].2, ValueKind::Array);
+
         Ok(())
     }
 }
@@ -8,4 +8,8 @@ pub mod cloud;
 pub mod json;
 pub mod located;
 
+#[cfg(feature = "json")]
+pub mod any;
+
 pub mod cell;
+pub mod hashmap;
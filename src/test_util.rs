@@ -0,0 +1,304 @@
+//! Stores and helpers shared by the tests.
+
+use std::{
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use crate::{
+    address::{
+        primitive::UniqueRootAddress,
+        traits::{AddressableGet, AddressableSet},
+        Addressable,
+    },
+    store::{Store, StoreResult},
+    stores::cell::{MemoryCellStore, MemoryCellStoreError},
+};
+
+#[cfg(feature = "json")]
+pub use self::json::*;
+
+/// A new path in the system's temporary directory. Nothing is created there.
+pub fn temp_dir() -> PathBuf {
+    std::env::temp_dir().join(uuid::Uuid::new_v4().to_string())
+}
+
+/// A string cell that counts the writes.
+#[derive(Clone)]
+pub struct CountingStore {
+    pub cell: MemoryCellStore<String>,
+    writes: Arc<AtomicUsize>,
+}
+
+impl CountingStore {
+    pub fn new(cell: MemoryCellStore<String>) -> Self {
+        CountingStore {
+            cell,
+            writes: Default::default(),
+        }
+    }
+
+    pub fn writes(&self) -> usize {
+        self.writes.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for CountingStore {
+    fn default() -> Self {
+        CountingStore::new(MemoryCellStore::new(None))
+    }
+}
+
+impl Store for CountingStore {
+    type Error = MemoryCellStoreError;
+}
+
+impl Addressable<UniqueRootAddress> for CountingStore {
+    type DefaultValue = String;
+}
+
+impl AddressableGet<String, UniqueRootAddress> for CountingStore {
+    async fn addr_get(&self, addr: &UniqueRootAddress) -> StoreResult<Option<String>, Self> {
+        AddressableGet::<String, _>::addr_get(&self.cell, addr).await
+    }
+}
+
+impl AddressableSet<String, UniqueRootAddress> for CountingStore {
+    async fn set_addr(
+        &self,
+        addr: &UniqueRootAddress,
+        value: &Option<String>,
+    ) -> StoreResult<(), Self> {
+        self.writes.fetch_add(1, Ordering::SeqCst);
+        self.cell.set_addr(addr, value).await
+    }
+}
+
+#[cfg(feature = "json")]
+mod json {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc, Mutex,
+        },
+    };
+
+    use futures::{stream, StreamExt, TryStreamExt};
+
+    use crate::{
+        address::{
+            traits::{
+                AddressableChild, AddressableGet, AddressableList, AddressableSet, AddressableTree,
+                BranchOrLeaf,
+            },
+            Addressable,
+        },
+        store::{Store, StoreEx, StoreResult},
+        stores::{
+            json::{JsonPath, JsonPathPart, JsonValueStore, JsonValueStoreError},
+            located::json::LocatedJsonStoreError,
+        },
+    };
+
+    /// Works like the JSON store, but fails operations on an address as many times as
+    /// configured with [`fail_at`](FlakyStore::fail_at).
+    #[derive(Clone)]
+    pub struct FlakyStore {
+        inner: JsonValueStore,
+        failures: Arc<Mutex<HashMap<String, usize>>>,
+    }
+
+    impl FlakyStore {
+        pub fn new(inner: JsonValueStore) -> Self {
+            FlakyStore {
+                inner,
+                failures: Default::default(),
+            }
+        }
+
+        pub fn fail_at(&self, addr: &str, times: usize) {
+            self.failures.lock().unwrap().insert(addr.to_owned(), times);
+        }
+
+        fn check(&self, addr: &JsonPath) -> Result<(), JsonValueStoreError> {
+            match self.failures.lock().unwrap().get_mut(&addr.to_string()) {
+                Some(n) if *n > 0 => {
+                    *n -= 1;
+                    Err(LocatedJsonStoreError::CustomError(addr.to_string()))
+                }
+                _ => Ok(()),
+            }
+        }
+    }
+
+    impl Store for FlakyStore {
+        type Error = JsonValueStoreError;
+
+        type RootAddress = JsonPath;
+    }
+
+    impl Addressable<JsonPath> for FlakyStore {
+        type DefaultValue = serde_json::Value;
+    }
+
+    impl AddressableGet<serde_json::Value, JsonPath> for FlakyStore {
+        async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<serde_json::Value>, Self> {
+            self.check(addr)?;
+
+            self.inner.sub(addr.clone()).getv().await
+        }
+    }
+
+    impl AddressableSet<serde_json::Value, JsonPath> for FlakyStore {
+        async fn set_addr(
+            &self,
+            addr: &JsonPath,
+            value: &Option<serde_json::Value>,
+        ) -> StoreResult<(), Self> {
+            self.check(addr)?;
+
+            self.inner.set_addr(addr, value).await
+        }
+    }
+
+    impl<'a> AddressableList<'a, JsonPath> for FlakyStore {
+        type AddedAddress = JsonPathPart;
+
+        type ItemAddress = JsonPath;
+
+        fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
+            match self.check(addr) {
+                Ok(()) => self.inner.list(addr),
+                Err(e) => stream::iter([Err(e)]).boxed_local(),
+            }
+        }
+    }
+
+    impl<'a> AddressableTree<'a, JsonPath, JsonPath> for FlakyStore {
+        async fn branch_or_leaf(
+            &self,
+            addr: JsonPath,
+        ) -> StoreResult<BranchOrLeaf<JsonPath, JsonPath>, Self> {
+            self.inner.branch_or_leaf(addr).await
+        }
+    }
+
+    /// Works like the JSON store, but lists the items in the reverse order,
+    /// for the code that must not rely on the listings being sorted.
+    #[derive(Clone)]
+    pub struct ReversedStore {
+        pub inner: JsonValueStore,
+    }
+
+    impl Store for ReversedStore {
+        type Error = JsonValueStoreError;
+
+        type RootAddress = JsonPath;
+    }
+
+    impl Addressable<JsonPath> for ReversedStore {
+        type DefaultValue = serde_json::Value;
+    }
+
+    impl<'a> AddressableList<'a, JsonPath> for ReversedStore {
+        type AddedAddress = JsonPathPart;
+
+        type ItemAddress = JsonPath;
+
+        fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
+            let items = self.inner.list(addr);
+
+            stream::once(async move {
+                let mut items = items.try_collect::<Vec<_>>().await?;
+                items.reverse();
+
+                Ok::<_, JsonValueStoreError>(stream::iter(items.into_iter().map(Ok)))
+            })
+            .try_flatten()
+            .boxed_local()
+        }
+    }
+
+    impl<'a> AddressableTree<'a, JsonPath, JsonPath> for ReversedStore {
+        async fn branch_or_leaf(
+            &self,
+            addr: JsonPath,
+        ) -> StoreResult<BranchOrLeaf<JsonPath, JsonPath>, Self> {
+            self.inner.branch_or_leaf(addr).await
+        }
+    }
+
+    /// Works like the JSON store, and counts the listings.
+    /// Looks the children up with the default [`AddressableChild::child`], by listing them.
+    #[derive(Clone)]
+    pub struct ListCountingStore {
+        pub inner: JsonValueStore,
+        lists: Arc<AtomicUsize>,
+    }
+
+    impl ListCountingStore {
+        pub fn new(inner: JsonValueStore) -> Self {
+            ListCountingStore {
+                inner,
+                lists: Default::default(),
+            }
+        }
+
+        pub fn lists(&self) -> usize {
+            self.lists.load(Ordering::SeqCst)
+        }
+    }
+
+    impl Store for ListCountingStore {
+        type Error = JsonValueStoreError;
+
+        type RootAddress = JsonPath;
+    }
+
+    impl Addressable<JsonPath> for ListCountingStore {
+        type DefaultValue = serde_json::Value;
+    }
+
+    impl AddressableGet<serde_json::Value, JsonPath> for ListCountingStore {
+        async fn addr_get(&self, addr: &JsonPath) -> StoreResult<Option<serde_json::Value>, Self> {
+            AddressableGet::<serde_json::Value, _>::addr_get(&self.inner, addr).await
+        }
+    }
+
+    impl AddressableSet<serde_json::Value, JsonPath> for ListCountingStore {
+        async fn set_addr(
+            &self,
+            addr: &JsonPath,
+            value: &Option<serde_json::Value>,
+        ) -> StoreResult<(), Self> {
+            self.inner.set_addr(addr, value).await
+        }
+    }
+
+    impl<'a> AddressableList<'a, JsonPath> for ListCountingStore {
+        type AddedAddress = JsonPathPart;
+
+        type ItemAddress = JsonPath;
+
+        fn list(&self, addr: &JsonPath) -> Self::ListOfAddressesStream {
+            self.lists.fetch_add(1, Ordering::SeqCst);
+
+            self.inner.list(addr)
+        }
+    }
+
+    impl<'a> AddressableChild<'a, JsonPath> for ListCountingStore {}
+
+    impl<'a> AddressableTree<'a, JsonPath, JsonPath> for ListCountingStore {
+        async fn branch_or_leaf(
+            &self,
+            addr: JsonPath,
+        ) -> StoreResult<BranchOrLeaf<JsonPath, JsonPath>, Self> {
+            self.inner.branch_or_leaf(addr).await
+        }
+    }
+}
@@ -1,2 +1,4 @@
 #[cfg(feature = "ratelimiter")]
 pub mod ratelimiter;
+#[cfg(feature = "retry")]
+pub mod retry;
@@ -0,0 +1,163 @@
+use std::future::Future;
+
+use futures::TryStreamExt;
+use tokio::time::{sleep, Duration};
+
+use crate::{
+    address::{
+        traits::{AddressableGet, AddressableList, AddressableSet},
+        Address, Addressable, SubAddress,
+    },
+    location::Location,
+    store::{Store, StoreResult},
+};
+
+/// How [`RetryingLocation`] retries: up to `attempts` tries in total, waiting `backoff`
+/// after the first failure, and twice as long after every next one, but never longer
+/// than `max_backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub attempts: usize,
+    pub backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// The wait is capped at a minute, see [`with_max_backoff`](RetryPolicy::with_max_backoff).
+    pub fn new(attempts: usize, backoff: Duration) -> RetryPolicy {
+        RetryPolicy {
+            attempts,
+            backoff,
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+
+    pub fn with_max_backoff(self, max_backoff: Duration) -> RetryPolicy {
+        RetryPolicy {
+            max_backoff,
+            ..self
+        }
+    }
+}
+
+/// A location whose operations are retried on errors, see [`Location::with_retry`].
+///
+/// Every error is retried, and the last one is returned when the attempts run out.
+pub struct RetryingLocation<'l, Addr: Address, S: Store + Addressable<Addr>> {
+    location: &'l Location<Addr, S>,
+    policy: RetryPolicy,
+}
+
+impl<'l, Addr: Address, S: Store + Addressable<Addr>> RetryingLocation<'l, Addr, S> {
+    pub fn new(location: &'l Location<Addr, S>, policy: RetryPolicy) -> Self {
+        RetryingLocation { location, policy }
+    }
+
+    /// Run any operation on the location, retrying it per the policy.
+    pub async fn retry<T, F, Fut>(&self, mut op: F) -> StoreResult<T, S>
+    where
+        F: FnMut(&'l Location<Addr, S>) -> Fut,
+        Fut: Future<Output = StoreResult<T, S>>,
+    {
+        let mut backoff = self.policy.backoff.min(self.policy.max_backoff);
+
+        for _ in 1..self.policy.attempts {
+            if let Ok(v) = op(self.location).await {
+                return Ok(v);
+            }
+
+            sleep(backoff).await;
+            backoff = backoff.saturating_mul(2).min(self.policy.max_backoff);
+        }
+
+        op(self.location).await
+    }
+
+    pub async fn get<Value>(&self) -> StoreResult<Option<Value>, S>
+    where
+        S: AddressableGet<Value, Addr>,
+    {
+        self.retry(|location| location.get()).await
+    }
+
+    pub async fn set<Value>(&self, value: &Option<Value>) -> StoreResult<(), S>
+    where
+        S: AddressableSet<Value, Addr>,
+    {
+        self.retry(|location| location.set(value)).await
+    }
+
+    /// The whole listing, collected. It's listed again from the start if any item fails.
+    pub async fn list<'a>(&self) -> StoreResult<Vec<(S::AddedAddress, S::ItemAddress)>, S>
+    where
+        S: 'a + AddressableList<'a, Addr>,
+        Addr: SubAddress<S::AddedAddress, Output = S::ItemAddress>,
+    {
+        self.retry(|location| location.list().try_collect()).await
+    }
+}
+
+impl<'l, V, Addr: Address, S: Store + Addressable<Addr, DefaultValue = V>>
+    RetryingLocation<'l, Addr, S>
+{
+    pub async fn getv(&self) -> StoreResult<Option<V>, S>
+    where
+        S: AddressableGet<V, Addr>,
+    {
+        self.get().await
+    }
+
+    pub async fn setv(&self, value: &Option<V>) -> StoreResult<(), S>
+    where
+        S: AddressableSet<V, Addr>,
+    {
+        self.set(value).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use serde_json::json;
+    use tokio::time::Duration;
+
+    use crate::{store::StoreEx, stores::json::json_value_store, test_util::FlakyStore};
+
+    use super::RetryPolicy;
+
+    #[tokio::test]
+    async fn test_retry() -> Result<(), anyhow::Error> {
+        let store = FlakyStore::new(json_value_store(json!({"a": {"x": 1, "y": 2}}))?);
+        let location = store.path("a")?;
+        let retrying = location.with_retry(RetryPolicy::new(2, Duration::from_millis(1)));
+
+        store.fail_at("a", 1);
+        assert_eq!(retrying.getv().await?, Some(json!({"x": 1, "y": 2})));
+
+        store.fail_at("a", 1);
+        retrying.setv(&Some(json!({"z": 3}))).await?;
+        assert_eq!(location.getv().await?, Some(json!({"z": 3})));
+
+        store.fail_at("a", 1);
+        assert_eq!(retrying.list().await?.len(), 1);
+
+        store.fail_at("a", 2);
+        assert!(retrying.getv().await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_max_backoff() -> Result<(), anyhow::Error> {
+        let store = FlakyStore::new(json_value_store(json!({"a": 1}))?);
+        let location = store.path("a")?;
+        let retrying = location.with_retry(
+            RetryPolicy::new(100, Duration::MAX).with_max_backoff(Duration::from_millis(1)),
+        );
+
+        store.fail_at("a", 99);
+        assert_eq!(retrying.getv().await?, Some(json!(1)));
+
+        Ok(())
+    }
+}
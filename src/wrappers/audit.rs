@@ -0,0 +1,368 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use derive_more::Display;
+use futures::{StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{
+    address::{
+        traits::{
+            AddressableGet, AddressableInsert, AddressableList, AddressableSet, AddressableTree,
+            BranchOrLeaf,
+        },
+        Address, Addressable, SubAddress,
+    },
+    location::Location,
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+#[derive(Display, Debug)]
+pub enum AuditWrapperError<E> {
+    StoreError(E),
+    /// Writing to the audit log failed, and [`AuditOptions::fail_on_error`] is set.
+    AuditError(Box<dyn std::error::Error + Send + Sync>),
+}
+
+impl<E> From<E> for AuditWrapperError<E> {
+    fn from(value: E) -> Self {
+        Self::StoreError(value)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for AuditWrapperError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::StoreError(e) => Some(e),
+            Self::AuditError(e) => Some(e.as_ref()),
+        }
+    }
+
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        if let Self::StoreError(e) = self {
+            request.provide_ref::<E>(e);
+            e.provide(request);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOp {
+    Get,
+    Set,
+    Delete,
+    Insert,
+}
+
+/// One entry of the audit log, as it's inserted into the log store (serialized to JSON).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub op: AuditOp,
+    pub address_parts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AuditOptions {
+    /// Also record reads, not only writes.
+    pub audit_reads: bool,
+    /// Fail the operation if its record can't be written.
+    /// By default such failures are ignored.
+    ///
+    /// With this, writes are recorded before they are made, so a write that fails
+    /// the audit isn't made at all. A write that fails in the store itself is recorded anyway.
+    pub fail_on_error: bool,
+}
+
+/// Wrap this over a store to record every write into another store,
+/// e.g. to keep an audit trail that the app itself can query.
+///
+/// Every successful write is inserted as an [`AuditRecord`] into the `log` location,
+/// which has to support inserting JSON values (like a JSON array). With
+/// [`AuditOptions::fail_on_error`], every attempted write is recorded before it's made.
+///
+/// ```
+/// use serde_json::json;
+///
+/// use anystore::stores::json::*;
+/// use anystore::store::StoreEx;
+/// use anystore::wrappers::audit::AuditWrapperStore;
+///
+/// # tokio_test::block_on(async {
+/// let log = json_value_store(json!([]))?;
+/// let store = AuditWrapperStore::new(json_value_store(json!({}))?, log.root());
+///
+/// store.path("a.b")?.setv(&Some(json!(1))).await?;
+///
+/// assert_eq!(log.path("[0].op")?.getv().await?, Some(json!("set")));
+/// assert_eq!(log.path("[0].address_parts")?.getv().await?, Some(json!([".a", ".b"])));
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+#[derive(Clone)]
+pub struct AuditWrapperStore<S: Store, LA: Address, L: Store + Addressable<LA>> {
+    underlying: S,
+    log: Location<LA, L>,
+    options: AuditOptions,
+}
+
+impl<S: Store, LA: Address, L: Store + Addressable<LA>> AuditWrapperStore<S, LA, L> {
+    pub fn new(underlying: S, log: Location<LA, L>) -> Self {
+        Self::new_with_options(underlying, log, AuditOptions::default())
+    }
+
+    pub fn new_with_options(underlying: S, log: Location<LA, L>, options: AuditOptions) -> Self {
+        AuditWrapperStore {
+            underlying,
+            log,
+            options,
+        }
+    }
+
+    pub fn destruct(self) -> (S, Location<LA, L>) {
+        (self.underlying, self.log)
+    }
+}
+
+impl<S: Store, LA, L> AuditWrapperStore<S, LA, L>
+where
+    LA: Address + SubAddress<L::AddedAddress, Output = L::ItemAddress>,
+    L: 'static + AddressableInsert<'static, Value, LA>,
+{
+    async fn audit(&self, op: AuditOp, address_parts: Vec<String>) -> StoreResult<(), Self> {
+        let record = AuditRecord {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            op,
+            address_parts,
+        };
+
+        let written: Result<_, Box<dyn std::error::Error + Send + Sync>> =
+            match serde_json::to_value(record) {
+                Ok(record) => self
+                    .log
+                    .insert(vec![record])
+                    .try_collect::<Vec<_>>()
+                    .await
+                    .map_err(Into::into),
+                Err(e) => Err(e.into()),
+            };
+
+        match written {
+            Err(e) if self.options.fail_on_error => Err(AuditWrapperError::AuditError(e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<S: Store, LA: Address, L: Store + Addressable<LA>> Store for AuditWrapperStore<S, LA, L> {
+    type Error = AuditWrapperError<S::Error>;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.underlying.root_kind()
+    }
+
+    /// Flushes the audit log too.
+    async fn flush(&self) -> StoreResult<(), Self> {
+        self.underlying.flush().await?;
+
+        match self.log.store.flush().await {
+            Err(e) if self.options.fail_on_error => Err(AuditWrapperError::AuditError(e.into())),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<A: Address, S: Addressable<A>, LA: Address, L: Store + Addressable<LA>> Addressable<A>
+    for AuditWrapperStore<S, LA, L>
+{
+    type DefaultValue = S::DefaultValue;
+}
+
+impl<V, A: Address, S: AddressableGet<V, A>, LA, L> AddressableGet<V, A>
+    for AuditWrapperStore<S, LA, L>
+where
+    LA: Address + SubAddress<L::AddedAddress, Output = L::ItemAddress>,
+    L: 'static + AddressableInsert<'static, Value, LA>,
+{
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        let value = self.underlying.addr_get(addr).await?;
+
+        if self.options.audit_reads {
+            self.audit(AuditOp::Get, addr.as_parts()).await?;
+        }
+
+        Ok(value)
+    }
+}
+
+impl<V, A: Address, S: AddressableSet<V, A>, LA, L> AddressableSet<V, A>
+    for AuditWrapperStore<S, LA, L>
+where
+    LA: Address + SubAddress<L::AddedAddress, Output = L::ItemAddress>,
+    L: 'static + AddressableInsert<'static, Value, LA>,
+{
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        let op = match value {
+            Some(_) => AuditOp::Set,
+            None => AuditOp::Delete,
+        };
+
+        if self.options.fail_on_error {
+            // so an `AuditError` means that nothing was written
+            self.audit(op, addr.as_parts()).await?;
+
+            return Ok(self.underlying.set_addr(addr, value).await?);
+        }
+
+        self.underlying.set_addr(addr, value).await?;
+
+        self.audit(op, addr.as_parts()).await
+    }
+}
+
+impl<
+        'a,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableList<'a, A, ItemAddress = Whole> + 'a,
+        LA: Address,
+        L: 'a + Store + Addressable<LA>,
+    > AddressableList<'a, A> for AuditWrapperStore<S, LA, L>
+{
+    type AddedAddress = S::AddedAddress;
+
+    type ItemAddress = S::ItemAddress;
+
+    fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        self.underlying
+            .list(addr)
+            .map_err(AuditWrapperError::StoreError)
+            .boxed_local()
+    }
+}
+
+/// Every inserted item is recorded with its own address.
+impl<
+        'a,
+        V,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableInsert<'a, V, A, ItemAddress = Whole> + 'a,
+        LA: Address + SubAddress<L::AddedAddress, Output = L::ItemAddress>,
+        L: 'static + AddressableInsert<'static, Value, LA>,
+    > AddressableInsert<'a, V, A> for AuditWrapperStore<S, LA, L>
+{
+    fn insert(&self, addr: &A, items: Vec<V>) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+
+        self.underlying
+            .insert(addr, items)
+            .map_err(AuditWrapperError::StoreError)
+            .and_then(move |(added, item)| {
+                let this = this.clone();
+
+                async move {
+                    this.audit(AuditOp::Insert, item.as_parts()).await?;
+                    Ok((added, item))
+                }
+            })
+            .boxed_local()
+    }
+}
+
+impl<
+        'a,
+        LA: SubAddress<S::AddedAddress, Output = LA>,
+        IA,
+        S: 'a + AddressableTree<'a, LA, IA>,
+        LogA: Address,
+        L: 'a + Store + Addressable<LogA>,
+    > AddressableTree<'a, LA, IA> for AuditWrapperStore<S, LogA, L>
+{
+    async fn branch_or_leaf(&self, addr: LA) -> StoreResult<BranchOrLeaf<LA, IA>, Self> {
+        Ok(self.underlying.branch_or_leaf(addr).await?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use crate::{store::StoreEx, stores::json::json_value_store};
+
+    use super::{AuditOptions, AuditWrapperError, AuditWrapperStore};
+
+    #[tokio::test]
+    async fn test_audit() -> Result<(), anyhow::Error> {
+        let underlying = json_value_store(json!({"a": 1}))?;
+        let log = json_value_store(json!([]))?;
+
+        let store = AuditWrapperStore::new_with_options(
+            underlying.clone(),
+            log.root(),
+            AuditOptions {
+                audit_reads: true,
+                ..Default::default()
+            },
+        );
+
+        assert_eq!(store.path("a")?.getv().await?, Some(json!(1)));
+        store.path("b.c")?.setv(&Some(json!(2))).await?;
+        store.path("a")?.setv(&None).await?;
+
+        assert_eq!(
+            underlying.root().getv().await?,
+            Some(json!({"b": {"c": 2}}))
+        );
+
+        let records = log.root().getv().await?.unwrap();
+        let records = records.as_array().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0]["op"], json!("get"));
+        assert_eq!(records[1]["op"], json!("set"));
+        assert_eq!(records[1]["address_parts"], json!([".b", ".c"]));
+        assert_eq!(records[2]["op"], json!("delete"));
+        assert!(records[2]["timestamp"].as_u64().unwrap() > 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_audit_failure() -> Result<(), anyhow::Error> {
+        // inserting into a number fails
+        let log = json_value_store(json!(7))?;
+
+        let lenient = AuditWrapperStore::new(json_value_store(json!({}))?, log.root());
+        lenient.path("a")?.setv(&Some(json!(1))).await?;
+        assert_eq!(lenient.path("a")?.getv().await?, Some(json!(1)));
+
+        let underlying = json_value_store(json!({}))?;
+        let strict = AuditWrapperStore::new_with_options(
+            underlying.clone(),
+            log.root(),
+            AuditOptions {
+                fail_on_error: true,
+                ..Default::default()
+            },
+        );
+        assert!(matches!(
+            strict.path("a")?.setv(&Some(json!(1))).await,
+            Err(AuditWrapperError::AuditError(_))
+        ));
+        assert_eq!(underlying.root().getv().await?, Some(json!({})));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use std::{
+    marker::PhantomData,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use tokio::{
+    sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    task::JoinHandle,
+};
+
+use crate::{
+    address::{
+        traits::{AddressableGet, AddressableSet},
+        Address, Addressable,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+struct Pending<A, V> {
+    addr: A,
+    generation: u64,
+    value: Option<V>,
+}
+
+enum Signal {
+    Write,
+    Stop,
+}
+
+struct DebounceState<S: Store, A, V> {
+    underlying: S,
+    pending: std::sync::Mutex<Vec<Pending<A, V>>>,
+    generation: AtomicU64,
+    flushing: tokio::sync::Mutex<()>,
+    error: std::sync::Mutex<Option<S::Error>>,
+}
+
+impl<S: AddressableSet<V, A>, A: Address, V: Clone> DebounceState<S, A, V> {
+    async fn flush(&self) -> StoreResult<(), S> {
+        self.flush_where(|_| true).await
+    }
+
+    async fn flush_where(&self, matches: impl Fn(&A) -> bool) -> StoreResult<(), S> {
+        let _flushing = self.flushing.lock().await;
+
+        let batch = self
+            .pending
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| matches(&p.addr))
+            .map(|p| (p.addr.clone(), p.generation, p.value.clone()))
+            .collect::<Vec<_>>();
+
+        for (addr, generation, value) in batch {
+            self.underlying.set_addr(&addr, &value).await?;
+
+            // keep it if it was overwritten while we were writing
+            self.pending
+                .lock()
+                .unwrap()
+                .retain(|p| !(p.addr == addr && p.generation == generation));
+        }
+
+        Ok(())
+    }
+
+    async fn run(self: Arc<Self>, mut signals: UnboundedReceiver<Signal>, quiet: Duration) {
+        while let Some(Signal::Write) = signals.recv().await {
+            // wait until nothing was written for `quiet`
+            let stop = loop {
+                match tokio::time::timeout(quiet, signals.recv()).await {
+                    Ok(Some(Signal::Write)) => continue,
+                    Ok(Some(Signal::Stop)) | Ok(None) => break true,
+                    Err(_) => break false,
+                }
+            };
+
+            if stop {
+                break;
+            }
+
+            if let Err(e) = self.flush().await {
+                *self.error.lock().unwrap() = Some(e);
+            }
+        }
+
+        // stopped, or all the handles are dropped
+        if let Err(e) = self.flush().await {
+            *self.error.lock().unwrap() = Some(e);
+        }
+    }
+}
+
+/// Wrap this over a store to coalesce rapid writes of `V` to addresses of type `A`.
+///
+/// Writes are buffered per address, and flushed to the underlying store after nothing was
+/// written for `quiet`, on [`flush`](Store::flush), on [`shutdown`](DebounceWrapperStore::shutdown),
+/// or when the last clone is dropped. Reads see the pending values before they are flushed.
+///
+/// Flushing happens in a background task on the runtime that created the wrapper.
+/// If a background flush fails, the error is returned by the next operation on the wrapper.
+/// The flush on drop can't be awaited: call `shutdown` before exiting to be sure
+/// that everything is written.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// use anystore::address::primitive::UniqueRootAddress;
+/// use anystore::store::{Store, StoreEx};
+/// use anystore::stores::cell::MemoryCellStore;
+/// use anystore::wrappers::debounce::DebounceWrapperStore;
+///
+/// # tokio_test::block_on(async {
+/// let cell = MemoryCellStore::new(None);
+/// let store =
+///     DebounceWrapperStore::<_, UniqueRootAddress, String>::new(cell.clone(), Duration::from_millis(50));
+///
+/// for i in 0..10 {
+///     store.root().set(&Some(i.to_string())).await?;
+/// }
+///
+/// assert_eq!(store.root().get::<String>().await?, Some("9".to_owned()));
+///
+/// store.flush().await?;
+/// assert_eq!(cell.root().get::<String>().await?, Some("9".to_owned()));
+/// # Ok::<(), anystore::stores::cell::MemoryCellStoreError>(())
+/// # }).unwrap()
+/// ```
+pub struct DebounceWrapperStore<S: Store, A, V> {
+    state: Arc<DebounceState<S, A, V>>,
+    signals: UnboundedSender<Signal>,
+    worker: Arc<std::sync::Mutex<Option<JoinHandle<()>>>>,
+    phantom: PhantomData<fn(A, V)>,
+}
+
+impl<S: Store, A, V> Clone for DebounceWrapperStore<S, A, V> {
+    fn clone(&self) -> Self {
+        Self {
+            state: self.state.clone(),
+            signals: self.signals.clone(),
+            worker: self.worker.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S, A, V> DebounceWrapperStore<S, A, V>
+where
+    S: AddressableSet<V, A> + Send + Sync + 'static,
+    A: Address + Send + Sync,
+    V: Clone + Send + Sync + 'static,
+{
+    /// Must be called within a Tokio runtime: the writes are flushed by a task spawned on it,
+    /// so the underlying store's writes must be `Send`. The task is dropped with the runtime,
+    /// so [`shutdown`](Self::shutdown) the wrapper before, or the pending writes are lost.
+    ///
+    /// The compiler can't check that before it knows `A` and `V`, so name them
+    /// if the underlying store can be written at several addresses or values.
+    pub fn new(underlying: S, quiet: Duration) -> Self
+    where
+        S: AddressableSet<V, A, set_addr(..): Send>,
+    {
+        let (signals, rx) = unbounded_channel();
+
+        let state = Arc::new(DebounceState {
+            underlying,
+            pending: Default::default(),
+            generation: AtomicU64::new(0),
+            flushing: Default::default(),
+            error: Default::default(),
+        });
+
+        let worker = tokio::spawn(state.clone().run(rx, quiet));
+
+        DebounceWrapperStore {
+            state,
+            signals,
+            worker: Arc::new(std::sync::Mutex::new(Some(worker))),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Stops the background flushing and waits until all the pending writes are written.
+    ///
+    /// After that, the writes through the other clones are only written on [`flush`](Store::flush).
+    pub async fn shutdown(self) -> StoreResult<(), Self> {
+        let worker = self.worker.lock().unwrap().take();
+
+        if let Some(worker) = worker {
+            let _ = self.signals.send(Signal::Stop);
+
+            if let Err(e) = worker.await {
+                std::panic::resume_unwind(e.into_panic());
+            }
+        }
+
+        self.take_error()?;
+
+        // anything written while it was stopping
+        self.state.flush().await?;
+        self.state.underlying.flush().await
+    }
+
+    /// Write the pending value of `addr`, if there's one, to the underlying store now.
+    /// Waits for a flush that's in progress, so nothing older is written after it.
+    pub async fn flush_addr(&self, addr: &A) -> StoreResult<(), Self> {
+        self.take_error()?;
+
+        self.state.flush_where(|a| a == addr).await
+    }
+
+    fn take_error(&self) -> StoreResult<(), Self> {
+        match self.state.error.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<S, A, V> Store for DebounceWrapperStore<S, A, V>
+where
+    S: AddressableSet<V, A> + Send + Sync + 'static,
+    A: Address + Send + Sync,
+    V: Clone + Send + Sync + 'static,
+{
+    type Error = S::Error;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.state.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.state.underlying.root_kind()
+    }
+
+    /// Writes all the pending values to the underlying store now, then flushes it.
+    async fn flush(&self) -> StoreResult<(), Self> {
+        self.take_error()?;
+
+        self.state.flush().await?;
+        self.state.underlying.flush().await
+    }
+}
+
+impl<S, A, V> Addressable<A> for DebounceWrapperStore<S, A, V>
+where
+    S: AddressableSet<V, A> + Send + Sync + 'static,
+    A: Address + Send + Sync,
+    V: Clone + Send + Sync + 'static,
+{
+    type DefaultValue = S::DefaultValue;
+}
+
+impl<S, A, V> AddressableGet<V, A> for DebounceWrapperStore<S, A, V>
+where
+    S: AddressableGet<V, A> + AddressableSet<V, A> + Send + Sync + 'static,
+    A: Address + Send + Sync,
+    V: Clone + Send + Sync + 'static,
+{
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        self.take_error()?;
+
+        let pending = self
+            .state
+            .pending
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|p| &p.addr == addr)
+            .map(|p| p.value.clone());
+
+        match pending {
+            Some(value) => Ok(value),
+            None => self.state.underlying.addr_get(addr).await,
+        }
+    }
+}
+
+impl<S, A, V> AddressableSet<V, A> for DebounceWrapperStore<S, A, V>
+where
+    S: AddressableSet<V, A> + Send + Sync + 'static,
+    A: Address + Send + Sync,
+    V: Clone + Send + Sync + 'static,
+{
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        self.take_error()?;
+
+        let generation = self.state.generation.fetch_add(1, Ordering::Relaxed);
+
+        {
+            let mut pending = self.state.pending.lock().unwrap();
+            pending.retain(|p| &p.addr != addr);
+            pending.push(Pending {
+                addr: addr.clone(),
+                generation,
+                value: value.clone(),
+            });
+        }
+
+        // fails only after a shutdown
+        let _ = self.signals.send(Signal::Write);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use crate::{
+        store::{Store, StoreEx},
+        test_util::CountingStore,
+    };
+
+    use super::DebounceWrapperStore;
+
+    #[tokio::test]
+    async fn test_debounce() -> Result<(), anyhow::Error> {
+        let underlying = CountingStore::default();
+        let store = DebounceWrapperStore::new(underlying.clone(), Duration::from_secs(60));
+        let root = store.root();
+
+        for i in 0..100 {
+            root.set(&Some(i.to_string())).await?;
+        }
+
+        assert_eq!(root.getv().await?, Some("99".to_owned()));
+        assert_eq!(underlying.writes(), 0);
+
+        store.flush().await?;
+
+        assert_eq!(underlying.writes(), 1);
+        assert_eq!(underlying.root().getv().await?, Some("99".to_owned()));
+
+        root.set(&Some("last".to_owned())).await?;
+        store.shutdown().await?;
+
+        assert_eq!(underlying.writes(), 2);
+        assert_eq!(underlying.root().getv().await?, Some("last".to_owned()));
+
+        Ok(())
+    }
+}
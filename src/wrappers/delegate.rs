@@ -0,0 +1,282 @@
+#[doc(hidden)]
+pub use futures;
+
+use crate::store::Store;
+
+/// A store wrapped over another one. Implemented by [`delegate_store!`](crate::delegate_store).
+pub trait WrapperStore {
+    type Inner: Store;
+
+    fn inner(&self) -> &Self::Inner;
+
+    fn into_inner(self) -> Self::Inner;
+}
+
+/// Implements [`WrapperStore`] and the trivial parts of a wrapper: the traits
+/// that just pass everything to the inner store. Implement the rest by hand,
+/// i.e. the methods whose behavior the wrapper changes.
+///
+/// The header names the wrapper's generics, the field with the inner store, and,
+/// optionally, the bounds shared by all the impls. Each of the following lines delegates a trait:
+///
+/// - `Clone(field, ...)` -- clones all the listed fields; list all of them
+/// - `Store` or `Store(ErrorType)` -- the wrapper's error type defaults to the inner one;
+///   it needs `From` the inner one otherwise
+/// - `Addressable`, `AddressableGet`, `AddressableSet`, `AddressableDelete`
+/// - `AddressableList` -- for any address `A`, items are `Whole`
+/// - `AddressableTree` -- for any `<'a, LA, IA>`
+///
+/// Each line can add bounds with `where [...]`, which can use the generics named above.
+/// The wrapper's generics can't have lifetimes.
+///
+#[cfg_attr(not(feature = "json"), doc = "```ignore")]
+#[cfg_attr(feature = "json", doc = "```")]
+/// use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+///
+/// use serde_json::json;
+///
+/// use anystore::address::{traits::AddressableSet, Address};
+/// use anystore::delegate_store;
+/// use anystore::store::{Store, StoreEx, StoreResult};
+/// use anystore::stores::json::*;
+/// use anystore::wrappers::delegate::WrapperStore;
+///
+/// /// Counts the writes.
+/// struct CountingStore<S: Store> {
+///     underlying: S,
+///     writes: Arc<AtomicUsize>,
+/// }
+///
+/// delegate_store! {
+///     impl[S: Store] CountingStore<S> { underlying: S };
+///     Clone(underlying, writes);
+///     Store;
+///     Addressable;
+///     AddressableGet;
+///     AddressableList;
+///     AddressableTree;
+/// }
+///
+/// impl<V, A: Address, S: AddressableSet<V, A>> AddressableSet<V, A> for CountingStore<S> {
+///     async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+///         self.writes.fetch_add(1, Ordering::SeqCst);
+///         self.underlying.set_addr(addr, value).await
+///     }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let store = CountingStore {
+///     underlying: json_value_store(json!({"a": 1}))?,
+///     writes: Default::default(),
+/// };
+///
+/// store.path("b")?.setv(&Some(json!(2))).await?;
+///
+/// assert_eq!(store.path("b")?.getv().await?, Some(json!(2)));
+/// assert_eq!(store.writes.load(Ordering::SeqCst), 1);
+/// assert_eq!(store.inner().path("a")?.getv().await?, Some(json!(1)));
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+#[macro_export]
+macro_rules! delegate_store {
+    (
+        impl[$($g:tt)*] $w:ty { $field:ident : $inner:ty } $(where [$($b:tt)*])?;
+        $($arms:tt)*
+    ) => {
+        $crate::delegate_store!(@arm WrapperStore [$($g)*] [$w] [$field $inner] [$($($b)*)?] []);
+        $crate::delegate_store!(@arms [$($g)*] [$w] [$field $inner] [$($($b)*)?] $($arms)*);
+    };
+
+    (@arms $g:tt $w:tt $i:tt $b:tt) => {};
+    (
+        @arms $g:tt $w:tt $i:tt [$($b:tt)*]
+        $arm:ident $(($($args:tt)*))? $(where [$($extra:tt)*])?;
+        $($rest:tt)*
+    ) => {
+        $crate::delegate_store!(@merge $arm $g $w $i [$($b)*] [$($($extra)*)?] [$($($args)*)?]);
+        $crate::delegate_store!(@arms $g $w $i [$($b)*] $($rest)*);
+    };
+
+    // joins the shared bounds and the ones of the line
+    (@merge $arm:ident $g:tt $w:tt $i:tt [$($b:tt)*] [] $args:tt) => {
+        $crate::delegate_store!(@arm $arm $g $w $i [$($b)*] $args);
+    };
+    (@merge $arm:ident $g:tt $w:tt $i:tt [] [$($e:tt)*] $args:tt) => {
+        $crate::delegate_store!(@arm $arm $g $w $i [$($e)*] $args);
+    };
+    (@merge $arm:ident $g:tt $w:tt $i:tt [$($b:tt)*] [$($e:tt)*] $args:tt) => {
+        $crate::delegate_store!(@arm $arm $g $w $i [$($b)*, $($e)*] $args);
+    };
+
+    (@arm WrapperStore [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] []) => {
+        impl<$($g)*> $crate::wrappers::delegate::WrapperStore for $w
+        where
+            $inner: $crate::store::Store,
+            $($b)*
+        {
+            type Inner = $inner;
+
+            fn inner(&self) -> &$inner {
+                &self.$field
+            }
+
+            fn into_inner(self) -> $inner {
+                self.$field
+            }
+        }
+    };
+
+    (@arm Clone [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] [$($f:ident),* $(,)?]) => {
+        impl<$($g)*> Clone for $w
+        where
+            $inner: $crate::store::Store,
+            $($b)*
+        {
+            fn clone(&self) -> Self {
+                Self {
+                    $($f: self.$f.clone()),*
+                }
+            }
+        }
+    };
+
+    (@arm Store $g:tt $w:tt [$field:ident $inner:ty] $b:tt []) => {
+        $crate::delegate_store!(@arm Store $g $w [$field $inner] $b [<$inner as $crate::store::Store>::Error]);
+    };
+    (@arm Store [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] [$error:ty]) => {
+        impl<$($g)*> $crate::store::Store for $w
+        where
+            $inner: $crate::store::Store,
+            $($b)*
+        {
+            type Error = $error;
+
+            type RootAddress = <$inner as $crate::store::Store>::RootAddress;
+
+            fn capabilities(&self) -> $crate::store::Capabilities {
+                self.$field.capabilities()
+            }
+
+            fn root_kind(&self) -> $crate::store::RootKind {
+                self.$field.root_kind()
+            }
+
+            #[allow(clippy::needless_question_mark)]
+            async fn flush(&self) -> $crate::store::StoreResult<(), Self> {
+                Ok(self.$field.flush().await?)
+            }
+        }
+    };
+
+    (@arm Addressable [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] []) => {
+        impl<A: $crate::address::Address, $($g)*> $crate::address::Addressable<A> for $w
+        where
+            $inner: $crate::address::Addressable<A>,
+            $($b)*
+        {
+            type DefaultValue = <$inner as $crate::address::Addressable<A>>::DefaultValue;
+        }
+    };
+
+    (@arm AddressableGet [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] []) => {
+        impl<V, A: $crate::address::Address, $($g)*> $crate::address::traits::AddressableGet<V, A>
+            for $w
+        where
+            $inner: $crate::address::traits::AddressableGet<V, A>,
+            $($b)*
+        {
+            // the `?` converts the error when the wrapper has its own
+            #[allow(clippy::needless_question_mark)]
+            async fn addr_get(&self, addr: &A) -> $crate::store::StoreResult<Option<V>, Self> {
+                Ok(self.$field.addr_get(addr).await?)
+            }
+        }
+    };
+
+    (@arm AddressableSet [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] []) => {
+        impl<V, A: $crate::address::Address, $($g)*> $crate::address::traits::AddressableSet<V, A>
+            for $w
+        where
+            $inner: $crate::address::traits::AddressableSet<V, A>,
+            $($b)*
+        {
+            #[allow(clippy::needless_question_mark)]
+            async fn set_addr(
+                &self,
+                addr: &A,
+                value: &Option<V>,
+            ) -> $crate::store::StoreResult<(), Self> {
+                Ok(self.$field.set_addr(addr, value).await?)
+            }
+        }
+    };
+
+    (@arm AddressableDelete [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] []) => {
+        impl<A: $crate::address::Address, $($g)*> $crate::address::traits::AddressableDelete<A>
+            for $w
+        where
+            $inner: $crate::address::traits::AddressableDelete<A>,
+            $($b)*
+        {
+            #[allow(clippy::needless_question_mark)]
+            async fn delete(&self, addr: &A) -> $crate::store::StoreResult<(), Self> {
+                Ok(self.$field.delete(addr).await?)
+            }
+        }
+    };
+
+    (@arm AddressableList [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] []) => {
+        impl<
+                'a,
+                Whole: $crate::address::Address,
+                A: $crate::address::Address
+                    + $crate::address::SubAddress<
+                        <$inner as $crate::address::traits::AddressableList<'a, A>>::AddedAddress,
+                        Output = Whole,
+                    >,
+                $($g)*
+            > $crate::address::traits::AddressableList<'a, A> for $w
+        where
+            $inner: 'a + $crate::address::traits::AddressableList<'a, A, ItemAddress = Whole>,
+            $($b)*
+        {
+            type AddedAddress =
+                <$inner as $crate::address::traits::AddressableList<'a, A>>::AddedAddress;
+
+            type ItemAddress = Whole;
+
+            fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+                use $crate::wrappers::delegate::futures::{StreamExt, TryStreamExt};
+
+                self.$field.list(addr).map_err(Into::into).boxed_local()
+            }
+        }
+    };
+
+    (@arm AddressableTree [$($g:tt)*] [$w:ty] [$field:ident $inner:ty] [$($b:tt)*] []) => {
+        impl<
+                'a,
+                LA: $crate::address::SubAddress<
+                    <$inner as $crate::address::traits::AddressableList<'a, LA>>::AddedAddress,
+                    Output = LA,
+                >,
+                IA,
+                $($g)*
+            > $crate::address::traits::AddressableTree<'a, LA, IA> for $w
+        where
+            $inner: 'a + $crate::address::traits::AddressableTree<'a, LA, IA>,
+            $($b)*
+        {
+            #[allow(clippy::needless_question_mark)]
+            async fn branch_or_leaf(
+                &self,
+                addr: LA,
+            ) -> $crate::store::StoreResult<$crate::address::traits::BranchOrLeaf<LA, IA>, Self>
+            {
+                Ok(self.$field.branch_or_leaf(addr).await?)
+            }
+        }
+    };
+}
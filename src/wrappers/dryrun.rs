@@ -0,0 +1,255 @@
+use std::sync::{Arc, Mutex};
+
+use futures::{stream, StreamExt};
+
+use crate::{
+    address::{
+        traits::{
+            AddressableGet, AddressableInsert, AddressableList, AddressableSet, AddressableTree,
+            BranchOrLeaf,
+        },
+        Address, Addressable, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+/// A write that [`DryRunWrapperStore`] didn't apply.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlannedChange<V> {
+    Set {
+        address_parts: Vec<String>,
+        value: V,
+    },
+    Delete {
+        address_parts: Vec<String>,
+    },
+    Insert {
+        address_parts: Vec<String>,
+        values: Vec<V>,
+    },
+}
+
+/// Wrap this over a store to preview the writes: they are recorded instead of
+/// being applied, and can be shown with [`planned_changes`](DryRunWrapperStore::planned_changes).
+/// Reads and listings go to the underlying store, so they don't see the planned writes.
+///
+/// Inserts yield no addresses, as the items were never created.
+///
+/// The planned values are kept as they are, so only writes of one value type `V`
+/// can be planned.
+///
+#[cfg_attr(not(feature = "json"), doc = "```ignore")]
+#[cfg_attr(feature = "json", doc = "```")]
+/// use serde_json::json;
+///
+/// use anystore::stores::json::*;
+/// use anystore::store::StoreEx;
+/// use anystore::wrappers::dryrun::{DryRunWrapperStore, PlannedChange};
+///
+/// # tokio_test::block_on(async {
+/// let store = json_value_store(json!({"a": 1}))?;
+/// let dry = DryRunWrapperStore::new(store.clone());
+///
+/// dry.path("a")?.setv(&Some(json!(2))).await?;
+/// dry.path("b")?.setv(&None).await?;
+///
+/// assert_eq!(store.path("a")?.getv().await?, Some(json!(1)));
+/// assert_eq!(
+///     dry.planned_changes(),
+///     vec![
+///         PlannedChange::Set { address_parts: vec![".a".to_owned()], value: json!(2) },
+///         PlannedChange::Delete { address_parts: vec![".b".to_owned()] },
+///     ]
+/// );
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+pub struct DryRunWrapperStore<S: Store, V> {
+    underlying: S,
+    planned: Arc<Mutex<Vec<PlannedChange<V>>>>,
+}
+
+impl<S: Store, V> Clone for DryRunWrapperStore<S, V> {
+    fn clone(&self) -> Self {
+        Self {
+            underlying: self.underlying.clone(),
+            planned: self.planned.clone(),
+        }
+    }
+}
+
+impl<S: Store, V> DryRunWrapperStore<S, V> {
+    pub fn new(underlying: S) -> Self {
+        DryRunWrapperStore {
+            underlying,
+            planned: Default::default(),
+        }
+    }
+
+    /// The writes made so far, in order. Clones share them.
+    pub fn planned_changes(&self) -> Vec<PlannedChange<V>>
+    where
+        V: Clone,
+    {
+        self.planned.lock().unwrap().clone()
+    }
+
+    /// Get the underlying store, e.g. to apply the changes for real.
+    pub fn destruct(self) -> S {
+        self.underlying
+    }
+
+    fn plan(&self, change: PlannedChange<V>) {
+        self.planned.lock().unwrap().push(change);
+    }
+}
+
+impl<S: Store, V: 'static> Store for DryRunWrapperStore<S, V> {
+    type Error = S::Error;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.underlying.root_kind()
+    }
+
+    async fn flush(&self) -> StoreResult<(), Self> {
+        self.underlying.flush().await
+    }
+}
+
+impl<V: 'static, A: Address, S: Addressable<A>> Addressable<A> for DryRunWrapperStore<S, V> {
+    type DefaultValue = S::DefaultValue;
+}
+
+impl<V: 'static, A: Address, S: AddressableGet<V, A>> AddressableGet<V, A>
+    for DryRunWrapperStore<S, V>
+{
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        self.underlying.addr_get(addr).await
+    }
+}
+
+/// Only records the write: `S` isn't touched.
+impl<V: Clone + 'static, A: Address, S: AddressableSet<V, A>> AddressableSet<V, A>
+    for DryRunWrapperStore<S, V>
+{
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        let address_parts = addr.as_parts();
+
+        self.plan(match value {
+            Some(value) => PlannedChange::Set {
+                address_parts,
+                value: value.clone(),
+            },
+            None => PlannedChange::Delete { address_parts },
+        });
+
+        Ok(())
+    }
+}
+
+impl<
+        'a,
+        V: 'static,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableList<'a, A, ItemAddress = Whole> + 'a,
+    > AddressableList<'a, A> for DryRunWrapperStore<S, V>
+{
+    type AddedAddress = S::AddedAddress;
+
+    type ItemAddress = S::ItemAddress;
+
+    fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        self.underlying.list(addr).boxed_local()
+    }
+}
+
+/// Only records the insert, and yields no addresses.
+impl<
+        'a,
+        V: 'static,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableInsert<'a, V, A, ItemAddress = Whole> + 'a,
+    > AddressableInsert<'a, V, A> for DryRunWrapperStore<S, V>
+{
+    fn insert(&self, addr: &A, items: Vec<V>) -> Self::ListOfAddressesStream {
+        self.plan(PlannedChange::Insert {
+            address_parts: addr.as_parts(),
+            values: items,
+        });
+
+        stream::empty().boxed_local()
+    }
+}
+
+impl<
+        'a,
+        V: 'static,
+        LA: SubAddress<S::AddedAddress, Output = LA>,
+        IA,
+        S: 'a + AddressableTree<'a, LA, IA>,
+    > AddressableTree<'a, LA, IA> for DryRunWrapperStore<S, V>
+{
+    async fn branch_or_leaf(&self, addr: LA) -> StoreResult<BranchOrLeaf<LA, IA>, Self> {
+        self.underlying.branch_or_leaf(addr).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    use crate::{store::StoreEx, stores::json::json_value_store};
+
+    use super::{DryRunWrapperStore, PlannedChange};
+
+    #[tokio::test]
+    async fn test() -> Result<(), anyhow::Error> {
+        let store = json_value_store(json!({"a": 1, "list": []}))?;
+        let dry = DryRunWrapperStore::new(store.clone());
+
+        dry.path("a")?.setv(&Some(json!(2))).await?;
+        dry.path("b")?.setv(&None).await?;
+        let inserted = dry
+            .path("list")?
+            .insert(vec![json!("x")])
+            .try_collect::<Vec<_>>()
+            .await?;
+
+        assert!(inserted.is_empty());
+        assert_eq!(dry.path("a")?.getv().await?, Some(json!(1)));
+        assert_eq!(
+            store.root().getv().await?,
+            Some(json!({"a": 1, "list": []}))
+        );
+
+        assert_eq!(
+            dry.planned_changes(),
+            vec![
+                PlannedChange::Set {
+                    address_parts: vec![".a".to_owned()],
+                    value: json!(2)
+                },
+                PlannedChange::Delete {
+                    address_parts: vec![".b".to_owned()]
+                },
+                PlannedChange::Insert {
+                    address_parts: vec![".list".to_owned()],
+                    values: vec![json!("x")]
+                },
+            ]
+        );
+
+        Ok(())
+    }
+}
@@ -11,32 +11,74 @@
 use std::{marker::PhantomData, sync::Arc};
 
 use derive_more::Display;
-use futures::{StreamExt, TryStreamExt};
-use thiserror::Error;
+use futures::{future, stream, StreamExt, TryStreamExt};
 
 use crate::{
     address::{
-        traits::{AddressableGet, AddressableList, AddressableSet, AddressableTree, BranchOrLeaf},
-        Address, Addressable, SubAddress,
+        primitive::UniqueRootAddress,
+        traits::{
+            AddressableDelete, AddressableGet, AddressableList, AddressableSet, AddressableTree,
+            BranchOrLeaf,
+        },
+        Address, Addressable, ResolveAddress, SubAddress,
     },
-    store::{Store, StoreResult},
+    delegate_store,
+    store::{Capabilities, RootKind, Store, StoreResult},
 };
 
-#[derive(Display, Debug, Error)]
+#[derive(Display, Debug)]
 pub enum FilterAddressesWrapperError<E> {
     StoreError(E),
     WriteToIgnoredLocation(String),
+    /// Listing an ignored address, see [`FilterAddressesWrapperStore::with_ignored_list_error`].
+    ListIgnoredLocation(String),
     SomeError(String),
 }
 
+/// `StoreError` is exposed as the `source`, and forwarded through `provide`,
+/// so `std::error::request_ref` can reach the wrapped error and anything it provides
+/// (e.g. its `Backtrace`).
+impl<E: std::error::Error + 'static> std::error::Error for FilterAddressesWrapperError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::StoreError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        if let Self::StoreError(e) = self {
+            request.provide_ref::<E>(e);
+            e.provide(request);
+        }
+    }
+}
+
 impl<E> From<E> for FilterAddressesWrapperError<E> {
     fn from(value: E) -> Self {
         Self::StoreError(value)
     }
 }
 
+/// Addresses made of the parts that listings add, one by one: see [`FilterAddedPartWrapperStore`].
+pub trait AddedParts<P> {
+    fn added_parts(&self) -> Vec<P>;
+}
+
+impl<P> AddedParts<P> for UniqueRootAddress {
+    fn added_parts(&self) -> Vec<P> {
+        vec![]
+    }
+}
+
 /// Wrap this over a store to dynamically filter out addresses.
 ///
+/// An ignored address hides its subtree: listing it, or anything under it, yields nothing
+/// (or fails, see [`with_ignored_list_error`](FilterAddressesWrapperStore::with_ignored_list_error)),
+/// so the walks don't go under it. Gets and writes only call the filter with the address itself,
+/// though, so to also hide the children from them, reject them in the filter as well
+/// (e.g. check all the parts of the address, not just the last one).
+///
 #[cfg_attr(not(all(feature = "fs")), doc = "```ignore")]
 #[cfg_attr(all(feature = "fs"), doc = "```")]
 /// use anystore::wrappers::filter_addresses::FilterAddressesWrapperStore;
@@ -74,17 +116,18 @@ impl<E> From<E> for FilterAddressesWrapperError<E> {
 pub struct FilterAddressesWrapperStore<S: Store, K: Clone, F: Fn(K) -> bool> {
     underlying: S,
     filter: Arc<F>,
+    ignored_list_error: bool,
     phantom_key: PhantomData<K>,
 }
 
-impl<S: Store, K: Clone, F: Fn(K) -> bool> Clone for FilterAddressesWrapperStore<S, K, F> {
-    fn clone(&self) -> Self {
-        Self {
-            underlying: self.underlying.clone(),
-            filter: self.filter.clone(),
-            phantom_key: self.phantom_key,
-        }
-    }
+delegate_store! {
+    impl[S: Store, K: Clone, F: Fn(K) -> bool] FilterAddressesWrapperStore<S, K, F> {
+        underlying: S
+    } where [S::RootAddress: Into<K>];
+    Clone(underlying, filter, ignored_list_error, phantom_key);
+    Store(FilterAddressesWrapperError<S::Error>);
+    Addressable;
+    AddressableTree where [LA: ResolveAddress + Into<K>, K: 'a, F: 'a];
 }
 
 impl<S: Store, K: Clone, F: Fn(K) -> bool> FilterAddressesWrapperStore<S, K, F>
@@ -99,10 +142,18 @@ where
         FilterAddressesWrapperStore {
             underlying,
             filter: Arc::new(filter),
+            ignored_list_error: false,
             phantom_key: PhantomData,
         }
     }
 
+    /// Fail with [`FilterAddressesWrapperError::ListIgnoredLocation`] when listing an ignored
+    /// address, instead of yielding nothing.
+    pub fn with_ignored_list_error(mut self) -> Self {
+        self.ignored_list_error = true;
+        self
+    }
+
     pub fn destruct(self) -> S {
         self.underlying
     }
@@ -112,6 +163,22 @@ where
         !(self.filter)(addr.clone().into())
     }
 
+    /// Whether `addr` or any of its ancestors is ignored: a hidden parent hides its subtree.
+    fn should_ignore_subtree<Addr: ResolveAddress + Into<K>>(&self, addr: &Addr) -> bool {
+        let mut addr = addr.clone();
+
+        loop {
+            if self.should_ignore_addr(&addr) {
+                return true;
+            }
+
+            match addr.clone().resolve("..") {
+                Ok(parent) if parent != addr => addr = parent,
+                _ => return false,
+            }
+        }
+    }
+
     fn check_ignore_addr<Addr: Address + Into<K>>(&self, addr: &Addr) -> StoreResult<(), Self> {
         if self.should_ignore_addr(addr) {
             Err(FilterAddressesWrapperError::WriteToIgnoredLocation(
@@ -123,21 +190,6 @@ where
     }
 }
 
-impl<S: Store, K: Clone, F: Fn(K) -> bool> Store for FilterAddressesWrapperStore<S, K, F>
-where
-    S::RootAddress: Into<K>,
-{
-    type Error = FilterAddressesWrapperError<S::Error>;
-
-    type RootAddress = S::RootAddress;
-}
-impl<A: Address, S: Addressable<A>, K: Clone, F: Fn(K) -> bool> Addressable<A>
-    for FilterAddressesWrapperStore<S, K, F>
-where
-    S::RootAddress: Into<K>,
-{
-    type DefaultValue = S::DefaultValue;
-}
 impl<V, A: Address, S: AddressableGet<V, A>, K: Clone, F: Fn(K) -> bool> AddressableGet<V, A>
     for FilterAddressesWrapperStore<S, K, F>
 where
@@ -164,11 +216,23 @@ where
         Ok(self.underlying.set_addr(addr, value).await?)
     }
 }
+impl<A: Address, S: AddressableDelete<A>, K: Clone, F: Fn(K) -> bool> AddressableDelete<A>
+    for FilterAddressesWrapperStore<S, K, F>
+where
+    S::RootAddress: Into<K>,
+    A: Into<K>,
+{
+    async fn delete(&self, addr: &A) -> StoreResult<(), Self> {
+        self.check_ignore_addr(addr)?;
+
+        Ok(self.underlying.delete(addr).await?)
+    }
+}
 
 impl<
         'a,
         Whole: Address,
-        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        A: ResolveAddress + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
         // TODO: is this 'static needed/fine?
         S: AddressableList<'a, A, ItemAddress = Whole> + 'a,
         K: 'a + Clone,
@@ -184,6 +248,17 @@ where
     type ItemAddress = S::ItemAddress;
 
     fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        if self.should_ignore_subtree(addr) {
+            return if self.ignored_list_error {
+                stream::once(future::err(
+                    FilterAddressesWrapperError::ListIgnoredLocation(format!("{addr:?}")),
+                ))
+                .boxed_local()
+            } else {
+                stream::empty().boxed_local()
+            };
+        }
+
         let this = self.clone();
         let addr = addr.clone();
 
@@ -203,14 +278,186 @@ where
     }
 }
 
+impl<S: Store> FilterAddressesWrapperStore<S, S::RootAddress, fn(S::RootAddress) -> bool> {
+    /// Construct a [`FilterAddedPartWrapperStore`]: a variant that applies `filter`
+    /// to the [`AddressableList::AddedAddress`] of each listed item
+    /// (e.g. the file name or the map key) instead of converting the whole address.
+    ///
+    /// Reads and writes check every part of the full address (see [`AddedParts`]):
+    /// reading a filtered address gives `None`, and writing or deleting it fails
+    /// with [`FilterAddressesWrapperError::WriteToIgnoredLocation`].
+    pub fn new_by_added_part<P, G: Fn(&P) -> bool>(
+        underlying: S,
+        filter: G,
+    ) -> FilterAddedPartWrapperStore<S, P, G> {
+        FilterAddedPartWrapperStore {
+            underlying,
+            filter: Arc::new(filter),
+            phantom_part: PhantomData,
+        }
+    }
+}
+
+/// Hides items from listings based on their added part only.
+/// See [`FilterAddressesWrapperStore::new_by_added_part`].
+///
+#[cfg_attr(not(all(feature = "json")), doc = "```ignore")]
+#[cfg_attr(all(feature = "json"), doc = "```")]
+/// use anystore::wrappers::filter_addresses::*;
+/// use anystore::stores::json::{json_value_store, paths::JsonPathPart, JsonValueStoreError};
+/// use anystore::store::StoreEx;
+///
+/// use futures::TryStreamExt;
+/// use serde_json::json;
+///
+/// # tokio_test::block_on(async {
+/// let val = json!({"wow": {"hello": "yes", "_why": 1}, "_ignore": {"haha": 3}});
+///
+/// let store = FilterAddressesWrapperStore::new_by_added_part(
+///     json_value_store(val)?,
+///     |part: &JsonPathPart| !part.to_key().starts_with('_'),
+/// );
+///
+/// let mut all_paths = store
+///     .root()
+///     .walk_tree_recursively()
+///     .map_ok(|v| v.to_string())
+///     .try_collect::<Vec<_>>()
+///     .await?;
+/// all_paths.sort();
+///
+/// assert_eq!(all_paths, vec!["wow", "wow.hello"]);
+///
+/// Ok::<(), FilterAddressesWrapperError<JsonValueStoreError>>(())
+/// # }).unwrap()
+/// ```
+pub struct FilterAddedPartWrapperStore<S: Store, P, F: Fn(&P) -> bool> {
+    underlying: S,
+    filter: Arc<F>,
+    phantom_part: PhantomData<fn(&P)>,
+}
+
+impl<S: Store, P, F: Fn(&P) -> bool> Clone for FilterAddedPartWrapperStore<S, P, F> {
+    fn clone(&self) -> Self {
+        Self {
+            underlying: self.underlying.clone(),
+            filter: self.filter.clone(),
+            phantom_part: self.phantom_part,
+        }
+    }
+}
+
+impl<S: Store, P, F: Fn(&P) -> bool> FilterAddedPartWrapperStore<S, P, F> {
+    pub fn destruct(self) -> S {
+        self.underlying
+    }
+
+    fn should_ignore_addr<A: AddedParts<P>>(&self, addr: &A) -> bool {
+        !addr.added_parts().iter().all(|p| (self.filter)(p))
+    }
+
+    fn check_ignore_addr<A: Address + AddedParts<P>>(&self, addr: &A) -> StoreResult<(), Self> {
+        if self.should_ignore_addr(addr) {
+            Err(FilterAddressesWrapperError::WriteToIgnoredLocation(
+                format!("{addr:?}"),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl<S: Store, P, F: Fn(&P) -> bool> Store for FilterAddedPartWrapperStore<S, P, F> {
+    type Error = FilterAddressesWrapperError<S::Error>;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.underlying.root_kind()
+    }
+
+    async fn flush(&self) -> StoreResult<(), Self> {
+        Ok(self.underlying.flush().await?)
+    }
+}
+impl<A: Address, S: Addressable<A>, P, F: Fn(&P) -> bool> Addressable<A>
+    for FilterAddedPartWrapperStore<S, P, F>
+{
+    type DefaultValue = S::DefaultValue;
+}
+impl<V, A: Address + AddedParts<P>, S: AddressableGet<V, A>, P, F: Fn(&P) -> bool>
+    AddressableGet<V, A> for FilterAddedPartWrapperStore<S, P, F>
+{
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        if self.should_ignore_addr(addr) {
+            Ok(None)
+        } else {
+            Ok(self.underlying.addr_get(addr).await?)
+        }
+    }
+}
+impl<V, A: Address + AddedParts<P>, S: AddressableSet<V, A>, P, F: Fn(&P) -> bool>
+    AddressableSet<V, A> for FilterAddedPartWrapperStore<S, P, F>
+{
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        self.check_ignore_addr(addr)?;
+
+        Ok(self.underlying.set_addr(addr, value).await?)
+    }
+}
+impl<A: Address + AddedParts<P>, S: AddressableDelete<A>, P, F: Fn(&P) -> bool> AddressableDelete<A>
+    for FilterAddedPartWrapperStore<S, P, F>
+{
+    async fn delete(&self, addr: &A) -> StoreResult<(), Self> {
+        self.check_ignore_addr(addr)?;
+
+        Ok(self.underlying.delete(addr).await?)
+    }
+}
+
 impl<
         'a,
-        LA: SubAddress<S::AddedAddress, Output = LA> + Into<K>,
-        IA: Into<K>,
-        S: 'a + Store + AddressableTree<'a, LA, IA>,
-        K: 'a + Clone + From<S::RootAddress>,
-        F: 'a + Fn(K) -> bool,
-    > AddressableTree<'a, LA, IA> for FilterAddressesWrapperStore<S, K, F>
+        Whole: Address,
+        P: Clone + 'static,
+        A: Address + SubAddress<P, Output = Whole>,
+        S: AddressableList<'a, A, AddedAddress = P, ItemAddress = Whole> + 'a,
+        F: 'a + Fn(&P) -> bool,
+    > AddressableList<'a, A> for FilterAddedPartWrapperStore<S, P, F>
+{
+    type AddedAddress = S::AddedAddress;
+
+    type ItemAddress = S::ItemAddress;
+
+    fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        let filter = self.filter.clone();
+
+        self.underlying
+            .list(addr)
+            .filter(move |s| {
+                let r = match s {
+                    Ok((added, _)) => filter(added),
+                    Err(_) => true,
+                };
+
+                async move { r }
+            })
+            .map_err(|e| e.into())
+            .boxed_local()
+    }
+}
+
+impl<
+        'a,
+        LA: SubAddress<P, Output = LA>,
+        IA,
+        P: Clone + 'static,
+        S: 'a + AddressableTree<'a, LA, IA, AddedAddress = P>,
+        F: 'a + Fn(&P) -> bool,
+    > AddressableTree<'a, LA, IA> for FilterAddedPartWrapperStore<S, P, F>
 {
     async fn branch_or_leaf(&self, addr: LA) -> StoreResult<BranchOrLeaf<LA, IA>, Self> {
         Ok(self.underlying.branch_or_leaf(addr).await?)
@@ -221,3 +468,160 @@ impl<
 //     for FilterAddressesWrapperStore<S, K, F>
 // {
 // }
+
+#[cfg(test)]
+mod test {
+    use thiserror::Error;
+
+    use super::FilterAddressesWrapperError;
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn test_list_ignored() -> Result<(), anyhow::Error> {
+        use futures::TryStreamExt;
+        use serde_json::json;
+
+        use super::FilterAddressesWrapperStore;
+        use crate::{
+            store::StoreEx,
+            stores::json::{json_value_store, paths::JsonPath},
+        };
+
+        let val = json!({"_hidden": {"a": {"x": 1}, "b": 2}, "shown": {"c": {"y": 3}}});
+        let visible = |p: JsonPath| {
+            p.last()
+                .map(|p| !p.to_key().starts_with('_'))
+                .unwrap_or(true)
+        };
+
+        let store = FilterAddressesWrapperStore::new(json_value_store(val.clone())?, visible);
+        assert!(store
+            .path("_hidden")?
+            .list()
+            .try_collect::<Vec<_>>()
+            .await?
+            .is_empty());
+        assert_eq!(
+            store
+                .path("shown")?
+                .list()
+                .try_collect::<Vec<_>>()
+                .await?
+                .len(),
+            1
+        );
+
+        // not just the leaf: the whole subtree of a hidden parent
+        assert!(store
+            .path("_hidden.a")?
+            .list()
+            .try_collect::<Vec<_>>()
+            .await?
+            .is_empty());
+        assert!(!store
+            .path("shown.c")?
+            .list()
+            .try_collect::<Vec<_>>()
+            .await?
+            .is_empty());
+
+        let strict = FilterAddressesWrapperStore::new(json_value_store(val)?, visible)
+            .with_ignored_list_error();
+        assert!(matches!(
+            strict.path("_hidden")?.list().try_collect::<Vec<_>>().await,
+            Err(FilterAddressesWrapperError::ListIgnoredLocation(_))
+        ));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn test_delete_ignored() -> Result<(), anyhow::Error> {
+        use serde_json::json;
+
+        use super::FilterAddressesWrapperStore;
+        use crate::{
+            store::StoreEx,
+            stores::json::{json_value_store, paths::JsonPath},
+        };
+
+        let underlying = json_value_store(json!({"_hidden": 1, "shown": 2}))?;
+        let store = FilterAddressesWrapperStore::new(underlying.clone(), |p: JsonPath| {
+            !p.to_string().starts_with('_')
+        });
+
+        assert!(matches!(
+            store.path("_hidden")?.delete().await,
+            Err(FilterAddressesWrapperError::WriteToIgnoredLocation(_))
+        ));
+        assert_eq!(underlying.path("_hidden")?.getv().await?, Some(json!(1)));
+
+        store.path("shown")?.delete().await?;
+        assert_eq!(underlying.root().getv().await?, Some(json!({"_hidden": 1})));
+
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    #[tokio::test]
+    async fn test_added_part_get_set() -> Result<(), anyhow::Error> {
+        use serde_json::json;
+
+        use super::FilterAddressesWrapperStore;
+        use crate::{
+            store::StoreEx,
+            stores::json::{json_value_store, paths::JsonPathPart},
+        };
+
+        let underlying =
+            json_value_store(json!({"_hidden": {"a": 1}, "shown": {"_b": 2, "c": 3}}))?;
+        let store = FilterAddressesWrapperStore::new_by_added_part(
+            underlying.clone(),
+            |part: &JsonPathPart| !part.to_key().starts_with('_'),
+        );
+
+        assert_eq!(store.path("shown.c")?.getv().await?, Some(json!(3)));
+        assert_eq!(store.path("shown._b")?.getv().await?, None);
+        assert_eq!(store.path("_hidden.a")?.getv().await?, None);
+
+        assert!(matches!(
+            store.path("_hidden.a")?.setv(&Some(json!(10))).await,
+            Err(FilterAddressesWrapperError::WriteToIgnoredLocation(_))
+        ));
+        assert!(matches!(
+            store.path("shown._b")?.delete().await,
+            Err(FilterAddressesWrapperError::WriteToIgnoredLocation(_))
+        ));
+        assert_eq!(
+            underlying.root().getv().await?,
+            Some(json!({"_hidden": {"a": 1}, "shown": {"_b": 2, "c": 3}}))
+        );
+
+        store.path("shown.c")?.setv(&Some(json!(4))).await?;
+        assert_eq!(underlying.path("shown.c")?.getv().await?, Some(json!(4)));
+
+        Ok(())
+    }
+
+    #[derive(Debug, Error, PartialEq, Eq)]
+    #[error("InnerError({0})")]
+    struct InnerError(String);
+
+    #[test]
+    fn test_provide() {
+        let err = FilterAddressesWrapperError::StoreError(FilterAddressesWrapperError::StoreError(
+            InnerError("inner".to_owned()),
+        ));
+
+        assert_eq!(
+            std::error::request_ref::<InnerError>(&err),
+            Some(&InnerError("inner".to_owned()))
+        );
+        assert!(std::error::request_ref::<FilterAddressesWrapperError<InnerError>>(&err).is_some());
+
+        let ignored =
+            FilterAddressesWrapperError::<InnerError>::WriteToIgnoredLocation("x".to_owned());
+        assert!(std::error::request_ref::<InnerError>(&ignored).is_none());
+    }
+}
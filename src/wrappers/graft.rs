@@ -0,0 +1,502 @@
+use derive_more::Display;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::{
+    address::{
+        primitive::{Existence, Present, UniqueRootAddress},
+        traits::{AddressableGet, AddressableList, AddressableSet, AddressableTree, BranchOrLeaf},
+        Address, Addressable, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+#[derive(Display, Debug)]
+pub enum GraftError<OE, IE> {
+    OuterError(OE),
+    InnerError(IE),
+    /// A [`GraftValue`] of one store was written to an address of the other one.
+    WrongSide(String),
+}
+
+impl<OE: std::error::Error + 'static, IE: std::error::Error + 'static> std::error::Error
+    for GraftError<OE, IE>
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::OuterError(e) => Some(e),
+            Self::InnerError(e) => Some(e),
+            Self::WrongSide(_) => None,
+        }
+    }
+
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        match self {
+            Self::OuterError(e) => {
+                request.provide_ref::<OE>(e);
+                e.provide(request);
+            }
+            Self::InnerError(e) => {
+                request.provide_ref::<IE>(e);
+                e.provide(request);
+            }
+            Self::WrongSide(_) => {}
+        }
+    }
+}
+
+/// An address in a [`GraftStore`]: an address of the outer store,
+/// or an address of the inner store under the boundary.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GraftAddress<OA, IA> {
+    Outer(OA),
+    Inner(OA, IA),
+}
+
+impl<OA: From<UniqueRootAddress>, IA> From<UniqueRootAddress> for GraftAddress<OA, IA> {
+    fn from(value: UniqueRootAddress) -> Self {
+        GraftAddress::Outer(value.into())
+    }
+}
+
+impl<OA: Address, IA: Address> Address for GraftAddress<OA, IA> {
+    fn own_name(&self) -> String {
+        match self {
+            GraftAddress::Outer(a) => a.own_name(),
+            GraftAddress::Inner(boundary, a) if a.as_parts().is_empty() => boundary.own_name(),
+            GraftAddress::Inner(_, a) => a.own_name(),
+        }
+    }
+
+    fn display_name(&self) -> String {
+        match self {
+            GraftAddress::Outer(a) => a.display_name(),
+            GraftAddress::Inner(boundary, a) if a.as_parts().is_empty() => boundary.display_name(),
+            GraftAddress::Inner(_, a) => a.display_name(),
+        }
+    }
+
+    /// Inner addresses start with the parts of the boundary.
+    fn as_parts(&self) -> Vec<String> {
+        match self {
+            GraftAddress::Outer(a) => a.as_parts(),
+            GraftAddress::Inner(boundary, a) => {
+                let mut parts = boundary.as_parts();
+                parts.extend(a.as_parts());
+                parts
+            }
+        }
+    }
+}
+
+/// The added part of the listed items, see [`AddressableList::AddedAddress`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GraftPart<OP, IP> {
+    Outer(OP),
+    Inner(IP),
+}
+
+/// An inner part added to an outer address crosses the boundary: it's added to the root
+/// of the inner store. An outer part added to an inner address is added to the boundary.
+impl<OA, IA, OP, IP> SubAddress<GraftPart<OP, IP>> for GraftAddress<OA, IA>
+where
+    OA: SubAddress<OP, Output = OA>,
+    IA: SubAddress<IP, Output = IA> + From<UniqueRootAddress>,
+{
+    type Output = Self;
+
+    fn sub(self, sub: GraftPart<OP, IP>) -> Self::Output {
+        match (self, sub) {
+            (GraftAddress::Outer(a), GraftPart::Outer(p)) => GraftAddress::Outer(a.sub(p)),
+            (GraftAddress::Outer(a), GraftPart::Inner(p)) => {
+                GraftAddress::Inner(a, IA::from(UniqueRootAddress).sub(p))
+            }
+            (GraftAddress::Inner(b, a), GraftPart::Inner(p)) => GraftAddress::Inner(b, a.sub(p)),
+            (GraftAddress::Inner(b, _), GraftPart::Outer(p)) => GraftAddress::Outer(b.sub(p)),
+        }
+    }
+}
+
+/// A value of either store.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GraftValue<OV, IV> {
+    Outer(OV),
+    Inner(IV),
+}
+
+/// Compose two stores: `outer` is used everywhere except under `boundary`,
+/// where `inner` takes over, rooted at the boundary.
+///
+/// E.g. the file system for the directories, and a JSON file browsed as a tree of values:
+/// graft a [`LocatedJsonStore`](crate::stores::located::json::LocatedJsonStore) over the file
+/// onto the file's path.
+///
+/// Listing the boundary lists the root of `inner`, and the boundary is always a branch, so
+/// [`walk_tree_recursively`](crate::location::Location::walk_tree_recursively) walks
+/// the outer tree, and continues into the inner one at the boundary.
+/// Reading or writing the boundary itself uses `outer`.
+///
+/// Values are [`GraftValue`]s, as the stores may keep different types.
+///
+#[cfg_attr(not(all(feature = "json", feature = "fs")), doc = "```ignore")]
+#[cfg_attr(all(feature = "json", feature = "fs"), doc = "```")]
+/// use serde_json::json;
+///
+/// use anystore::address::{primitive::UniqueRootAddress, SubAddress};
+/// use anystore::store::StoreEx;
+/// use anystore::stores::{fs::FileSystemStore, json::paths::*, located::json::LocatedJsonStore};
+/// use anystore::wrappers::graft::{GraftAddress, GraftStore, GraftValue};
+///
+/// # tokio_test::block_on(async {
+/// # let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+/// # tokio::fs::create_dir(&dir).await?;
+/// let fs = FileSystemStore::new(dir.clone());
+/// let config = fs.path("config.json")?;
+/// config.set_string(r#"{"port": 8080}"#).await?;
+///
+/// let store = GraftStore::new(fs, config.address.clone(), LocatedJsonStore::new(config.clone()));
+///
+/// let port = store.sub(GraftAddress::Inner(
+///     config.address,
+///     JsonPath::from(UniqueRootAddress).sub(JsonPathPart::Key("port".to_owned())),
+/// ));
+/// assert_eq!(port.getv().await?, Some(GraftValue::Inner(json!(8080))));
+/// # tokio::fs::remove_dir_all(&dir).await?;
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+#[derive(Clone)]
+pub struct GraftStore<O: Store, B: Address, I: Store> {
+    outer: O,
+    boundary: B,
+    inner: I,
+}
+
+impl<O: Store, B: Address, I: Store> GraftStore<O, B, I> {
+    pub fn new(outer: O, boundary: B, inner: I) -> Self {
+        GraftStore {
+            outer,
+            boundary,
+            inner,
+        }
+    }
+
+    pub fn destruct(self) -> (O, B, I) {
+        (self.outer, self.boundary, self.inner)
+    }
+}
+
+impl<O: Store, B: Address, I: Store> Store for GraftStore<O, B, I> {
+    type Error = GraftError<O::Error, I::Error>;
+
+    type RootAddress = GraftAddress<O::RootAddress, I::RootAddress>;
+
+    /// What both stores support.
+    fn capabilities(&self) -> Capabilities {
+        let outer = self.outer.capabilities();
+        let inner = self.inner.capabilities();
+
+        Capabilities {
+            can_write: outer.can_write && inner.can_write,
+            can_list: outer.can_list && inner.can_list,
+            can_delete: outer.can_delete && inner.can_delete,
+            can_query: outer.can_query && inner.can_query,
+            can_watch: outer.can_watch && inner.can_watch,
+        }
+    }
+
+    /// The root is the outer store's.
+    fn root_kind(&self) -> RootKind {
+        self.outer.root_kind()
+    }
+
+    /// Flushes both stores.
+    async fn flush(&self) -> StoreResult<(), Self> {
+        self.outer.flush().await.map_err(GraftError::OuterError)?;
+        self.inner.flush().await.map_err(GraftError::InnerError)
+    }
+}
+
+impl<O: Addressable<B>, B: Address, I: Addressable<IA>, IA: Address>
+    Addressable<GraftAddress<B, IA>> for GraftStore<O, B, I>
+{
+    type DefaultValue = GraftValue<O::DefaultValue, I::DefaultValue>;
+}
+
+impl<OV, IV, O, B, I, IA> AddressableGet<GraftValue<OV, IV>, GraftAddress<B, IA>>
+    for GraftStore<O, B, I>
+where
+    O: AddressableGet<OV, B>,
+    B: Address,
+    I: AddressableGet<IV, IA>,
+    IA: Address,
+{
+    async fn addr_get(
+        &self,
+        addr: &GraftAddress<B, IA>,
+    ) -> StoreResult<Option<GraftValue<OV, IV>>, Self> {
+        match addr {
+            GraftAddress::Outer(a) => Ok(self
+                .outer
+                .addr_get(a)
+                .await
+                .map_err(GraftError::OuterError)?
+                .map(GraftValue::Outer)),
+            GraftAddress::Inner(_, a) => Ok(self
+                .inner
+                .addr_get(a)
+                .await
+                .map_err(GraftError::InnerError)?
+                .map(GraftValue::Inner)),
+        }
+    }
+}
+
+/// Writing a value of one store to an address of the other one fails with
+/// [`GraftError::WrongSide`].
+impl<OV: Clone, IV: Clone, O, B, I, IA> AddressableSet<GraftValue<OV, IV>, GraftAddress<B, IA>>
+    for GraftStore<O, B, I>
+where
+    O: AddressableSet<OV, B>,
+    B: Address,
+    I: AddressableSet<IV, IA>,
+    IA: Address,
+{
+    async fn set_addr(
+        &self,
+        addr: &GraftAddress<B, IA>,
+        value: &Option<GraftValue<OV, IV>>,
+    ) -> StoreResult<(), Self> {
+        match (addr, value) {
+            (GraftAddress::Outer(a), None) => self
+                .outer
+                .set_addr(a, &None)
+                .await
+                .map_err(GraftError::OuterError),
+            (GraftAddress::Outer(a), Some(GraftValue::Outer(v))) => self
+                .outer
+                .set_addr(a, &Some(v.clone()))
+                .await
+                .map_err(GraftError::OuterError),
+            (GraftAddress::Inner(_, a), None) => self
+                .inner
+                .set_addr(a, &None)
+                .await
+                .map_err(GraftError::InnerError),
+            (GraftAddress::Inner(_, a), Some(GraftValue::Inner(v))) => self
+                .inner
+                .set_addr(a, &Some(v.clone()))
+                .await
+                .map_err(GraftError::InnerError),
+            _ => Err(GraftError::WrongSide(format!("{addr:?}"))),
+        }
+    }
+}
+
+impl<O, B, I, IA> AddressableGet<Existence, GraftAddress<B, IA>> for GraftStore<O, B, I>
+where
+    O: AddressableGet<Existence, B>,
+    B: Address,
+    I: AddressableGet<Existence, IA>,
+    IA: Address,
+{
+    async fn addr_get(&self, addr: &GraftAddress<B, IA>) -> StoreResult<Option<Existence>, Self> {
+        match addr {
+            GraftAddress::Outer(a) => self.outer.addr_get(a).await.map_err(GraftError::OuterError),
+            GraftAddress::Inner(_, a) => {
+                self.inner.addr_get(a).await.map_err(GraftError::InnerError)
+            }
+        }
+    }
+}
+
+impl<O, B, I, IA> AddressableGet<Present, GraftAddress<B, IA>> for GraftStore<O, B, I>
+where
+    O: AddressableGet<Existence, B>,
+    B: Address,
+    I: AddressableGet<Existence, IA>,
+    IA: Address,
+{
+    async fn addr_get(&self, addr: &GraftAddress<B, IA>) -> StoreResult<Option<Present>, Self> {
+        let e = AddressableGet::<Existence, _>::addr_get(self, addr).await?;
+
+        Ok(Some(Present(e.is_some())))
+    }
+}
+
+impl<'a, O, B, I, IA> AddressableList<'a, GraftAddress<B, IA>> for GraftStore<O, B, I>
+where
+    O: 'a + AddressableList<'a, B, ItemAddress = B>,
+    B: SubAddress<O::AddedAddress, Output = B>,
+    I: 'a + AddressableList<'a, IA, ItemAddress = IA>,
+    IA: SubAddress<I::AddedAddress, Output = IA> + From<UniqueRootAddress>,
+{
+    type AddedAddress = GraftPart<O::AddedAddress, I::AddedAddress>;
+
+    type ItemAddress = GraftAddress<B, IA>;
+
+    fn list(&self, addr: &GraftAddress<B, IA>) -> Self::ListOfAddressesStream {
+        let (boundary, inner) = match addr {
+            GraftAddress::Outer(a) if *a != self.boundary => {
+                return self
+                    .outer
+                    .list(a)
+                    .map_ok(|(p, a)| (GraftPart::Outer(p), GraftAddress::Outer(a)))
+                    .map_err(GraftError::OuterError)
+                    .boxed_local();
+            }
+            GraftAddress::Outer(a) => (a.clone(), IA::from(UniqueRootAddress)),
+            GraftAddress::Inner(b, a) => (b.clone(), a.clone()),
+        };
+
+        self.inner
+            .list(&inner)
+            .map_ok(move |(p, a)| {
+                (
+                    GraftPart::Inner(p),
+                    GraftAddress::Inner(boundary.clone(), a),
+                )
+            })
+            .map_err(GraftError::InnerError)
+            .boxed_local()
+    }
+}
+
+impl<'a, O, B, I, IA, OL, IL> AddressableTree<'a, GraftAddress<B, IA>, GraftAddress<OL, IL>>
+    for GraftStore<O, B, I>
+where
+    O: 'a + AddressableTree<'a, B, OL>,
+    B: SubAddress<O::AddedAddress, Output = B> + Into<OL>,
+    I: 'a + AddressableTree<'a, IA, IL>,
+    IA: SubAddress<I::AddedAddress, Output = IA> + From<UniqueRootAddress>,
+{
+    async fn branch_or_leaf(
+        &self,
+        addr: GraftAddress<B, IA>,
+    ) -> StoreResult<BranchOrLeaf<GraftAddress<B, IA>, GraftAddress<OL, IL>>, Self> {
+        match addr {
+            GraftAddress::Outer(a) if a == self.boundary => {
+                Ok(BranchOrLeaf::Branch(GraftAddress::Outer(a)))
+            }
+            GraftAddress::Outer(a) => Ok(
+                match self
+                    .outer
+                    .branch_or_leaf(a)
+                    .await
+                    .map_err(GraftError::OuterError)?
+                {
+                    BranchOrLeaf::Branch(b) => BranchOrLeaf::Branch(GraftAddress::Outer(b)),
+                    BranchOrLeaf::Leaf(l) => BranchOrLeaf::Leaf(GraftAddress::Outer(l)),
+                },
+            ),
+            GraftAddress::Inner(boundary, a) => Ok(
+                match self
+                    .inner
+                    .branch_or_leaf(a)
+                    .await
+                    .map_err(GraftError::InnerError)?
+                {
+                    BranchOrLeaf::Branch(b) => {
+                        BranchOrLeaf::Branch(GraftAddress::Inner(boundary, b))
+                    }
+                    BranchOrLeaf::Leaf(l) => {
+                        BranchOrLeaf::Leaf(GraftAddress::Inner(boundary.into(), l))
+                    }
+                },
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+#[cfg(all(feature = "json", feature = "fs"))]
+mod test {
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    use crate::{
+        address::{primitive::Present, traits::BranchOrLeaf, Address},
+        store::StoreEx,
+        stores::{
+            fs::{FileOrDir, FileSystemStore},
+            located::json::LocatedJsonStore,
+        },
+        test_util::temp_dir,
+    };
+
+    use super::{GraftAddress, GraftStore, GraftValue};
+
+    #[tokio::test]
+    async fn test() -> Result<(), anyhow::Error> {
+        let dir = temp_dir();
+        let fs = FileSystemStore::new(dir.clone());
+
+        fs.path("docs")?.ensure_branch().await?;
+        fs.path("docs/readme.txt")?.set_string("hi").await?;
+        let config = fs.path("config.json")?;
+        config
+            .set_string(&json!({"db": {"host": "x"}, "debug": true}).to_string())
+            .await?;
+
+        let store = GraftStore::new(
+            fs,
+            config.address.clone(),
+            LocatedJsonStore::new(config.clone()),
+        );
+
+        let mut walked = store
+            .root()
+            .walk_tree_recursively()
+            .map_ok(|bl| {
+                let parts = match &bl {
+                    BranchOrLeaf::Branch(b) => b.as_parts(),
+                    BranchOrLeaf::Leaf(l) => l.as_parts(),
+                };
+                (bl.unit(), parts)
+            })
+            .try_collect::<Vec<_>>()
+            .await?;
+        walked.sort_by(|a, b| a.1.cmp(&b.1));
+
+        let parts = walked.iter().map(|(_, p)| p.join(" ")).collect::<Vec<_>>();
+        assert_eq!(
+            parts,
+            vec![
+                "config.json",
+                "config.json .db",
+                "config.json .db .host",
+                "config.json .debug",
+                "docs",
+                "docs readme.txt",
+            ]
+        );
+        assert!(walked[0].0.as_branch().is_some());
+        assert!(walked[2].0.as_leaf().is_some());
+
+        let debug = store.sub(GraftAddress::Inner(
+            config.address.clone(),
+            "debug".parse()?,
+        ));
+        assert_eq!(debug.getv().await?, Some(GraftValue::Inner(json!(true))));
+        assert_eq!(debug.get().await?, Some(Present(true)));
+        assert_eq!(
+            store
+                .sub(GraftAddress::Outer("missing".into()))
+                .get()
+                .await?,
+            Some(Present(false))
+        );
+        debug.setv(&Some(GraftValue::Inner(json!(false)))).await?;
+        assert!(debug
+            .setv(&Some(GraftValue::Outer(FileOrDir::Dir)))
+            .await
+            .is_err());
+        assert_eq!(
+            config.get_string().await?.map(|s| s.contains("false")),
+            Some(true)
+        );
+
+        tokio::fs::remove_dir_all(&dir).await?;
+
+        Ok(())
+    }
+}
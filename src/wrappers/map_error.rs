@@ -0,0 +1,255 @@
+use std::{marker::PhantomData, sync::Arc};
+
+use futures::{StreamExt, TryStreamExt};
+
+use crate::{
+    address::{
+        traits::{
+            AddressableGet, AddressableInsert, AddressableList, AddressableQuery, AddressableSet,
+            AddressableTree, BranchOrLeaf,
+        },
+        Address, Addressable, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+/// Wrap this over a store to convert every error it returns with `F`,
+/// e.g. to give stores with different error types a common one.
+///
+#[cfg_attr(not(all(feature = "json")), doc = "```ignore")]
+#[cfg_attr(all(feature = "json"), doc = "```")]
+/// use serde_json::json;
+///
+/// use anystore::store::StoreEx;
+/// use anystore::stores::json::{json_value_store, paths::JsonPathParseError};
+/// use anystore::wrappers::map_error::MapErrorStore;
+///
+/// #[derive(Debug, thiserror::Error)]
+/// #[error("app error: {0}")]
+/// struct AppError(String);
+///
+/// impl From<JsonPathParseError> for AppError {
+///     fn from(e: JsonPathParseError) -> Self {
+///         AppError(e.to_string())
+///     }
+/// }
+///
+/// # tokio_test::block_on(async {
+/// let store = MapErrorStore::new(json_value_store(json!({"a": 1})).unwrap(), |e| {
+///     AppError(e.to_string())
+/// });
+///
+/// assert_eq!(store.path("a")?.getv().await?, Some(json!(1)));
+///
+/// Ok::<(), AppError>(())
+/// # }).unwrap()
+/// ```
+pub struct MapErrorStore<S: Store, E, F: Fn(S::Error) -> E> {
+    underlying: S,
+    map: Arc<F>,
+    phantom: PhantomData<fn() -> E>,
+}
+
+impl<S: Store, E, F: Fn(S::Error) -> E> Clone for MapErrorStore<S, E, F> {
+    fn clone(&self) -> Self {
+        Self {
+            underlying: self.underlying.clone(),
+            map: self.map.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<S: Store, E, F: Fn(S::Error) -> E> MapErrorStore<S, E, F> {
+    pub fn new(underlying: S, map: F) -> Self {
+        MapErrorStore {
+            underlying,
+            map: Arc::new(map),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn destruct(self) -> S {
+        self.underlying
+    }
+}
+
+impl<S: Store, E, F: Fn(S::Error) -> E> Store for MapErrorStore<S, E, F>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    type Error = E;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.underlying.root_kind()
+    }
+
+    async fn flush(&self) -> StoreResult<(), Self> {
+        self.underlying.flush().await.map_err(&*self.map)
+    }
+}
+
+impl<A: Address, S: Addressable<A>, E, F: Fn(S::Error) -> E> Addressable<A>
+    for MapErrorStore<S, E, F>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    type DefaultValue = S::DefaultValue;
+}
+
+impl<V, A: Address, S: AddressableGet<V, A>, E, F: Fn(S::Error) -> E> AddressableGet<V, A>
+    for MapErrorStore<S, E, F>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        self.underlying.addr_get(addr).await.map_err(&*self.map)
+    }
+}
+
+impl<V, A: Address, S: AddressableSet<V, A>, E, F: Fn(S::Error) -> E> AddressableSet<V, A>
+    for MapErrorStore<S, E, F>
+where
+    E: std::error::Error + Send + Sync + 'static,
+{
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        self.underlying
+            .set_addr(addr, value)
+            .await
+            .map_err(&*self.map)
+    }
+}
+
+impl<
+        'a,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableList<'a, A, ItemAddress = Whole> + 'a,
+        E: 'a + std::error::Error + Send + Sync + 'static,
+        F: 'a + Fn(S::Error) -> E,
+    > AddressableList<'a, A> for MapErrorStore<S, E, F>
+{
+    type AddedAddress = S::AddedAddress;
+
+    type ItemAddress = S::ItemAddress;
+
+    fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        let map = self.map.clone();
+
+        self.underlying
+            .list(addr)
+            .map_err(move |e| map(e))
+            .boxed_local()
+    }
+}
+
+impl<
+        'a,
+        Query,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableQuery<'a, Query, A, ItemAddress = Whole> + 'a,
+        E: 'a + std::error::Error + Send + Sync + 'static,
+        F: 'a + Fn(S::Error) -> E,
+    > AddressableQuery<'a, Query, A> for MapErrorStore<S, E, F>
+{
+    fn query(&self, addr: &A, query: Query) -> Self::ListOfAddressesStream {
+        let map = self.map.clone();
+
+        self.underlying
+            .query(addr, query)
+            .map_err(move |e| map(e))
+            .boxed_local()
+    }
+}
+
+impl<
+        'a,
+        Value,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableInsert<'a, Value, A, ItemAddress = Whole> + 'a,
+        E: 'a + std::error::Error + Send + Sync + 'static,
+        F: 'a + Fn(S::Error) -> E,
+    > AddressableInsert<'a, Value, A> for MapErrorStore<S, E, F>
+{
+    fn insert(&self, addr: &A, items: Vec<Value>) -> Self::ListOfAddressesStream {
+        let map = self.map.clone();
+
+        self.underlying
+            .insert(addr, items)
+            .map_err(move |e| map(e))
+            .boxed_local()
+    }
+}
+
+impl<
+        'a,
+        LA: SubAddress<S::AddedAddress, Output = LA>,
+        IA,
+        S: 'a + AddressableTree<'a, LA, IA>,
+        E: 'a + std::error::Error + Send + Sync + 'static,
+        F: 'a + Fn(S::Error) -> E,
+    > AddressableTree<'a, LA, IA> for MapErrorStore<S, E, F>
+{
+    async fn branch_or_leaf(&self, addr: LA) -> StoreResult<BranchOrLeaf<LA, IA>, Self> {
+        self.underlying
+            .branch_or_leaf(addr)
+            .await
+            .map_err(&*self.map)
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use futures::TryStreamExt;
+    use serde_json::json;
+    use thiserror::Error;
+
+    use crate::{
+        store::StoreEx,
+        stores::json::{json_value_store, paths::JsonPathParseError},
+    };
+
+    use super::MapErrorStore;
+
+    #[derive(Debug, Error)]
+    #[error("AppError({0})")]
+    struct AppError(String);
+
+    impl From<JsonPathParseError> for AppError {
+        fn from(e: JsonPathParseError) -> Self {
+            AppError(e.to_string())
+        }
+    }
+
+    #[tokio::test]
+    async fn test() -> Result<(), AppError> {
+        let store = MapErrorStore::new(json_value_store(json!({"a": [1, 2]})).unwrap(), |e| {
+            AppError(e.to_string())
+        });
+
+        let a = store.path("a")?;
+        assert_eq!(a.list().try_collect::<Vec<_>>().await?.len(), 2);
+
+        a.insert(vec![json!(3)]).try_collect::<Vec<_>>().await?;
+        assert_eq!(a.getv().await?, Some(json!([1, 2, 3])));
+
+        let err = store
+            .path("nothing")?
+            .list()
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap_err();
+        assert!(err.0.contains("Path doesn't exist"));
+
+        Ok(())
+    }
+}
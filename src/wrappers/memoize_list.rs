@@ -0,0 +1,294 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{stream, StreamExt, TryStreamExt};
+
+use crate::{
+    address::{
+        traits::{
+            AddressableGet, AddressableInsert, AddressableList, AddressableSet, AddressableTree,
+            BranchOrLeaf,
+        },
+        Address, Addressable, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+struct CachedListing {
+    listed_at: Instant,
+    /// `Vec<(AddedAddress, ItemAddress)>`
+    items: Box<dyn Any>,
+}
+
+type ListingKey = (TypeId, Vec<String>);
+
+/// Wrap this over a slow store to remember its listings, e.g. to walk the same tree
+/// with [`walk_tree_recursively`](crate::location::Location::walk_tree_recursively) repeatedly.
+///
+/// The first `list` of an address is collected into memory, and replayed until `ttl` passes.
+/// Listings aren't cached if they fail.
+///
+/// Writes and inserts made through this wrapper forget the listings they could change:
+/// the ones of the written address, its parents, and its children.
+/// Changes made in any other way (directly in the underlying store, or by another process)
+/// aren't seen until `ttl` passes, or until [`invalidate`](MemoizeListWrapperStore::invalidate)
+/// or [`clear`](MemoizeListWrapperStore::clear) is called.
+///
+/// Values aren't cached, only the addresses.
+///
+#[cfg_attr(not(feature = "json"), doc = "```ignore")]
+#[cfg_attr(feature = "json", doc = "```")]
+/// use std::time::Duration;
+///
+/// use futures::TryStreamExt;
+/// use serde_json::json;
+///
+/// use anystore::stores::json::*;
+/// use anystore::store::StoreEx;
+/// use anystore::wrappers::memoize_list::MemoizeListWrapperStore;
+///
+/// # tokio_test::block_on(async {
+/// let underlying = json_value_store(json!({"a": 1}))?;
+/// let store = MemoizeListWrapperStore::new(underlying.clone(), Duration::from_secs(60));
+///
+/// assert_eq!(store.root().list().try_collect::<Vec<_>>().await?.len(), 1);
+///
+/// // not seen through the wrapper yet
+/// underlying.path("b")?.setv(&Some(json!(2))).await?;
+/// assert_eq!(store.root().list().try_collect::<Vec<_>>().await?.len(), 1);
+///
+/// store.invalidate(&store.root().address);
+/// assert_eq!(store.root().list().try_collect::<Vec<_>>().await?.len(), 2);
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+pub struct MemoizeListWrapperStore<S: Store> {
+    underlying: S,
+    ttl: Duration,
+    listings: Arc<Mutex<HashMap<ListingKey, CachedListing>>>,
+}
+
+impl<S: Store> Clone for MemoizeListWrapperStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            underlying: self.underlying.clone(),
+            ttl: self.ttl,
+            listings: self.listings.clone(),
+        }
+    }
+}
+
+impl<S: Store> MemoizeListWrapperStore<S> {
+    pub fn new(underlying: S, ttl: Duration) -> Self {
+        MemoizeListWrapperStore {
+            underlying,
+            ttl,
+            listings: Default::default(),
+        }
+    }
+
+    pub fn destruct(self) -> S {
+        self.underlying
+    }
+
+    /// Forget the listings of `addr`, its parents, and its children.
+    pub fn invalidate<A: Address>(&self, addr: &A) {
+        let parts = addr.as_parts();
+
+        self.listings
+            .lock()
+            .unwrap()
+            .retain(|(_, listed), _| !(parts.starts_with(listed) || listed.starts_with(&parts)));
+    }
+
+    /// Forget all the listings.
+    pub fn clear(&self) {
+        self.listings.lock().unwrap().clear();
+    }
+
+    fn cached<A: Address, T: Clone + 'static>(&self, addr: &A) -> Option<T> {
+        let key = (TypeId::of::<A>(), addr.as_parts());
+        let mut listings = self.listings.lock().unwrap();
+
+        match listings.get(&key) {
+            Some(cached) if cached.listed_at.elapsed() < self.ttl => {
+                cached.items.downcast_ref::<T>().cloned()
+            }
+            Some(_) => {
+                listings.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn remember<A: Address, T: 'static>(&self, addr: &A, items: T) {
+        self.listings.lock().unwrap().insert(
+            (TypeId::of::<A>(), addr.as_parts()),
+            CachedListing {
+                listed_at: Instant::now(),
+                items: Box::new(items),
+            },
+        );
+    }
+}
+
+impl<S: Store> Store for MemoizeListWrapperStore<S> {
+    type Error = S::Error;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.underlying.root_kind()
+    }
+
+    async fn flush(&self) -> StoreResult<(), Self> {
+        self.underlying.flush().await
+    }
+}
+
+impl<A: Address, S: Addressable<A>> Addressable<A> for MemoizeListWrapperStore<S> {
+    type DefaultValue = S::DefaultValue;
+}
+
+impl<V, A: Address, S: AddressableGet<V, A>> AddressableGet<V, A> for MemoizeListWrapperStore<S> {
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        self.underlying.addr_get(addr).await
+    }
+}
+
+impl<V, A: Address, S: AddressableSet<V, A>> AddressableSet<V, A> for MemoizeListWrapperStore<S> {
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        let result = self.underlying.set_addr(addr, value).await;
+
+        self.invalidate(addr);
+
+        result
+    }
+}
+
+impl<
+        'a,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableList<'a, A, ItemAddress = Whole> + 'a,
+    > AddressableList<'a, A> for MemoizeListWrapperStore<S>
+{
+    type AddedAddress = S::AddedAddress;
+
+    type ItemAddress = S::ItemAddress;
+
+    fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        if let Some(items) = self.cached::<A, Vec<(S::AddedAddress, Whole)>>(addr) {
+            return stream::iter(items.into_iter().map(Ok)).boxed_local();
+        }
+
+        let this = self.clone();
+        let addr = addr.clone();
+        let listing = self.underlying.list(&addr);
+
+        stream::once(async move {
+            let items = listing.try_collect::<Vec<_>>().await?;
+
+            this.remember(&addr, items.clone());
+
+            Ok(stream::iter(items.into_iter().map(Ok)))
+        })
+        .try_flatten()
+        .boxed_local()
+    }
+}
+
+/// Forgets the listings of `addr` (and its parents and children) as the items are inserted.
+impl<
+        'a,
+        V,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableInsert<'a, V, A, ItemAddress = Whole> + 'a,
+    > AddressableInsert<'a, V, A> for MemoizeListWrapperStore<S>
+{
+    fn insert(&self, addr: &A, items: Vec<V>) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        self.invalidate(&addr);
+
+        self.underlying
+            .insert(&addr, items)
+            .inspect(move |_| this.invalidate(&addr))
+            .boxed_local()
+    }
+}
+
+impl<'a, LA: SubAddress<S::AddedAddress, Output = LA>, IA, S: 'a + AddressableTree<'a, LA, IA>>
+    AddressableTree<'a, LA, IA> for MemoizeListWrapperStore<S>
+{
+    async fn branch_or_leaf(&self, addr: LA) -> StoreResult<BranchOrLeaf<LA, IA>, Self> {
+        self.underlying.branch_or_leaf(addr).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use std::time::Duration;
+
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    use crate::{store::StoreEx, stores::json::json_value_store, test_util::ListCountingStore};
+
+    use super::MemoizeListWrapperStore;
+
+    #[tokio::test]
+    async fn test() -> Result<(), anyhow::Error> {
+        let underlying = ListCountingStore::new(json_value_store(
+            json!({"a": {"b": [1, 2], "c": 3}, "d": 4}),
+        )?);
+
+        let store = MemoizeListWrapperStore::new(underlying.clone(), Duration::from_secs(60));
+
+        let walk = || store.root().walk_tree_recursively().try_collect::<Vec<_>>();
+
+        // the root, "a" and "a.b"
+        assert_eq!(walk().await?.len(), 6);
+        assert_eq!(underlying.lists(), 3);
+
+        assert_eq!(walk().await?.len(), 6);
+        assert_eq!(underlying.lists(), 3);
+
+        // relists the root and "a", but not "a.b"
+        store.path("a.e")?.setv(&Some(json!(5))).await?;
+        assert_eq!(walk().await?.len(), 7);
+        assert_eq!(underlying.lists(), 3 + 2);
+
+        store.clear();
+        walk().await?;
+        assert_eq!(underlying.lists(), 3 + 2 + 3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ttl() -> Result<(), anyhow::Error> {
+        let underlying = json_value_store(json!({"a": 1}))?;
+        let store = MemoizeListWrapperStore::new(underlying.clone(), Duration::ZERO);
+
+        store.root().list().try_collect::<Vec<_>>().await?;
+        underlying.path("b")?.setv(&Some(json!(2))).await?;
+
+        assert_eq!(store.root().list().try_collect::<Vec<_>>().await?.len(), 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,356 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use futures::{future, stream, StreamExt};
+
+use crate::{
+    address::{
+        traits::{AddressableGet, AddressableList, AddressableSet, AddressableTree, BranchOrLeaf},
+        Address, Addressable, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+/// Latencies up to `2^BUCKETS` microseconds (about 18 minutes) are told apart.
+const BUCKETS: usize = 30;
+
+/// Counts and latencies of one kind of operation, kept as a histogram with
+/// power-of-two buckets, so the memory used doesn't grow with the number of operations.
+#[derive(Debug, Clone, Default)]
+struct OperationHistogram {
+    count: usize,
+    errors: usize,
+    total: Duration,
+    max: Duration,
+    /// `buckets[i]` counts the latencies below `2^i` microseconds (and at least `2^(i-1)`).
+    buckets: [usize; BUCKETS + 1],
+}
+
+impl OperationHistogram {
+    fn record(&mut self, latency: Duration, failed: bool) {
+        self.count += 1;
+        self.errors += failed as usize;
+        self.total += latency;
+        self.max = self.max.max(latency);
+
+        let micros = latency.as_micros();
+        let bucket = (u128::BITS - micros.leading_zeros()) as usize;
+        self.buckets[bucket.min(BUCKETS)] += 1;
+    }
+
+    /// The upper bound of the bucket with the `p`-th percentile.
+    fn percentile(&self, p: f64) -> Duration {
+        let wanted = ((self.count as f64) * p).ceil().max(1.0) as usize;
+        let mut seen = 0;
+
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= wanted {
+                return Duration::from_micros(1 << i).min(self.max);
+            }
+        }
+
+        self.max
+    }
+
+    fn snapshot(&self) -> OperationMetrics {
+        if self.count == 0 {
+            return OperationMetrics::default();
+        }
+
+        OperationMetrics {
+            count: self.count,
+            errors: self.errors,
+            mean: self.total.div_f64(self.count as f64),
+            p50: self.percentile(0.5),
+            p90: self.percentile(0.9),
+            p99: self.percentile(0.99),
+            max: self.max,
+        }
+    }
+}
+
+/// The numbers for one kind of operation in a [`MetricsSnapshot`].
+///
+/// The percentiles are approximate: they are rounded up to the next power of two
+/// microseconds (but never above `max`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OperationMetrics {
+    pub count: usize,
+    /// How many of the operations failed. A listing fails if any of its items is an error.
+    pub errors: usize,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+    pub max: Duration,
+}
+
+/// The numbers collected by [`MetricsWrapperStore`] so far.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MetricsSnapshot {
+    pub reads: OperationMetrics,
+    pub writes: OperationMetrics,
+    /// A listing takes from the call until its stream ends, or is dropped.
+    pub lists: OperationMetrics,
+}
+
+#[derive(Debug, Default)]
+struct Histograms {
+    reads: OperationHistogram,
+    writes: OperationHistogram,
+    lists: OperationHistogram,
+}
+
+/// Wrap this over a store to count its reads, writes and listings, and measure how long they take,
+/// e.g. to compare backends. The numbers are kept in memory, see [`snapshot`](MetricsWrapperStore::snapshot).
+///
+/// Clones share the numbers.
+///
+#[cfg_attr(not(feature = "json"), doc = "```ignore")]
+#[cfg_attr(feature = "json", doc = "```")]
+/// use futures::TryStreamExt;
+/// use serde_json::json;
+///
+/// use anystore::stores::json::*;
+/// use anystore::store::StoreEx;
+/// use anystore::wrappers::metrics::MetricsWrapperStore;
+///
+/// # tokio_test::block_on(async {
+/// let store = MetricsWrapperStore::new(json_value_store(json!({"a": 1}))?);
+///
+/// store.path("a")?.getv().await?;
+/// store.path("b")?.setv(&Some(json!(2))).await?;
+/// store.root().list().try_collect::<Vec<_>>().await?;
+///
+/// let metrics = store.snapshot();
+/// assert_eq!(
+///     (metrics.reads.count, metrics.writes.count, metrics.lists.count),
+///     (1, 1, 1)
+/// );
+/// assert!(metrics.reads.p50 <= metrics.reads.max);
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+pub struct MetricsWrapperStore<S: Store> {
+    underlying: S,
+    histograms: Arc<Mutex<Histograms>>,
+}
+
+impl<S: Store> Clone for MetricsWrapperStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            underlying: self.underlying.clone(),
+            histograms: self.histograms.clone(),
+        }
+    }
+}
+
+impl<S: Store> MetricsWrapperStore<S> {
+    pub fn new(underlying: S) -> Self {
+        MetricsWrapperStore {
+            underlying,
+            histograms: Default::default(),
+        }
+    }
+
+    pub fn destruct(self) -> S {
+        self.underlying
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        let histograms = self.histograms.lock().unwrap();
+
+        MetricsSnapshot {
+            reads: histograms.reads.snapshot(),
+            writes: histograms.writes.snapshot(),
+            lists: histograms.lists.snapshot(),
+        }
+    }
+
+    /// Forget the numbers collected so far.
+    pub fn reset(&self) {
+        *self.histograms.lock().unwrap() = Default::default();
+    }
+
+    fn record(
+        &self,
+        operation: fn(&mut Histograms) -> &mut OperationHistogram,
+        started: Instant,
+        failed: bool,
+    ) {
+        operation(&mut self.histograms.lock().unwrap()).record(started.elapsed(), failed);
+    }
+}
+
+impl<S: Store> Store for MetricsWrapperStore<S> {
+    type Error = S::Error;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.underlying.root_kind()
+    }
+
+    async fn flush(&self) -> StoreResult<(), Self> {
+        self.underlying.flush().await
+    }
+}
+
+impl<A: Address, S: Addressable<A>> Addressable<A> for MetricsWrapperStore<S> {
+    type DefaultValue = S::DefaultValue;
+}
+
+impl<V, A: Address, S: AddressableGet<V, A>> AddressableGet<V, A> for MetricsWrapperStore<S> {
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        let started = Instant::now();
+        let result = self.underlying.addr_get(addr).await;
+
+        self.record(|h| &mut h.reads, started, result.is_err());
+
+        result
+    }
+}
+
+impl<V, A: Address, S: AddressableSet<V, A>> AddressableSet<V, A> for MetricsWrapperStore<S> {
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        let started = Instant::now();
+        let result = self.underlying.set_addr(addr, value).await;
+
+        self.record(|h| &mut h.writes, started, result.is_err());
+
+        result
+    }
+}
+
+impl<
+        'a,
+        Whole: Address,
+        A: Address + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableList<'a, A, ItemAddress = Whole> + 'a,
+    > AddressableList<'a, A> for MetricsWrapperStore<S>
+{
+    type AddedAddress = S::AddedAddress;
+
+    type ItemAddress = S::ItemAddress;
+
+    fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        let timer = Arc::new(Mutex::new(ListingTimer {
+            store: self.clone(),
+            started: Instant::now(),
+            failed: false,
+            recorded: false,
+        }));
+        let timer2 = timer.clone();
+
+        self.underlying
+            .list(addr)
+            .inspect(move |item| {
+                if item.is_err() {
+                    timer2.lock().unwrap().failed = true;
+                }
+            })
+            .chain(
+                stream::once(async move { timer.lock().unwrap().finish() })
+                    .filter_map(|_| future::ready(None)),
+            )
+            .boxed_local()
+    }
+}
+
+/// Records a listing when its stream ends, or when it's dropped before that.
+struct ListingTimer<S: Store> {
+    store: MetricsWrapperStore<S>,
+    started: Instant,
+    failed: bool,
+    recorded: bool,
+}
+
+impl<S: Store> ListingTimer<S> {
+    fn finish(&mut self) {
+        if !self.recorded {
+            self.recorded = true;
+            self.store
+                .record(|h| &mut h.lists, self.started, self.failed);
+        }
+    }
+}
+
+impl<S: Store> Drop for ListingTimer<S> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+impl<'a, LA: SubAddress<S::AddedAddress, Output = LA>, IA, S: 'a + AddressableTree<'a, LA, IA>>
+    AddressableTree<'a, LA, IA> for MetricsWrapperStore<S>
+{
+    async fn branch_or_leaf(&self, addr: LA) -> StoreResult<BranchOrLeaf<LA, IA>, Self> {
+        self.underlying.branch_or_leaf(addr).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use std::time::Duration;
+
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    use crate::{
+        store::StoreEx,
+        stores::json::json_value_store,
+        wrappers::metrics::{MetricsWrapperStore, OperationHistogram},
+    };
+
+    #[tokio::test]
+    async fn test() -> Result<(), anyhow::Error> {
+        let store = MetricsWrapperStore::new(json_value_store(json!({"a": 1, "b": [1, 2]}))?);
+
+        for _ in 0..3 {
+            store.path("a")?.getv().await?;
+        }
+        assert!(store.path("a.b")?.setv(&Some(json!(2))).await.is_err());
+        store.path("b")?.list().try_collect::<Vec<_>>().await?;
+        assert!(store
+            .path("a")?
+            .list()
+            .try_collect::<Vec<_>>()
+            .await
+            .is_err());
+
+        let metrics = store.snapshot();
+        assert_eq!((metrics.reads.count, metrics.reads.errors), (3, 0));
+        assert_eq!((metrics.writes.count, metrics.writes.errors), (1, 1));
+        assert_eq!((metrics.lists.count, metrics.lists.errors), (2, 1));
+
+        store.reset();
+        assert_eq!(store.snapshot(), Default::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_percentiles() {
+        let mut histogram = OperationHistogram::default();
+
+        for ms in 1..=100 {
+            histogram.record(Duration::from_millis(ms), false);
+        }
+
+        let metrics = histogram.snapshot();
+        assert_eq!(metrics.max, Duration::from_millis(100));
+        // rounded up to a power of two microseconds
+        assert_eq!(metrics.p50, Duration::from_micros(65536));
+        assert_eq!(metrics.p99, Duration::from_millis(100));
+        assert!(metrics.p50 <= metrics.p90 && metrics.p90 <= metrics.p99);
+    }
+}
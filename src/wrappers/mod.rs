@@ -1 +1,13 @@
+#[cfg(feature = "json")]
+pub mod audit;
+#[cfg(feature = "debounce")]
+pub mod debounce;
+pub mod delegate;
+pub mod dryrun;
 pub mod filter_addresses;
+pub mod graft;
+pub mod map_error;
+pub mod memoize_list;
+pub mod metrics;
+pub mod remap;
+pub mod skip_unchanged;
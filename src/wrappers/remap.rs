@@ -0,0 +1,352 @@
+use std::sync::Arc;
+
+use derive_more::Display;
+use futures::{StreamExt, TryStreamExt};
+
+use crate::{
+    address::{
+        primitive::UniqueRootAddress,
+        traits::{AddressableGet, AddressableList, AddressableSet, AddressableTree, BranchOrLeaf},
+        Address, Addressable, SubAddress,
+    },
+    store::{Capabilities, RootKind, Store, StoreResult},
+};
+
+#[derive(Display, Debug)]
+pub enum MapKeyWrapperError<E> {
+    StoreError(E),
+    UnknownKey(String),
+}
+
+impl<E> From<E> for MapKeyWrapperError<E> {
+    fn from(value: E) -> Self {
+        Self::StoreError(value)
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for MapKeyWrapperError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::StoreError(e) => Some(e),
+            _ => None,
+        }
+    }
+
+    fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+        if let Self::StoreError(e) = self {
+            request.provide_ref::<E>(e);
+            e.provide(request);
+        }
+    }
+}
+
+/// Addresses whose last key can be renamed.
+///
+/// If the last part isn't a name (like an array index), or there are no parts, it's left as is.
+pub trait MappableKeys: Sized {
+    /// Returns `None` if `f` rejects the key.
+    fn map_last_key(&self, f: &dyn Fn(&str) -> Option<String>) -> Option<Self>;
+}
+
+impl MappableKeys for UniqueRootAddress {
+    fn map_last_key(&self, _f: &dyn Fn(&str) -> Option<String>) -> Option<Self> {
+        Some(UniqueRootAddress)
+    }
+}
+
+/// Wrap this over a store to rename the keys transparently,
+/// e.g. to expose `timeout` while storing `timeout_ms`.
+///
+/// `to_inner` maps the exposed names to the stored ones, `to_outer` is its inverse.
+/// The last key of the address is mapped on the way in, and the listed names are mapped on the way out.
+/// The keys before it are used as they are, so e.g. `nested.delay` is stored as `nested.delay_ms`.
+///
+/// If a function returns `None` for a key, it's passed through as is, unless `reject_unknown` is set:
+/// then reading or writing it fails with [`MapKeyWrapperError::UnknownKey`], and listing skips it.
+///
+#[cfg_attr(not(feature = "json"), doc = "```ignore")]
+#[cfg_attr(feature = "json", doc = "```")]
+/// use serde_json::json;
+///
+/// use anystore::stores::json::*;
+/// use anystore::wrappers::remap::*;
+/// use anystore::store::StoreEx;
+///
+/// # tokio_test::block_on(async {
+/// let store = json_value_store(json!({"timeout_ms": 300}))?;
+/// let store = MapKeyWrapperStore::new(
+///     store,
+///     |k: &str| (k == "timeout").then(|| "timeout_ms".to_owned()),
+///     |k: &str| (k == "timeout_ms").then(|| "timeout".to_owned()),
+/// );
+///
+/// assert_eq!(store.path("timeout")?.getv().await?, Some(json!(300)));
+///
+/// Ok::<(), MapKeyWrapperError<JsonValueStoreError>>(())
+/// # }).unwrap()
+/// ```
+pub struct MapKeyWrapperStore<S: Store, In, Out>
+where
+    In: Fn(&str) -> Option<String>,
+    Out: Fn(&str) -> Option<String>,
+{
+    pub reject_unknown: bool,
+
+    underlying: S,
+    to_inner: Arc<In>,
+    to_outer: Arc<Out>,
+}
+
+impl<S: Store, In, Out> Clone for MapKeyWrapperStore<S, In, Out>
+where
+    In: Fn(&str) -> Option<String>,
+    Out: Fn(&str) -> Option<String>,
+{
+    fn clone(&self) -> Self {
+        Self {
+            reject_unknown: self.reject_unknown,
+            underlying: self.underlying.clone(),
+            to_inner: self.to_inner.clone(),
+            to_outer: self.to_outer.clone(),
+        }
+    }
+}
+
+impl<S: Store, In, Out> MapKeyWrapperStore<S, In, Out>
+where
+    In: Fn(&str) -> Option<String>,
+    Out: Fn(&str) -> Option<String>,
+{
+    /// Construct a `MapKeyWrapperStore` out of a store and a pair of
+    /// mutually inverse key mappings. Unknown keys are passed through.
+    pub fn new(underlying: S, to_inner: In, to_outer: Out) -> Self {
+        MapKeyWrapperStore {
+            reject_unknown: false,
+            underlying,
+            to_inner: Arc::new(to_inner),
+            to_outer: Arc::new(to_outer),
+        }
+    }
+
+    pub fn destruct(self) -> S {
+        self.underlying
+    }
+
+    fn map_with<A: MappableKeys>(&self, addr: &A, f: &dyn Fn(&str) -> Option<String>) -> Option<A> {
+        addr.map_last_key(&|k| match f(k) {
+            Some(k) => Some(k),
+            None if self.reject_unknown => None,
+            None => Some(k.to_owned()),
+        })
+    }
+
+    fn inner<A: Address + MappableKeys>(&self, addr: &A) -> StoreResult<A, Self> {
+        self.map_with(addr, &*self.to_inner)
+            .ok_or_else(|| MapKeyWrapperError::UnknownKey(format!("{addr:?}")))
+    }
+
+    fn outer<A: Address + MappableKeys>(&self, addr: &A) -> StoreResult<A, Self> {
+        self.map_with(addr, &*self.to_outer)
+            .ok_or_else(|| MapKeyWrapperError::UnknownKey(format!("{addr:?}")))
+    }
+}
+
+impl<S: Store, In, Out> Store for MapKeyWrapperStore<S, In, Out>
+where
+    In: Fn(&str) -> Option<String>,
+    Out: Fn(&str) -> Option<String>,
+{
+    type Error = MapKeyWrapperError<S::Error>;
+
+    type RootAddress = S::RootAddress;
+
+    fn capabilities(&self) -> Capabilities {
+        self.underlying.capabilities()
+    }
+
+    fn root_kind(&self) -> RootKind {
+        self.underlying.root_kind()
+    }
+
+    async fn flush(&self) -> StoreResult<(), Self> {
+        Ok(self.underlying.flush().await?)
+    }
+}
+
+impl<A: Address, S: Addressable<A>, In, Out> Addressable<A> for MapKeyWrapperStore<S, In, Out>
+where
+    In: Fn(&str) -> Option<String>,
+    Out: Fn(&str) -> Option<String>,
+{
+    type DefaultValue = S::DefaultValue;
+}
+
+impl<V, A: Address + MappableKeys, S: AddressableGet<V, A>, In, Out> AddressableGet<V, A>
+    for MapKeyWrapperStore<S, In, Out>
+where
+    In: Fn(&str) -> Option<String>,
+    Out: Fn(&str) -> Option<String>,
+{
+    async fn addr_get(&self, addr: &A) -> StoreResult<Option<V>, Self> {
+        Ok(self.underlying.addr_get(&self.inner(addr)?).await?)
+    }
+}
+
+impl<V, A: Address + MappableKeys, S: AddressableSet<V, A>, In, Out> AddressableSet<V, A>
+    for MapKeyWrapperStore<S, In, Out>
+where
+    In: Fn(&str) -> Option<String>,
+    Out: Fn(&str) -> Option<String>,
+{
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        Ok(self.underlying.set_addr(&self.inner(addr)?, value).await?)
+    }
+}
+
+impl<
+        'a,
+        Whole: Address,
+        A: Address
+            + MappableKeys
+            + SubAddress<<S as AddressableList<'a, A>>::AddedAddress, Output = Whole>,
+        S: AddressableList<'a, A, ItemAddress = Whole> + 'a,
+        In: 'a + Fn(&str) -> Option<String>,
+        Out: 'a + Fn(&str) -> Option<String>,
+    > AddressableList<'a, A> for MapKeyWrapperStore<S, In, Out>
+where
+    S::AddedAddress: MappableKeys,
+{
+    type AddedAddress = S::AddedAddress;
+
+    type ItemAddress = S::ItemAddress;
+
+    fn list(&self, addr: &A) -> Self::ListOfAddressesStream {
+        let this = self.clone();
+        let addr = addr.clone();
+
+        let inner = match self.inner(&addr) {
+            Ok(inner) => inner,
+            Err(e) => return futures::stream::once(async { Err(e) }).boxed_local(),
+        };
+
+        self.underlying
+            .list(&inner)
+            .map_err(MapKeyWrapperError::from)
+            .try_filter_map(move |(added, _)| {
+                // in `reject_unknown` mode, the keys we don't know are simply invisible
+                let r = this
+                    .map_with(&added, &*this.to_outer)
+                    .map(|added| (added.clone(), addr.clone().sub(added)));
+
+                async move { Ok(r) }
+            })
+            .boxed_local()
+    }
+}
+
+impl<
+        'a,
+        LA: SubAddress<S::AddedAddress, Output = LA> + MappableKeys,
+        IA: Address + MappableKeys,
+        S: 'a + Store + AddressableTree<'a, LA, IA>,
+        In: 'a + Fn(&str) -> Option<String>,
+        Out: 'a + Fn(&str) -> Option<String>,
+    > AddressableTree<'a, LA, IA> for MapKeyWrapperStore<S, In, Out>
+where
+    S::AddedAddress: MappableKeys,
+{
+    async fn branch_or_leaf(&self, addr: LA) -> StoreResult<BranchOrLeaf<LA, IA>, Self> {
+        Ok(
+            match self.underlying.branch_or_leaf(self.inner(&addr)?).await? {
+                BranchOrLeaf::Branch(b) => BranchOrLeaf::Branch(self.outer(&b)?),
+                BranchOrLeaf::Leaf(l) => BranchOrLeaf::Leaf(self.outer(&l)?),
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use std::collections::HashSet;
+
+    use futures::TryStreamExt;
+    use serde_json::json;
+
+    use crate::{store::StoreEx, stores::json::json_value_store};
+
+    use super::{MapKeyWrapperError, MapKeyWrapperStore};
+
+    const KEYS: [(&str, &str); 3] = [
+        ("timeout", "timeout_ms"),
+        ("delay", "delay_ms"),
+        ("retries", "retries_ms"),
+    ];
+
+    fn to_inner(k: &str) -> Option<String> {
+        KEYS.iter()
+            .find(|(o, _)| *o == k)
+            .map(|(_, i)| i.to_string())
+    }
+
+    fn to_outer(k: &str) -> Option<String> {
+        KEYS.iter()
+            .find(|(_, i)| *i == k)
+            .map(|(o, _)| o.to_string())
+    }
+
+    #[tokio::test]
+    async fn test() -> Result<(), anyhow::Error> {
+        let json = json_value_store(json!({
+            "timeout_ms": 300,
+            "nested": {"delay_ms": 5},
+            "other": 1
+        }))?;
+        let mut store = MapKeyWrapperStore::new(json.clone(), to_inner, to_outer);
+
+        assert_eq!(store.path("timeout")?.getv().await?, Some(json!(300)));
+        assert_eq!(store.path("nested.delay")?.getv().await?, Some(json!(5)));
+
+        store.path("retries")?.setv(&Some(json!(3))).await?;
+        assert_eq!(json.path("retries_ms")?.getv().await?, Some(json!(3)));
+
+        // only the last key is mapped
+        store.path("nested.timeout")?.setv(&Some(json!(1))).await?;
+        assert_eq!(
+            json.path("nested")?.getv().await?,
+            Some(json!({"delay_ms": 5, "timeout_ms": 1}))
+        );
+        store.path("nested.timeout")?.setv(&None).await?;
+
+        let all_paths = store
+            .root()
+            .walk_tree_recursively()
+            .map_ok(|v| v.to_string())
+            .try_collect::<HashSet<_>>()
+            .await?;
+
+        assert_eq!(
+            all_paths,
+            HashSet::from(
+                ["timeout", "nested", "nested.delay", "other", "retries"].map(|p| p.to_owned())
+            )
+        );
+
+        store.reject_unknown = true;
+
+        let all_paths = store
+            .root()
+            .list()
+            .map_ok(|(k, _)| k.to_string())
+            .try_collect::<HashSet<_>>()
+            .await?;
+        assert!(!all_paths.contains(".other"));
+
+        assert!(matches!(
+            store.path("timeout_ms")?.getv().await,
+            Err(MapKeyWrapperError::UnknownKey(_))
+        ));
+
+        Ok(())
+    }
+}
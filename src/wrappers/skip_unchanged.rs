@@ -0,0 +1,113 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use crate::{
+    address::{
+        traits::{AddressableGet, AddressableSet},
+        Address,
+    },
+    delegate_store,
+    store::{Store, StoreResult},
+};
+
+/// Wrap this over a store where writes are expensive (network, `fsync`) to skip
+/// writing a value equal to the one already there. Every write becomes a read,
+/// and a write only if the value differs. Writing `None` where there's nothing is skipped too.
+///
+/// The read and the write are not atomic: a concurrent change in between can still be overwritten.
+/// Clones share the count of [`skipped_writes`](SkipUnchangedWrapperStore::skipped_writes).
+///
+#[cfg_attr(not(feature = "json"), doc = "```ignore")]
+#[cfg_attr(feature = "json", doc = "```")]
+/// use serde_json::json;
+///
+/// use anystore::stores::json::*;
+/// use anystore::store::StoreEx;
+/// use anystore::wrappers::skip_unchanged::SkipUnchangedWrapperStore;
+///
+/// # tokio_test::block_on(async {
+/// let store = SkipUnchangedWrapperStore::new(json_value_store(json!({"a": 1}))?);
+///
+/// store.path("a")?.setv(&Some(json!(1))).await?;
+/// store.path("a")?.setv(&Some(json!(2))).await?;
+///
+/// assert_eq!(store.skipped_writes(), 1);
+/// assert_eq!(store.path("a")?.getv().await?, Some(json!(2)));
+///
+/// # Ok::<(), anyhow::Error>(())
+/// # }).unwrap()
+/// ```
+pub struct SkipUnchangedWrapperStore<S: Store> {
+    underlying: S,
+    skipped: Arc<AtomicUsize>,
+}
+
+impl<S: Store> SkipUnchangedWrapperStore<S> {
+    pub fn new(underlying: S) -> Self {
+        SkipUnchangedWrapperStore {
+            underlying,
+            skipped: Default::default(),
+        }
+    }
+
+    /// How many writes were skipped so far.
+    pub fn skipped_writes(&self) -> usize {
+        self.skipped.load(Ordering::Relaxed)
+    }
+}
+
+delegate_store! {
+    impl[S: Store] SkipUnchangedWrapperStore<S> { underlying: S };
+    Clone(underlying, skipped);
+    Store;
+    Addressable;
+    AddressableGet;
+    AddressableDelete;
+    AddressableList;
+    AddressableTree;
+}
+
+impl<V: PartialEq, A: Address, S: AddressableSet<V, A> + AddressableGet<V, A>> AddressableSet<V, A>
+    for SkipUnchangedWrapperStore<S>
+{
+    async fn set_addr(&self, addr: &A, value: &Option<V>) -> StoreResult<(), Self> {
+        let current = self.underlying.addr_get(addr).await?;
+
+        if current == *value {
+            self.skipped.fetch_add(1, Ordering::Relaxed);
+            return Ok(());
+        }
+
+        self.underlying.set_addr(addr, value).await
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "json")]
+mod test {
+    use serde_json::json;
+
+    use crate::{
+        store::StoreEx, stores::json::json_value_store, wrappers::metrics::MetricsWrapperStore,
+    };
+
+    use super::SkipUnchangedWrapperStore;
+
+    #[tokio::test]
+    async fn test() -> Result<(), anyhow::Error> {
+        let metrics = MetricsWrapperStore::new(json_value_store(json!({}))?);
+        let store = SkipUnchangedWrapperStore::new(metrics.clone());
+
+        store.path("a.b")?.setv(&Some(json!([1, 2]))).await?;
+        store.path("a.b")?.setv(&Some(json!([1, 2]))).await?;
+        store.path("c")?.setv(&None).await?;
+
+        assert_eq!(metrics.snapshot().writes.count, 1);
+        assert_eq!(store.skipped_writes(), 2);
+        assert_eq!(store.path("a")?.getv().await?, Some(json!({"b": [1, 2]})));
+
+        Ok(())
+    }
+}